@@ -2,6 +2,7 @@
 #undef min
 #undef max
 #include <array>
+#include <cstring>
 #include <filters.hpp>
 #include <memory>
 
@@ -10,8 +11,23 @@ using std::bit_cast;
 
 constexpr auto BAUD_RATE = 115'200UL;
 constexpr auto SAMPLING_FREQUENCY = uint32_t(1000);
-constexpr auto END_TRANSMISSION_MARKER = uint32_t(0x7f'c0'00'00);
+constexpr auto TIME_SYNC_MARKER = uint32_t(0x7f'c0'00'03);
 constexpr auto SYNC = bit_cast<uint32_t>(array{'S', 'Y', 'N', 'C'});
+// Frames the handshake response, so the host can skip bootloader output
+constexpr auto ACK = bit_cast<uint32_t>(array{'A', 'C', 'K', '\0'});
+// Echo samples unfiltered, for the host's round-trip self-test
+constexpr auto PASS_THROUGH = false;
+// Once the handshake is done, both ends exchange frames: this start, the
+// payload's length and the frame's sequence number as `uint16_t`s, the payload,
+// and the CRC-16/CCITT-FALSE of all but the start. An empty payload ends the
+// transmission
+constexpr auto FRAME_START = array<byte, 2>{0xA5, 0x5A};
+constexpr auto MAX_FRAME_PAYLOAD = size_t(2048);
+
+// Time synchronization replies are larger than their requests
+array<byte, MAX_FRAME_PAYLOAD> input;
+array<byte, MAX_FRAME_PAYLOAD * 3 / 2> output;
+auto sequence = uint16_t(0);
 
 auto f = digital_filter<float>::create(num<0.29289322, 0.0, -0.29289322>,
                                        den<1.0, -0.58578644, 0.41421356>);
@@ -26,6 +42,64 @@ template <typename T> T receive() noexcept {
   return result;
 }
 
+uint16_t crc16(uint16_t crc, byte const *bytes, size_t length) noexcept {
+  for (auto i = size_t(0); i < length; ++i) {
+    crc ^= uint16_t(bytes[i]) << 8;
+    for (auto bit = 0; bit < 8; ++bit) {
+      crc = crc & 0x8000 ? (crc << 1) ^ 0x1021 : crc << 1;
+    }
+  }
+
+  return crc;
+}
+
+void transmit_frame(byte const *payload, uint16_t length) noexcept {
+  auto const header = array{length, sequence++};
+  auto const *fields = reinterpret_cast<byte const *>(header.data());
+  auto crc = crc16(0xFFFF, fields, sizeof(header));
+  crc = crc16(crc, payload, length);
+
+  Serial.write(FRAME_START.data(), FRAME_START.size());
+  Serial.write(fields, sizeof(header));
+  Serial.write(payload, length);
+  transmit(crc);
+}
+
+// Receives the next intact frame into `input`, returning its length. Corrupted
+// frames are skipped whole, scanning for the next start past them
+uint16_t receive_frame() noexcept {
+  for (;;) {
+    if (receive<byte>() != FRAME_START[0] ||
+        receive<byte>() != FRAME_START[1]) {
+      continue;
+    }
+
+    auto const header = array{receive<uint16_t>(), receive<uint16_t>()};
+    auto const length = header[0];
+    if (length > input.size()) {
+      continue;
+    }
+
+    Serial.readBytes(input.data(), length);
+    auto crc = crc16(0xFFFF, reinterpret_cast<byte const *>(header.data()),
+                     sizeof(header));
+    if (crc16(crc, input.data(), length) == receive<uint16_t>()) {
+      return length;
+    }
+  }
+}
+
+template <typename T> T load(size_t offset) noexcept {
+  T result;
+  std::memcpy(&result, input.data() + offset, sizeof(T));
+  return result;
+}
+
+template <typename T> void store(size_t &offset, T value) noexcept {
+  std::memcpy(output.data() + offset, &value, sizeof(T));
+  offset += sizeof(T);
+}
+
 void setup() {}
 
 void loop() {
@@ -34,21 +108,40 @@ void loop() {
     delay(150);
   }
 
+  transmit(ACK);
   transmit(SAMPLING_FREQUENCY);
   Serial.flush();
+  sequence = 0;
 
-  for (;;) {
-    if (auto const sample = receive<float>();
-        bit_cast<uint32_t>(sample) != END_TRANSMISSION_MARKER) {
-      transmit(f.filter(sample));
-    } else {
+  for (auto count = uint32_t(0);;) {
+    auto const length = receive_frame();
+    if (length == 0) {
       f.reset();
 
-      transmit(END_TRANSMISSION_MARKER);
+      transmit_frame(nullptr, 0);
       Serial.flush();
       Serial.end();
 
       break;
     }
+
+    // Outputs of the frame's samples, in one frame
+    auto written = size_t(0);
+    for (auto offset = size_t(0); offset + sizeof(float) <= length;
+         offset += sizeof(float)) {
+      if (load<uint32_t>(offset) == TIME_SYNC_MARKER) {
+        offset += sizeof(uint32_t);
+
+        store(written, TIME_SYNC_MARKER);
+        store(written, load<uint32_t>(offset));
+        store(written, count);
+      } else {
+        auto const sample = load<float>(offset);
+        store(written, PASS_THROUGH ? sample : f.filter(sample));
+        ++count;
+      }
+    }
+
+    transmit_frame(output.data(), uint16_t(written));
   }
 }
@@ -1,23 +1,54 @@
-use iced::{executor, Application, Command, Element, Subscription, Theme};
+use iced::{
+    executor, keyboard, subscription,
+    widget::column,
+    window, Application, Command, Element, Event, Subscription, Theme,
+};
 
+mod config;
+use config::Config;
+pub use config::Geometry;
+pub mod crash;
+mod diagnostics;
+use diagnostics::Diagnostics;
 mod filter;
-use filter::Filter;
+pub use filter::headless;
+use filter::{Filter, Transition};
+mod persist;
 mod ports;
+mod profile;
 use ports::Ports;
+mod schema;
+mod signal;
+mod template;
+mod theme;
+
+use crate::perf::Hud;
 
 enum State {
+    Diagnostics(Diagnostics),
     Ports(Ports),
     Filter(Filter),
 }
 
 pub struct OnlineFiltering {
     state: State,
+    /// Performance HUD, toggled with F3
+    hud: Option<Hud>,
+    /// Window as last resized and moved by the user, saved on close
+    geometry: Geometry,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    Diagnostics(diagnostics::Message),
     Ports(ports::Message),
     Filter(filter::Message),
+    ToggleHud,
+    RefreshHud,
+    /// The window is being closed, once running workers are torn down
+    CloseRequested,
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
 }
 
 impl Application for OnlineFiltering {
@@ -27,11 +58,16 @@ impl Application for OnlineFiltering {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        theme::load();
+        let (diagnostics, command) = Diagnostics::new();
+
         (
             Self {
-                state: State::Ports(Ports::new()),
+                state: State::Diagnostics(diagnostics),
+                hud: None,
+                geometry: Geometry::load(),
             },
-            Command::none(),
+            command,
         )
     }
 
@@ -41,17 +77,73 @@ impl Application for OnlineFiltering {
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         match (message, &mut self.state) {
-            (Message::Ports(message), State::Ports(ports)) => {
-                if let Some((filter, command)) = ports.update(message) {
+            (Message::Diagnostics(message), State::Diagnostics(diagnostics)) => {
+                match diagnostics.update(message) {
+                    diagnostics::Transition::Stay(command) => return command,
+                    diagnostics::Transition::Proceed(ports) => self.state = State::Ports(ports),
+                }
+            }
+
+            (Message::Ports(message), State::Ports(ports)) => match ports.update(message) {
+                ports::Transition::Stay(command) => return command,
+                ports::Transition::Filter(filter, command) => {
                     self.state = State::Filter(filter);
                     return command;
                 }
-            }
+            },
 
             (Message::Filter(message), State::Filter(filter)) => {
-                if let Some(ports) = filter.update(message) {
-                    self.state = State::Ports(ports);
+                match filter.update(message, self.geometry) {
+                    Transition::Stay(command) => {
+                        return Command::batch([command, filter.analyze()])
+                    }
+                    Transition::Leave(ports, command) => {
+                        self.state = State::Ports(ports);
+                        return command;
+                    }
+                }
+            }
+
+            // Late results of background work started before returning to port selection
+            (Message::Filter(_), State::Ports(_)) => {}
+
+            // Late results of a self-test still running when filtering started
+            (Message::Ports(_), State::Filter(_)) => {}
+
+            // Late interpreter picks and checks, after proceeding without waiting for them
+            (Message::Diagnostics(_), _) => {}
+
+            (Message::ToggleHud, _) => {
+                self.hud = if self.hud.is_some() {
+                    None
+                } else {
+                    Some(Hud::new())
+                };
+            }
+
+            (Message::RefreshHud, _) => {
+                if let Some(hud) = &mut self.hud {
+                    hud.tick();
+                }
+            }
+
+            // Full screen and the mini monitor are left out, as they only last for a run
+            (Message::WindowResized(..) | Message::WindowMoved(..), State::Filter(filter))
+                if filter.is_resized() => {}
+
+            (Message::WindowResized(width, height), _) => self.geometry.size = (width, height),
+
+            (Message::WindowMoved(x, y), _) => self.geometry.position = Some((x, y)),
+
+            (Message::CloseRequested, state) => {
+                if let State::Filter(filter) = state {
+                    filter.shutdown();
                 }
+
+                let geometry = self.geometry;
+                Config::update(|config| config.window = geometry);
+
+                return window::close();
             }
 
             _ => unreachable!(),
@@ -61,20 +153,54 @@ impl Application for OnlineFiltering {
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
-        match &self.state {
+        let content = match &self.state {
+            State::Diagnostics(diagnostics) => diagnostics.view(),
             State::Ports(ports) => ports.view(),
             State::Filter(filter) => filter.view(),
+        };
+
+        match &self.hud {
+            Some(hud) => column![hud.view(), content].into(),
+            None => content,
         }
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        match &self.state {
+        use iced::time::{self, Duration};
+
+        let state = match &self.state {
+            State::Diagnostics(_) => Subscription::none(),
             State::Ports(ports) => ports.subscription(),
             State::Filter(filter) => filter.subscription(),
-        }
+        };
+
+        let hotkey = subscription::events_with(|event, _| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::F3,
+                ..
+            }) => Some(Message::ToggleHud),
+
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+
+            Event::Window(window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+
+            Event::Window(window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+
+            _ => None,
+        });
+
+        let hud = if self.hud.is_some() {
+            time::every(Duration::from_millis(500)).map(|_| Message::RefreshHud)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([state, hotkey, hud])
     }
 
     fn theme(&self) -> Self::Theme {
-        Theme::Dark
+        theme::theme()
     }
 }
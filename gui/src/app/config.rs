@@ -0,0 +1,275 @@
+use iced::window;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    filter::{Layout, Transmission},
+    persist,
+    schema::Schema,
+    signal::{Overrange, Preset, TimeBase},
+    theme,
+};
+
+/// Layout of the settings file, older ones being read through serde defaults
+///
+/// - 1.0: the original layout
+const SCHEMA: Schema = Schema::new(1, 0);
+
+/// Persisted application settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Most recent first
+    pub recent_sessions: Vec<Session>,
+    /// Exported and replayed files, most recent first
+    pub recent_files: Vec<PathBuf>,
+    /// Validated functions, most recent first
+    pub function_history: Vec<String>,
+    /// Name of the selected device profile
+    pub profile: Option<String>,
+    /// Handling of generated samples outside of the device's full scale
+    pub overrange: Overrange,
+    /// Convention of generated time vectors
+    pub time_base: TimeBase,
+    /// Workspace layouts, by profile name
+    pub layouts: BTreeMap<String, Layout>,
+    /// Interpreter whose environment's packages are used, rather than the embedded Python's
+    pub python: Option<PathBuf>,
+    /// Appearance of the application and its charts
+    pub theme: theme::Settings,
+    /// `host:port` address of the networked device last connected to
+    pub network_address: Option<String>,
+    /// Window as it was last closed
+    pub window: Geometry,
+}
+
+/// Size and position of the window, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Geometry {
+    pub size: (u32, u32),
+    /// Left to the window manager if unknown
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            size: window::Settings::default().size,
+            position: None,
+        }
+    }
+}
+
+impl Geometry {
+    /// Geometry of the window as it was last closed
+    pub fn load() -> Self {
+        Config::load().window
+    }
+
+    pub fn position(self) -> window::Position {
+        self.position.map_or(window::Position::Default, |(x, y)| {
+            window::Position::Specific(x, y)
+        })
+    }
+}
+
+/// Configuration of a previous run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub port: String,
+    pub function: String,
+    /// Built-in signal, described by [`Self::function`]
+    #[serde(default)]
+    pub preset: Option<Preset>,
+    pub stop_time: f32,
+    /// Run length in samples, if entered as such
+    #[serde(default)]
+    pub samples: Option<u32>,
+    pub seed: u32,
+    /// Percentage of the full scale the input's peak was normalized to, if it was
+    #[serde(default)]
+    pub normalization: Option<f32>,
+    /// Name of the device profile
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub overrange: Overrange,
+    #[serde(default)]
+    pub time_base: TimeBase,
+    #[serde(default)]
+    pub transmission: Transmission,
+}
+
+impl Session {
+    /// Labelled settings, in the same order for every session so that they can be compared
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let Transmission {
+            pre_trigger,
+            gap,
+            chunk,
+            repeat,
+            sync,
+            retries,
+            stall,
+            protocol,
+            handshakes,
+            keep_open,
+            ..
+        } = self.transmission;
+
+        vec![
+            ("Port", self.port.clone()),
+            (
+                "Profile",
+                self.profile.clone().unwrap_or_else(|| "unknown".to_owned()),
+            ),
+            ("f(t)", self.function.clone()),
+            (
+                "Length",
+                match self.samples {
+                    Some(samples) => format!("{samples} samples"),
+                    None => format!("{:.2} s", self.stop_time),
+                },
+            ),
+            ("Seed", self.seed.to_string()),
+            (
+                "Normalization",
+                self.normalization
+                    .map_or_else(|| "off".to_owned(), |percent| format!("{percent:.0}%")),
+            ),
+            ("Overrange", format!("{:?}", self.overrange)),
+            (
+                "Time base",
+                format!(
+                    "endpoint {}, centered {}",
+                    self.time_base.endpoint, self.time_base.centered
+                ),
+            ),
+            ("Pre-trigger", format!("{} ms", pre_trigger.as_millis())),
+            (
+                "Gap",
+                format!("{} µs every {chunk} samples", gap.as_micros()),
+            ),
+            ("Repeat", format!("{repeat} times")),
+            ("Clock sync", sync.to_string()),
+            (
+                "Read timeouts",
+                format!(
+                    "{retries} retried, {:.1} s stall limit",
+                    stall.as_secs_f32()
+                ),
+            ),
+            ("Protocol", protocol.to_string()),
+            ("Handshakes", format!("up to {handshakes}")),
+            ("Keep open", keep_open.to_string()),
+        ]
+    }
+}
+
+impl Config {
+    /// Loads the settings file, falling back to defaults if missing or invalid
+    pub fn load() -> Self {
+        let Some(path) = path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                // Still loaded, as settings may legitimately be edited by hand
+                if let Err(e) = persist::verify(contents.as_bytes(), persist::CHECKSUM_COMMENT) {
+                    tracing::warn!("Settings file {} was modified: {e}", path.display());
+                }
+
+                let schema = contents
+                    .lines()
+                    .find_map(|line| line.strip_prefix("# schema="))
+                    .and_then(|schema| schema.parse::<Schema>().ok());
+
+                if let Some(schema) = schema.filter(|schema| !schema.is_readable_by(SCHEMA)) {
+                    tracing::error!(
+                        "Settings file {} has schema {schema}, which this version (schema \
+                         {SCHEMA}) can't read",
+                        path.display()
+                    );
+                    return Self::default();
+                }
+
+                toml::from_str(&contents).unwrap_or_else(|e| {
+                    tracing::error!("Invalid settings file {}: {e}", path.display());
+                    Self::default()
+                })
+            }
+
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+
+            Err(e) => {
+                tracing::error!("Unable to read settings file {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = path() else {
+            tracing::warn!("No configuration directory, settings won't be saved");
+            return;
+        };
+
+        let result = toml::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|contents| {
+                fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+                persist::write_checksummed(&path, |writer| {
+                    writeln!(writer, "# schema={SCHEMA}")?;
+                    writeln!(writer, "# version={}", crate::VERSION)?;
+                    writer.write_all(contents.as_bytes())
+                })
+            });
+
+        if let Err(e) = result {
+            tracing::error!("Unable to save settings to {}: {e}", path.display());
+        }
+    }
+
+    /// Loads, modifies and saves the settings file
+    pub fn update(f: impl FnOnce(&mut Self)) {
+        let mut config = Self::load();
+        f(&mut config);
+        config.save();
+    }
+
+    pub fn remember_session(&mut self, session: Session) {
+        remember(&mut self.recent_sessions, session, crate::RECENT_LIMIT);
+    }
+
+    pub fn remember_file(&mut self, path: PathBuf) {
+        let path = path.canonicalize().unwrap_or(path);
+        remember(&mut self.recent_files, path, crate::RECENT_LIMIT);
+    }
+
+    pub fn remember_function(&mut self, function: String) {
+        let function = function.trim().to_owned();
+        remember(
+            &mut self.function_history,
+            function,
+            crate::FUNCTION_HISTORY_LIMIT,
+        );
+    }
+}
+
+/// Moves `entry` to the front of `entries`, keeping at most `limit`
+fn remember<T: PartialEq>(entries: &mut Vec<T>, entry: T, limit: usize) {
+    entries.retain(|existing| *existing != entry);
+    entries.insert(0, entry);
+    entries.truncate(limit);
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("online-filtering").join("settings.toml"))
+}
@@ -0,0 +1,198 @@
+use parking_lot::Mutex;
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt::{self, Write as _},
+    fs, panic, thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::config;
+
+/// Recent log events, oldest first
+static RECENT: Mutex<VecDeque<String>> = parking_lot::const_mutex(VecDeque::new());
+
+/// Log layer keeping the latest [`crate::CRASH_LOG_LINES`] events for crash reports
+pub struct Recorder;
+
+impl<S: Subscriber> Layer<S> for Recorder {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        let mut line = format!("{timestamp:.3} {} {}:", metadata.level(), metadata.target());
+        event.record(&mut Fields(&mut line));
+
+        let mut recent = crate::perf::lock(&RECENT);
+        if recent.len() == crate::CRASH_LOG_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// Appends an event's fields to a log line
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, " {value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+/// Reports panics in a dialog offering to save a diagnostics bundle, after the default hook
+/// has printed them
+pub fn install() {
+    let default = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        default(info);
+
+        let thread = thread::current();
+        let report = format!(
+            "Online filtering {} on {} {}, thread {}\n\n{info}\n\n{}",
+            crate::VERSION,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            thread.name().unwrap_or("unnamed"),
+            Backtrace::force_capture()
+        );
+
+        offer(&report);
+    }));
+}
+
+/// Shows the crash dialog, saving the diagnostics bundle of `report` if asked to
+fn offer(report: &str) {
+    let save = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title("Online filtering crashed")
+        .set_description(
+            "Something went wrong and the application has to close. Save a diagnostics bundle, \
+             with what happened, the recent log and your settings, to report the problem?",
+        )
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+
+    if !save {
+        return;
+    }
+
+    let Some(path) = rfd::FileDialog::new()
+        .set_title("Save diagnostics")
+        .set_file_name(crate::CRASH_FILENAME)
+        .add_filter("Zip archive", &["zip"])
+        .save_file()
+    else {
+        return;
+    };
+
+    // The panic may have happened while logging
+    let log = RECENT.try_lock().map_or_else(
+        || "Unavailable, the log was in use when the crash happened\n".to_owned(),
+        |recent| recent.iter().map(|line| format!("{line}\n")).collect(),
+    );
+
+    let settings = config::path().map_or_else(
+        || "No configuration directory".to_owned(),
+        |path| {
+            fs::read_to_string(&path)
+                .unwrap_or_else(|e| format!("Unable to read {}: {e}", path.display()))
+        },
+    );
+
+    let bundle = zip(&[
+        ("crash.txt", report.as_bytes()),
+        ("log.txt", log.as_bytes()),
+        ("settings.toml", settings.as_bytes()),
+    ]);
+
+    if let Err(e) = fs::write(&path, bundle) {
+        eprintln!("Unable to save diagnostics to {}: {e}", path.display());
+    }
+}
+
+/// Archives `files`, as `(name, contents)`, uncompressed
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    // Version 2.0, names in UTF-8, stored, dated 1980-01-01
+    const VERSION: u16 = 20;
+    const UTF8: u16 = 1 << 11;
+    const DATE: u16 = (1 << 5) | 1;
+
+    fn u16_le(buffer: &mut Vec<u8>, value: u16) {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32_le(buffer: &mut Vec<u8>, value: u32) {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Fields shared by local and central headers, from the version needed to the extra length
+    fn header(buffer: &mut Vec<u8>, name: &str, contents: &[u8]) {
+        let size = u32::try_from(contents.len()).expect("File too large for zip");
+
+        u16_le(buffer, VERSION);
+        u16_le(buffer, UTF8);
+        u16_le(buffer, 0); // Stored
+        u16_le(buffer, 0); // Time
+        u16_le(buffer, DATE);
+        u32_le(buffer, crc32fast::hash(contents));
+        u32_le(buffer, size); // Compressed
+        u32_le(buffer, size);
+        u16_le(
+            buffer,
+            u16::try_from(name.len()).expect("File name too long for zip"),
+        );
+        u16_le(buffer, 0); // Extra field
+    }
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+
+    for (name, contents) in files {
+        let offset = u32::try_from(archive.len()).expect("Archive too large for zip");
+
+        u32_le(&mut archive, 0x0403_4B50);
+        header(&mut archive, name, contents);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents);
+
+        u32_le(&mut directory, 0x0201_4B50);
+        u16_le(&mut directory, VERSION); // Made by
+        header(&mut directory, name, contents);
+        u16_le(&mut directory, 0); // Comment
+        u16_le(&mut directory, 0); // Disk
+        u16_le(&mut directory, 0); // Internal attributes
+        u32_le(&mut directory, 0); // External attributes
+        u32_le(&mut directory, offset);
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let offset = u32::try_from(archive.len()).expect("Archive too large for zip");
+    let size = u32::try_from(directory.len()).expect("Archive too large for zip");
+    let count = u16::try_from(files.len()).expect("Too many files for zip");
+    archive.append(&mut directory);
+
+    u32_le(&mut archive, 0x0605_4B50);
+    u16_le(&mut archive, 0); // Disk
+    u16_le(&mut archive, 0); // Disk of the central directory
+    u16_le(&mut archive, count); // On this disk
+    u16_le(&mut archive, count);
+    u32_le(&mut archive, size);
+    u32_le(&mut archive, offset);
+    u16_le(&mut archive, 0); // Comment
+
+    archive
+}
@@ -0,0 +1,205 @@
+use iced::{
+    alignment::Horizontal,
+    widget::{button, column, row, text, vertical_space},
+    Alignment, Command, Element, Length,
+};
+use std::path::PathBuf;
+
+use super::{config::Config, ports::Ports, signal::Environment, Message::Diagnostics as App};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Checked(Result<Environment, String>),
+    Retry,
+    ChooseInterpreter,
+    /// Picked interpreter, if any
+    InterpreterPicked(Option<PathBuf>),
+    /// Use the embedded interpreter's own packages
+    EmbeddedInterpreter,
+    /// Proceed without numpy, evaluating functions with built-in equivalents
+    Continue,
+}
+
+/// Outcome of [`Diagnostics::update`]
+pub enum Transition {
+    /// Keep diagnosing, running the given command
+    Stay(Command<super::Message>),
+    /// The environment is ready, proceed to port selection
+    Proceed(Ports),
+}
+
+/// Startup check of the Python environment functions are evaluated in
+pub struct Diagnostics {
+    /// Outcome of the last check, if it completed
+    environment: Option<Result<Environment, String>>,
+    /// Interpreter whose environment's packages are used, if not the embedded one
+    interpreter: Option<PathBuf>,
+}
+
+impl Diagnostics {
+    pub fn new() -> (Self, Command<super::Message>) {
+        let interpreter = Config::load().python;
+
+        (
+            Self {
+                environment: None,
+                interpreter: interpreter.clone(),
+            },
+            check(interpreter),
+        )
+    }
+
+    pub fn update(&mut self, message: Message) -> Transition {
+        match message {
+            Message::Checked(environment) => {
+                match &environment {
+                    Ok(environment) if environment.ready() => {
+                        tracing::info!(
+                            "Python {} with numpy {}",
+                            environment.python,
+                            environment.numpy.as_deref().unwrap_or_default()
+                        );
+                        return Transition::Proceed(Ports::new());
+                    }
+                    Ok(environment) => {
+                        tracing::error!("Python environment not ready: {environment:?}");
+                    }
+                    Err(e) => tracing::error!("Unable to inspect Python: {e}"),
+                }
+
+                self.environment = Some(environment);
+                Transition::Stay(Command::none())
+            }
+
+            Message::Retry => {
+                self.environment = None;
+                Transition::Stay(check(self.interpreter.clone()))
+            }
+
+            Message::ChooseInterpreter => Transition::Stay(
+                Command::perform(pick_interpreter(), Message::InterpreterPicked).map(App),
+            ),
+
+            Message::InterpreterPicked(Some(interpreter)) => {
+                self.interpreter = Some(interpreter.clone());
+                Config::update(|config| config.python = Some(interpreter));
+
+                self.environment = None;
+                Transition::Stay(check(self.interpreter.clone()))
+            }
+
+            Message::InterpreterPicked(None) => Transition::Stay(Command::none()),
+
+            Message::Continue => {
+                tracing::warn!("Continuing without numpy, evaluating with built-in functions");
+                Transition::Proceed(Ports::new())
+            }
+
+            Message::EmbeddedInterpreter => {
+                self.interpreter = None;
+                Config::update(|config| config.python = None);
+
+                self.environment = None;
+                Transition::Stay(check(None))
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, super::Message> {
+        let title = text("Online filtering")
+            .width(Length::Fill)
+            .size(48)
+            .horizontal_alignment(Horizontal::Center);
+
+        let report: Element<'_, Message> = match &self.environment {
+            None => text("Checking the Python environment...").into(),
+
+            Some(Ok(environment)) => {
+                let steps = environment
+                    .remediation()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, step)| text(format!("{}. {step}", i + 1)).into())
+                    .collect();
+
+                column![
+                    text("Python environment").size(24),
+                    text(format!("Python {}", environment.python)),
+                    text(format!("Prefix: {}", environment.prefix)),
+                    text(match &environment.interpreter {
+                        None => "Interpreter: embedded".to_owned(),
+                        Some(Ok(interpreter)) => format!("Interpreter: {}", interpreter.display()),
+                        Some(Err(e)) => format!("Interpreter unusable: {e}"),
+                    }),
+                    text(match &environment.numpy {
+                        Ok(version) => format!("numpy {version}"),
+                        Err(e) => format!("numpy unavailable: {e}"),
+                    }),
+                    text("Remediation").size(24),
+                    column(steps).spacing(5),
+                ]
+                .spacing(10)
+                .into()
+            }
+
+            Some(Err(e)) => column![
+                text("Python environment").size(24),
+                text(format!("The embedded interpreter can't be inspected: {e}")),
+                text("Reinstall Python, making sure its standard library is complete"),
+                text("Or continue with built-in signals and numpy equivalents"),
+            ]
+            .spacing(10)
+            .into(),
+        };
+
+        let mut retry = button("Retry");
+        let mut choose = button("Choose interpreter...");
+        let mut embedded = button("Use embedded");
+        let mut proceed = button("Continue with built-in functions");
+        if self.environment.is_some() {
+            retry = retry.on_press(Message::Retry);
+            choose = choose.on_press(Message::ChooseInterpreter);
+            proceed = proceed.on_press(Message::Continue);
+
+            if self.interpreter.is_some() {
+                embedded = embedded.on_press(Message::EmbeddedInterpreter);
+            }
+        }
+
+        let content: Element<'_, Message> = column![
+            title,
+            report,
+            vertical_space(Length::Fill),
+            row![retry, choose, embedded, proceed]
+                .spacing(10)
+                .align_items(Alignment::Center),
+        ]
+        .padding(15)
+        .spacing(60)
+        .into();
+
+        content.map(App)
+    }
+}
+
+/// Inspects the environment in the background, as importing numpy can be slow
+fn check(interpreter: Option<PathBuf>) -> Command<super::Message> {
+    let future = async move {
+        tokio::task::spawn_blocking(move || {
+            Environment::check(interpreter.as_deref()).map_err(|e| e.to_string())
+        })
+        .await
+        .expect("blocking task ran")
+    };
+
+    Command::perform(future, Message::Checked).map(App)
+}
+
+/// Asks for a Python interpreter, such as an environment's `bin/python` or `python.exe`
+pub async fn pick_interpreter() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Choose a Python interpreter")
+        .pick_file()
+        .await
+        .map(|file| file.path().to_owned())
+}
@@ -1,229 +1,1204 @@
 use iced::{
     alignment::{Horizontal, Vertical},
-    widget::{button, column, row, text},
-    Command, Element, Length, Subscription,
+    keyboard, subscription,
+    widget::{
+        button, column, container, pane_grid, pick_list, progress_bar, row, scrollable, text,
+        PaneGrid,
+    },
+    window, Alignment, Command, Element, Event, Length, Subscription,
 };
-use pyo3::{types::IntoPyDict, PyResult, Python};
+use parking_lot::Mutex;
 use serialport::SerialPort;
 use std::{
+    fmt,
     io::{self, Read, Write},
     mem,
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU16, Ordering},
         Arc,
     },
-    thread::{self, JoinHandle},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
+mod analysis;
+mod bode;
+mod clock;
+mod dsp;
+pub mod emulator;
+mod export;
+mod framing;
 mod graph;
+pub mod headless;
+mod hilbert;
+pub mod import;
+mod impulse;
+mod inspector;
+mod layout;
+mod link;
+mod lock;
+mod log;
+#[cfg(feature = "lsl")]
+mod lsl;
+pub mod osc;
+mod queue;
+mod response;
+mod scalogram;
+pub mod selftest;
+mod snapshot;
+pub mod soak;
+mod spectrum;
+mod storage;
+mod telemetry;
+#[cfg(test)]
+mod tests;
 mod workers;
+mod zplane;
+use analysis::{Analysis, Metrics};
+use bode::Bode;
+pub use framing::Protocol;
 use graph::Graph;
+use impulse::Impulse;
+pub use layout::Layout;
+use layout::Panel;
+use link::Link;
+use lock::PortLock;
+use scalogram::Scalogram;
+use snapshot::Snapshot;
+use storage::Samples;
+pub use workers::Transmission;
+use workers::{Block, Control, Outcome, Supervisor, Throughput};
+use zplane::{Coefficients, ZPlane};
 
 #[cfg(windows)]
 use serialport::COMPort as Serial;
 #[cfg(not(windows))]
 use serialport::TTYPort as Serial;
 
-use super::{ports::Ports, Message::Filter as App};
+use super::{
+    config::{Config, Geometry},
+    ports::Ports,
+    profile::Profile,
+    signal::{self, Excursion, Overrange, Parameters},
+    Message::Filter as App,
+};
 
 #[derive(Debug)]
 pub enum Message {
-    ConnectionFailed,
+    /// Unable to connect, for the given reason
+    ConnectionFailed(String),
+    /// Connects again after failing to, with the same configuration
+    Retry,
+    /// Run read back from an export, to replay
+    Replayed(io::Result<import::Recording>),
     ConnectionEstablished {
-        serial: Serial,
+        link: Link,
+        /// Time and input vectors, evaluated once the sampling interval is known
+        tensors: (Vec<f32>, Vec<f32>),
+        /// Estimated bandwidth of the input, in Hz
+        bandwidth: Option<f32>,
         sampling_interval: f32,
+        output_interval: Option<f32>,
+        block: Option<Block>,
+        /// Number of interleaved output channels
+        channels: usize,
     },
     Graph(graph::Message),
+    Bode(bode::Message),
+    Impulse(impulse::Message),
+    ZPlane(zplane::Message),
+    Log(log::Message),
+    ViewSelected(View),
+    PaneDragged(pane_grid::DragEvent),
+    PaneResized(pane_grid::ResizeEvent),
+    ToggleFullScreen,
+    ExitFullScreen,
+    ToggleMiniMonitor,
+    Analyzed(Metrics),
+    ScalogramComputed(Option<Scalogram>),
     Refresh,
     Finish,
+    /// Suspends or resumes transmission
+    TogglePause,
+    FormatSelected(export::Format),
     Export,
+    /// Chosen export destination, if any
+    ExportPathPicked(Option<PathBuf>),
+    Exported(io::Result<PathBuf>),
+    CancelExport,
+    ExportImage,
+    ExportAnimation,
+    /// Chosen animation destination, if any
+    AnimationPathPicked(Option<PathBuf>),
+    AnimationExported(io::Result<PathBuf>),
+}
+
+/// Plots available once connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    TimeSeries,
+    Response,
+    Impulse,
+    PoleZero,
+    Scalogram,
+    Log,
+    /// Frames decoded last, for debugging firmwares
+    Inspector,
+}
+
+impl View {
+    const ALL: [Self; 7] = [
+        Self::TimeSeries,
+        Self::Response,
+        Self::Impulse,
+        Self::PoleZero,
+        Self::Scalogram,
+        Self::Log,
+        Self::Inspector,
+    ];
+}
+
+impl fmt::Display for View {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TimeSeries => "Time series",
+            Self::Response => "Frequency response",
+            Self::Impulse => "Impulse response",
+            Self::PoleZero => "Pole-zero plot",
+            Self::Scalogram => "Scalogram",
+            Self::Log => "Device log",
+            Self::Inspector => "Protocol inspector",
+        })
+    }
 }
 
 enum State {
-    Connecting {
-        function: String,
-        stop_time: f32,
-    },
+    /// Handshake attempt under way, counted from 1
+    Connecting(Arc<AtomicU16>),
+
+    /// Reading the export at the path, to replay it
+    Loading(PathBuf),
 
     Connected {
         /// Realtime graph
         graph: Graph,
-        /// For signalling cancellation to reader and writer threads
-        cancellation_token: Arc<AtomicBool>,
-        /// Thread handles. [`Option`] used to side-step shared reference issues
-        /// Reference: https://stackoverflow.com/questions/57670145/how-to-store-joinhandle-of-a-thread-to-close-it-later
-        receiver: Option<JoinHandle<()>>,
-        transmitter: Option<JoinHandle<()>>,
+        /// Frequency response plot
+        bode: Bode,
+        /// Impulse response plot
+        impulse: Impulse,
+        /// Pole-zero plot
+        zplane: ZPlane,
+        /// Wavelet transform of the output, computed when first shown
+        scalogram: scalogram::Status,
+        /// Plot being shown
+        view: View,
+        sampling_interval: f32,
+        /// Interval between outputs of decimating or interpolating devices
+        output_interval: Option<f32>,
+        /// For pausing transmission
+        control: Arc<Control>,
+        /// Receiver, transmitter and threads streaming the outputs to [`Outlets`], until the
+        /// run ends
+        workers: Supervisor,
+        /// How reception ended, unknown for replayed runs
+        outcome: Outcome,
+        /// Background metric computation
+        analysis: Analysis,
+        /// Progress of the export in flight
+        export: Option<Arc<export::Progress>>,
+        /// Path of the last successful export
+        exported: Option<PathBuf>,
+        /// Generated samples outside of the device's full scale
+        excursion: Option<Excursion>,
+        /// Values of the function over one cycle, before normalization and clamping
+        generated: Arc<Samples>,
+        /// Gain applied to normalize the input, if it was
+        gain: Option<f32>,
+        /// Where the run comes from, when it ended being filled in on export
+        provenance: export::Provenance,
+        /// Bandwidth of the input, when beyond the Nyquist frequency
+        aliased: Option<f32>,
+        /// Effective transmission rate
+        throughput: Arc<Throughput>,
+        /// Internal state reported by the device
+        telemetry: telemetry::Shared,
+        /// Messages logged by the device
+        log: log::Log,
+        log_panel: log::Panel,
+        /// Time synchronization with the device
+        clock: clock::Shared,
+        /// Frames decoded last
+        inspector: inspector::Shared,
+        /// Arrangement of the workspace's panels
+        panes: pane_grid::State<Panel>,
+        /// Only the chart is shown, filling the screen
+        full_screen: bool,
+        /// Only the trace and progress are shown, in a small window kept above others
+        mini_monitor: bool,
     },
 
-    Errored,
+    /// Failed, with the reason shown
+    Errored(String),
+}
+
+/// Device to filter with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// Microcontroller on the named serial port
+    Serial(String),
+    /// Device bridged over the network, or a simulator, at a `host:port` address
+    Tcp(String),
+    Emulated(emulator::Device, emulator::Artifacts),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serial(port_name) => f.write_str(port_name),
+            Self::Tcp(address) => write!(f, "tcp://{address}"),
+            Self::Emulated(device, _) => write!(f, "Emulated {device}"),
+        }
+    }
 }
 
 pub struct Filter {
+    /// Device to connect to, again on retry, or [`None`] when replaying
+    target: Option<Target>,
+    /// Parameters of the generated input signal
+    parameters: Parameters,
+    /// How the input is transmitted
+    transmission: Transmission,
+    /// Capabilities of the device
+    profile: Profile,
+    /// Coefficients the device was designed with, if known
+    designed: Option<Coefficients>,
+    /// Front end of the emulated device, if any
+    front_end: Option<emulator::Artifacts>,
+    /// Name of the device, identifying its streams
+    source: String,
+    /// Plot shown once connected
+    initial_view: View,
+    /// Live streams of the outputs
+    outlets: Outlets,
+    /// Format of exported outputs
+    format: export::Format,
     state: State,
 }
 
+/// Live streams of the outputs to other applications
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outlets {
+    /// Open Sound Control publishing, if enabled
+    pub osc: Option<osc::Settings>,
+    /// Lab Streaming Layer outlet, requiring the `lsl` feature
+    pub lsl: bool,
+}
+
+/// Outcome of [`Filter::update`]
+pub enum Transition {
+    /// Keep filtering, running the given command
+    Stay(Command<super::Message>),
+    /// Return to port selection, running the given command
+    Leave(Ports, Command<super::Message>),
+}
+
 impl Filter {
     pub fn new(
-        port_name: String,
-        function: String,
-        stop_time: f32,
+        target: Target,
+        parameters: Parameters,
+        transmission: Transmission,
+        profile: Profile,
+        initial_view: View,
+        outlets: Outlets,
     ) -> (Self, Command<super::Message>) {
-        let future = async move {
-            tokio::task::spawn_blocking(move || -> io::Result<_> {
-                use std::time::Duration;
-                let mut serial = serialport::new(port_name, crate::BAUD_RATE)
-                    .timeout(Duration::from_secs(3))
-                    .open_native()?;
+        let source = target.to_string();
+        let designed = match &target {
+            Target::Emulated(device, _) => Some(device.coefficients()),
+            Target::Serial(_) | Target::Tcp(_) => None,
+        };
+        let front_end = match &target {
+            Target::Emulated(_, artifacts) => Some(*artifacts),
+            Target::Serial(_) | Target::Tcp(_) => None,
+        };
+
+        let mut filter = Self {
+            target: Some(target),
+            parameters,
+            transmission,
+            profile,
+            designed,
+            front_end,
+            source,
+            initial_view,
+            outlets,
+            format: export::Format::Json,
+            state: State::Connecting(Arc::default()),
+        };
+        let command = filter.establish();
 
-                thread::sleep(Duration::from_millis(250));
-                serial.write_all(crate::SYN)?;
+        (filter, command)
+    }
 
-                let mut buf = [0u8; mem::size_of::<u32>()];
-                serial.read_exact(&mut buf)?;
+    /// Connects to [`Self::target`] and evaluates the input, in the background
+    fn establish(&mut self) -> Command<super::Message> {
+        let attempt = Arc::new(AtomicU16::new(0));
+        self.state = State::Connecting(attempt.clone());
+
+        let target = self.target.clone().expect("target to connect to");
+        let device_profile = self.profile.clone();
+        let generated = self.parameters.clone();
+        let Transmission {
+            handshakes,
+            keep_open,
+            protocol,
+            ..
+        } = self.transmission;
+        let future = async move {
+            tokio::task::spawn_blocking(move || -> io::Result<_> {
+                let (announcement, link) = connect(
+                    target,
+                    &device_profile,
+                    generated.seed,
+                    handshakes,
+                    keep_open,
+                    protocol,
+                    &attempt,
+                )?;
 
-                let sampling_frequency = u32::from_le_bytes(buf);
-                tracing::info!("Sampling frequency: {sampling_frequency}");
+                tracing::info!("Seed: {}", generated.seed);
+                let sampling_interval = (announcement.input as f32).recip();
+                let tensors = signal::evaluate(&generated, sampling_interval)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                let bandwidth = signal::estimate_bandwidth(&generated, sampling_interval)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
 
-                serial.set_timeout(Duration::from_millis(100))?;
-                Ok((sampling_frequency, serial))
+                Ok((announcement, link, tensors, bandwidth))
             })
             .await
             .expect("blocking task ran")
         };
 
+        Command::perform(future, |result| match result {
+            Ok((
+                Announcement {
+                    input,
+                    output,
+                    block,
+                    channels,
+                },
+                link,
+                tensors,
+                bandwidth,
+            )) => Message::ConnectionEstablished {
+                link,
+                tensors,
+                bandwidth,
+                sampling_interval: (input as f32).recip(),
+                output_interval: (output != input).then(|| (output as f32).recip()),
+                block: block.map(|block| Block::new(block, input, output)),
+                channels: usize::try_from(channels).expect("channel count fits in memory"),
+            },
+
+            Err(e) => {
+                tracing::error!("Unable to establish connection: {e}");
+                Message::ConnectionFailed(e.to_string())
+            }
+        })
+        .map(App)
+    }
+
+    /// Replays the run exported to `path`, shown like a finished one
+    ///
+    /// Files without a sampling rate are assumed to be sampled at the `profile`'s maximum
+    pub fn replay(path: PathBuf, profile: Profile) -> (Self, Command<super::Message>) {
+        let task = import::load(path.clone(), profile.max_sampling_rate as f32);
+
         (
             Self {
-                state: State::Connecting {
-                    function,
-                    stop_time,
+                target: None,
+                parameters: Parameters {
+                    function: String::new(),
+                    preset: None,
+                    stop_time: 0.0,
+                    samples: None,
+                    seed: 0,
+                    overrange: Overrange::default(),
+                    normalization: None,
+                    time_base: signal::TimeBase::default(),
                 },
+                transmission: Transmission::default(),
+                profile,
+                designed: None,
+                front_end: None,
+                source: path.display().to_string(),
+                initial_view: View::TimeSeries,
+                outlets: Outlets::default(),
+                format: export::Format::Json,
+                state: State::Loading(path),
             },
-            Command::perform(future, |result| match result {
-                Ok((sampling_frequency, serial)) => Message::ConnectionEstablished {
-                    serial,
-                    sampling_interval: (sampling_frequency as f32).recip(),
-                },
-
-                Err(e) => {
-                    tracing::error!("Unable to establish connection: {e}");
-                    Message::ConnectionFailed
-                }
-            })
-            .map(App),
+            Command::perform(task, |result| App(Message::Replayed(result))),
         )
     }
 }
 
 impl Filter {
-    pub fn update(&mut self, message: Message) -> Option<Ports> {
+    /// Handles `message`, with `geometry` being the window as last resized and moved by the user
+    pub fn update(&mut self, message: Message, geometry: Geometry) -> Transition {
         match message {
-            Message::ConnectionFailed => {
-                self.state = State::Errored;
-                None
+            Message::ConnectionFailed(reason) => {
+                self.state = State::Errored(format!("Unable to connect: {reason}"));
+                Transition::Stay(Command::none())
+            }
+
+            Message::Retry => {
+                tracing::info!("Retrying connection to {}", self.source);
+                Transition::Stay(self.establish())
+            }
+
+            Message::Replayed(Err(e)) => {
+                tracing::error!("Unable to replay: {e}");
+                self.state = State::Errored("Unable to replay...".to_owned());
+                Transition::Stay(Command::none())
+            }
+
+            Message::Replayed(Ok(recording)) => {
+                let State::Loading(path) = &self.state else {
+                    unreachable!()
+                };
+
+                let import::Recording {
+                    schema,
+                    version,
+                    metadata,
+                    log,
+                    pre_trigger,
+                    generated,
+                    input,
+                    output,
+                } = recording;
+
+                tracing::info!(
+                    "Replaying {}: {} inputs and {} outputs at {} Hz",
+                    path.display(),
+                    input.len(),
+                    output.len(),
+                    metadata.sampling_rate
+                );
+                Config::update(|config| config.remember_file(path.clone()));
+
+                if schema < export::SCHEMA {
+                    tracing::info!(
+                        "Converted from schema {schema} to {}, exported by version {}",
+                        export::SCHEMA,
+                        version.as_deref().unwrap_or("unknown")
+                    );
+                }
+
+                self.parameters.seed = metadata.seed;
+                self.parameters.time_base = metadata.time_base;
+
+                let sampling_interval = metadata.sampling_rate.recip();
+                let output_interval = metadata.output_rate.map(f32::recip);
+                let start = if metadata.time_base.centered {
+                    0.5 * sampling_interval
+                } else {
+                    0.0
+                };
+
+                let throughput = Arc::new(Throughput::replayed(input.len(), output.len()));
+                let output = Samples::from(output);
+                let mut graph = Graph::new(
+                    start,
+                    sampling_interval,
+                    Arc::new(Samples::from(input)),
+                    Snapshot::complete(&output),
+                    Arc::new(Mutex::new(output)),
+                    workers::Channels::default(),
+                    Arc::new(Mutex::new(pre_trigger)),
+                    metadata.cycle,
+                    output_interval,
+                    &self.profile,
+                );
+                graph.update(graph::Message::SwitchMode);
+
+                self.state = State::Connected {
+                    graph,
+                    bode: Bode::new(sampling_interval),
+                    impulse: Impulse::new(),
+                    zplane: ZPlane::new(false),
+                    scalogram: scalogram::Status::Idle,
+                    view: self.initial_view,
+                    sampling_interval,
+                    output_interval,
+                    control: Arc::default(),
+                    workers: Supervisor::default(),
+                    outcome: Outcome::default(),
+                    analysis: Analysis::new(),
+                    export: None,
+                    exported: None,
+                    excursion: None,
+                    generated: Arc::new(Samples::from(generated)),
+                    gain: metadata.gain,
+                    provenance: metadata.provenance,
+                    aliased: None,
+                    throughput,
+                    telemetry: telemetry::Shared::default(),
+                    log: Arc::new(Mutex::new(log)),
+                    log_panel: log::Panel::new(),
+                    clock: clock::Shared::default(),
+                    inspector: inspector::Shared::default(),
+                    panes: Config::load()
+                        .layouts
+                        .get(&self.profile.name)
+                        .cloned()
+                        .unwrap_or_default()
+                        .panes(),
+                    full_screen: false,
+                    mini_monitor: false,
+                };
+
+                Transition::Stay(Command::none())
             }
 
             Message::ConnectionEstablished {
-                serial: rx,
+                link: Link { tx, rx },
+                tensors: (time, mut unfiltered_data),
+                bandwidth,
                 sampling_interval,
+                output_interval,
+                block,
+                channels,
             } => {
-                let tx = rx.try_clone_native().expect("successful split");
-                let (time, unfiltered_data) = self.compute_tensors(sampling_interval);
-                let unfiltered_data = Arc::new(unfiltered_data);
+                // Only the start of the time vector is kept, times being regularly spaced
+                let start = time.first().copied().unwrap_or_default();
+                let generated = Arc::new(Samples::from(unfiltered_data.clone()));
+                let gain = self.parameters.normalization.map(|percent| {
+                    signal::normalize(
+                        &mut unfiltered_data,
+                        percent / 100.0 * self.profile.full_scale,
+                    )
+                });
+                let excursion = signal::limit(
+                    &mut unfiltered_data,
+                    self.profile.full_scale,
+                    self.parameters.overrange,
+                );
+                let nyquist = 0.5 / sampling_interval;
+                let aliased = bandwidth.filter(|&bandwidth| bandwidth > nyquist);
+                if let Some(bandwidth) = aliased {
+                    tracing::warn!(
+                        "Input bandwidth of {bandwidth} Hz exceeds the Nyquist frequency of \
+                         {nyquist} Hz"
+                    );
+                }
+
+                let cycle = unfiltered_data.len();
+                signal::repeat(&mut unfiltered_data, self.transmission.repeat);
+                if let Some(block) = block {
+                    signal::pad(&mut unfiltered_data, block.input);
+                }
+                let unfiltered_data = Arc::new(Samples::from(unfiltered_data));
+                self.transmission.block = block;
 
                 let total_samples = unfiltered_data.len();
-                let cancellation_token = Arc::new(AtomicBool::new(false));
+                let control = Arc::new(Control::default());
+                let mut workers = Supervisor::default();
+
+                let started = Arc::new(AtomicBool::new(false));
+                let throughput = Arc::new(Throughput::default());
+                let epoch = Instant::now();
+                let provenance = export::Provenance {
+                    function: Some(self.parameters.function.clone()),
+                    stop_time: Some(self.parameters.stop_time),
+                    device: Some(self.source.clone()),
+                    started: Some(export::Provenance::timestamp(SystemTime::now())),
+                    ended: None,
+                };
 
-                let (filtered_data, receiver) = workers::spawn_receiver(rx, total_samples);
+                let workers::Received {
+                    output: filtered_data,
+                    snapshot,
+                    channels,
+                    pre_trigger,
+                    telemetry,
+                    log,
+                    clock,
+                    inspector,
+                    outcome,
+                } = workers::spawn_receiver(
+                    &mut workers,
+                    rx,
+                    total_samples,
+                    channels,
+                    self.transmission,
+                    Arc::clone(&control),
+                    Arc::clone(&started),
+                    Arc::clone(&throughput),
+                    epoch,
+                );
 
-                let transmitter = workers::spawn_transmitter(
+                workers::spawn_transmitter(
+                    &mut workers,
                     tx,
                     Arc::clone(&unfiltered_data),
-                    Arc::clone(&cancellation_token),
+                    self.transmission,
+                    Arc::clone(&control),
+                    started,
+                    Arc::clone(&throughput),
+                    epoch,
                 );
 
+                if let Some(settings) = &self.outlets.osc {
+                    if let Err(e) = osc::spawn(&mut workers, settings, Arc::clone(&filtered_data)) {
+                        tracing::error!("Unable to publish over OSC: {e}");
+                    }
+                }
+
+                #[cfg(feature = "lsl")]
+                if self.outlets.lsl {
+                    lsl::spawn(
+                        &mut workers,
+                        self.source.clone(),
+                        self.profile.unit.clone(),
+                        output_interval.unwrap_or(sampling_interval),
+                        Arc::clone(&filtered_data),
+                    );
+                }
+
                 self.state = State::Connected {
-                    graph: Graph::new(time, unfiltered_data, filtered_data),
-                    cancellation_token,
-                    receiver: Some(receiver),
-                    transmitter: Some(transmitter),
+                    graph: Graph::new(
+                        start,
+                        sampling_interval,
+                        unfiltered_data,
+                        snapshot,
+                        filtered_data,
+                        channels,
+                        pre_trigger,
+                        cycle,
+                        output_interval,
+                        &self.profile,
+                    ),
+                    bode: Bode::new(sampling_interval),
+                    impulse: Impulse::new(),
+                    zplane: ZPlane::new(self.designed.is_some()),
+                    scalogram: scalogram::Status::Idle,
+                    view: self.initial_view,
+                    sampling_interval,
+                    output_interval,
+                    control,
+                    workers,
+                    outcome,
+                    analysis: Analysis::new(),
+                    export: None,
+                    exported: None,
+                    excursion,
+                    generated,
+                    gain,
+                    provenance,
+                    aliased,
+                    throughput,
+                    telemetry,
+                    log,
+                    log_panel: log::Panel::new(),
+                    clock,
+                    inspector,
+                    panes: Config::load()
+                        .layouts
+                        .get(&self.profile.name)
+                        .cloned()
+                        .unwrap_or_default()
+                        .panes(),
+                    full_screen: false,
+                    mini_monitor: false,
                 };
 
-                None
+                Transition::Stay(Command::none())
             }
 
-            Message::Finish => match &mut self.state {
-                State::Connected {
-                    cancellation_token,
-                    receiver,
-                    transmitter,
-                    ..
-                } => {
-                    // Signal termination
-                    cancellation_token.store(true, Ordering::Relaxed);
+            Message::Finish => match &self.state {
+                State::Connected { .. } | State::Errored(_) => {
+                    self.shutdown();
+                    Transition::Leave(Ports::new(), self.restore_window(geometry))
+                }
 
-                    // Wait for threads to terminate
-                    if let Some(transmitter) = transmitter.take() {
-                        transmitter.join().expect("successful tx termination");
-                    }
+                State::Connecting(_) | State::Loading(_) => unreachable!(),
+            },
 
-                    if let Some(receiver) = receiver.take() {
-                        receiver.join().expect("successful rx termination");
-                    }
+            Message::TogglePause => {
+                let State::Connected { control, .. } = &self.state else {
+                    unreachable!();
+                };
 
-                    Some(Ports::new())
+                if control.is_paused() {
+                    tracing::info!("Resuming transmission");
+                    control.resume();
+                } else {
+                    tracing::info!("Pausing transmission");
+                    control.pause();
                 }
 
-                State::Errored => Some(Ports::new()),
+                Transition::Stay(Command::none())
+            }
 
-                State::Connecting { .. } => unreachable!(),
-            },
+            Message::ToggleFullScreen => {
+                let State::Connected { full_screen, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                *full_screen = !*full_screen;
+                Transition::Stay(window::change_mode(if *full_screen {
+                    window::Mode::Fullscreen
+                } else {
+                    window::Mode::Windowed
+                }))
+            }
+
+            Message::ExitFullScreen => {
+                let State::Connected { full_screen, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                if !mem::replace(full_screen, false) {
+                    return Transition::Stay(Command::none());
+                }
+
+                Transition::Stay(window::change_mode(window::Mode::Windowed))
+            }
+
+            Message::ToggleMiniMonitor => {
+                let State::Connected { mini_monitor, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                *mini_monitor = !*mini_monitor;
+                let (width, height) = if *mini_monitor {
+                    crate::MINI_MONITOR_SIZE
+                } else {
+                    geometry.size
+                };
+
+                Transition::Stay(Command::batch([
+                    window::resize(width, height),
+                    window::change_always_on_top(*mini_monitor),
+                ]))
+            }
+
+            Message::PaneDragged(pane_grid::DragEvent::Dropped { pane, target }) => {
+                let State::Connected { panes, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                panes.swap(&pane, &target);
+                Transition::Stay(Command::none())
+            }
+
+            Message::PaneDragged(_) => Transition::Stay(Command::none()),
+
+            Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
+                let State::Connected { panes, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                panes.resize(&split, ratio);
+                Transition::Stay(Command::none())
+            }
 
             Message::Graph(message) => {
-                let State::Connected { graph, .. } = &mut self.state else {
+                let State::Connected {
+                    graph, analysis, ..
+                } = &mut self.state
+                else {
                     unreachable!();
                 };
 
                 graph.update(message);
-                None
+
+                // Metrics are computed over detrended series too
+                if matches!(
+                    message,
+                    graph::Message::InputDetrendSelected(_)
+                        | graph::Message::OutputDetrendSelected(_)
+                ) {
+                    analysis.invalidate();
+                }
+                Transition::Stay(Command::none())
+            }
+
+            Message::Bode(message) => {
+                let State::Connected { bode, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                bode.update(message);
+                Transition::Stay(Command::none())
+            }
+
+            Message::Impulse(message) => {
+                let State::Connected {
+                    impulse, analysis, ..
+                } = &mut self.state
+                else {
+                    unreachable!();
+                };
+
+                let spectra = analysis
+                    .metrics
+                    .as_ref()
+                    .and_then(|metrics| metrics.spectra.as_ref());
+
+                impulse.update(message, spectra);
+                Transition::Stay(Command::none())
             }
 
+            Message::ZPlane(message) => {
+                let State::Connected { zplane, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                zplane.update(message);
+                Transition::Stay(Command::none())
+            }
+
+            Message::Log(message) => {
+                let State::Connected { log_panel, .. } = &mut self.state else {
+                    unreachable!();
+                };
+
+                log_panel.update(message);
+                Transition::Stay(Command::none())
+            }
+
+            Message::ViewSelected(selected) => {
+                let State::Connected {
+                    graph,
+                    view,
+                    scalogram,
+                    sampling_interval,
+                    output_interval,
+                    ..
+                } = &mut self.state
+                else {
+                    unreachable!();
+                };
+
+                *view = selected;
+
+                if selected != View::Scalogram || !matches!(scalogram, scalogram::Status::Idle) {
+                    return Transition::Stay(Command::none());
+                }
+
+                *scalogram = scalogram::Status::Pending;
+                let (_, output) = graph.data();
+
+                Transition::Stay(Command::perform(
+                    scalogram::compute(output, output_interval.unwrap_or(*sampling_interval)),
+                    |scalogram| App(Message::ScalogramComputed(scalogram)),
+                ))
+            }
+
+            // Background results arriving once the run errored are dropped
+            Message::ScalogramComputed(computed) => {
+                let State::Connected { scalogram, .. } = &mut self.state else {
+                    return Transition::Stay(Command::none());
+                };
+
+                *scalogram = scalogram::Status::Done(computed);
+                Transition::Stay(Command::none())
+            }
+
+            Message::Analyzed(metrics) => {
+                let State::Connected {
+                    graph, analysis, ..
+                } = &mut self.state
+                else {
+                    return Transition::Stay(Command::none());
+                };
+
+                if let Some(lag) = metrics.lag {
+                    graph.set_lag(lag);
+                }
+                graph.set_trends(metrics.input.trend, metrics.output.trend);
+
+                analysis.finish(metrics);
+                Transition::Stay(Command::none())
+            }
+
+            // While connecting, only redraws the attempt counter
             Message::Refresh => {
-                let State::Connected { receiver, transmitter, .. } = &mut self.state else {
-                    unreachable!()
+                let State::Connected {
+                    graph,
+                    workers,
+                    outcome,
+                    ..
+                } = &mut self.state
+                else {
+                    return Transition::Stay(Command::none());
                 };
 
-                if receiver.as_ref().map_or(false, JoinHandle::is_finished) {
-                    let tx = transmitter.take().expect("tx thread");
-                    let rx = receiver.take().expect("rx thread");
+                // Checked first, so that the last outputs are drawn once it has
+                let ended = workers.has_ended(workers::RECEIVER);
+                graph.refresh();
 
-                    rx.join().expect("successful rx termination");
-                    tx.join().expect("successful tx termination");
+                // However the receiver ended, the other workers are torn down with it
+                if ended {
+                    let ending = *crate::perf::lock(outcome);
+                    self.shutdown();
+
+                    // The partial run of a dead device, or of a full disk, isn't shown as a
+                    // finished one
+                    if let Some(ending) = ending.filter(|ending| ending.error().is_some()) {
+                        let command = self.restore_window(geometry);
+                        self.state = State::Errored(ending.to_string());
+                        return Transition::Stay(command);
+                    }
                 }
 
-                None
+                Transition::Stay(Command::none())
             }
 
-            Message::Export => match &self.state {
+            Message::Export => Transition::Stay(
+                Command::perform(export::pick_path(self.format), Message::ExportPathPicked)
+                    .map(App),
+            ),
+
+            Message::ExportPathPicked(None) => Transition::Stay(Command::none()),
+
+            Message::ExportPathPicked(Some(path)) => match &mut self.state {
                 State::Connected {
                     graph,
-                    receiver: None,
-                    transmitter: None,
+                    workers,
+                    export,
+                    generated,
+                    gain,
+                    provenance,
+                    throughput,
+                    log,
+                    sampling_interval,
+                    output_interval,
                     ..
-                } => {
-                    match graph.export() {
-                        Ok(()) => tracing::info!("Exported outputs"),
-                        Err(e) => tracing::error!("Unable to export: {e}"),
+                } if !workers.is_running() => {
+                    let (transmitted, output) = graph.data();
+                    let progress = Arc::new(export::Progress::default());
+                    let task = export::export(
+                        path,
+                        self.format,
+                        export::Metadata {
+                            seed: self.parameters.seed,
+                            gain: *gain,
+                            time_base: self.parameters.time_base,
+                            cycle: graph.cycle(),
+                            sampling_rate: sampling_interval.recip(),
+                            output_rate: output_interval.map(f32::recip),
+                            provenance: export::Provenance {
+                                ended: provenance.ended.or_else(|| {
+                                    provenance
+                                        .started
+                                        .map(|started| started + throughput.elapsed().as_secs_f64())
+                                }),
+                                ..provenance.clone()
+                            },
+                        },
+                        graph.pre_trigger(),
+                        crate::perf::lock(log).clone(),
+                        export::Inputs {
+                            generated: Arc::clone(generated),
+                            transmitted,
+                            sent: throughput.sent(),
+                            front_end: self
+                                .front_end
+                                .map(|artifacts| (artifacts, self.profile.full_scale)),
+                        },
+                        output,
+                        Arc::clone(&progress),
+                    );
+
+                    *export = Some(progress);
+                    Transition::Stay(Command::perform(task, |result| {
+                        App(Message::Exported(result))
+                    }))
+                }
+
+                _ => unreachable!(),
+            },
+
+            Message::Exported(result) => {
+                let path = match result {
+                    Ok(path) => {
+                        tracing::info!("Exported outputs to {}", path.display());
+                        Config::update(|config| config.remember_file(path.clone()));
+                        Some(path)
+                    }
+                    Err(e) => {
+                        tracing::error!("Unable to export: {e}");
+                        None
                     }
+                };
+
+                // The file is written even if the run errored meanwhile
+                if let State::Connected {
+                    export, exported, ..
+                } = &mut self.state
+                {
+                    *exported = path.or(exported.take());
+                    *export = None;
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::CancelExport => {
+                if let State::Connected {
+                    export: Some(progress),
+                    ..
+                } = &self.state
+                {
+                    progress.cancel();
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::FormatSelected(format) => {
+                self.format = format;
+                Transition::Stay(Command::none())
+            }
+
+            Message::ExportImage => {
+                let State::Connected { graph, .. } = &self.state else {
+                    unreachable!()
+                };
+
+                match graph.export_image(crate::IMAGE_FILENAME) {
+                    Ok(()) => tracing::info!("Exported image"),
+                    Err(e) => tracing::error!("Unable to export image: {e}"),
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::ExportAnimation => Transition::Stay(
+                Command::perform(export::pick_animation_path(), Message::AnimationPathPicked)
+                    .map(App),
+            ),
 
-                    None
+            Message::AnimationPathPicked(None) => Transition::Stay(Command::none()),
+
+            Message::AnimationPathPicked(Some(path)) => match &mut self.state {
+                State::Connected {
+                    graph,
+                    workers,
+                    export,
+                    ..
+                } if !workers.is_running() => {
+                    let progress = Arc::new(export::Progress::default());
+                    let task = export::animate(graph.detached(), path, Arc::clone(&progress));
+
+                    *export = Some(progress);
+                    Transition::Stay(Command::perform(task, |result| {
+                        App(Message::AnimationExported(result))
+                    }))
                 }
 
                 _ => unreachable!(),
             },
+
+            Message::AnimationExported(result) => {
+                match result {
+                    Ok(path) => tracing::info!("Exported animation to {}", path.display()),
+                    Err(e) => tracing::error!("Unable to export animation: {e}"),
+                }
+
+                if let State::Connected { export, .. } = &mut self.state {
+                    *export = None;
+                }
+
+                Transition::Stay(Command::none())
+            }
         }
     }
 
+    /// Saves the workspace layout and tears down the run's workers, waiting for them to end
+    ///
+    /// Outputs in flight are drained until the device's EOT, for up to
+    /// [`crate::EOT_DRAIN_TIMEOUT`]. Shared by finishing and closing the window
+    pub fn shutdown(&mut self) {
+        let State::Connected { workers, panes, .. } = &mut self.state else {
+            return;
+        };
+
+        let layout = Layout::of(panes);
+        let profile = self.profile.name.clone();
+        Config::update(|config| {
+            config.layouts.insert(profile, layout);
+        });
+
+        workers.shutdown();
+    }
+
+    /// Leaves full screen and the mini monitor, restoring the window to `geometry`'s size if
+    /// shrunk, as the run's workspace is left
+    fn restore_window(&self, geometry: Geometry) -> Command<super::Message> {
+        let State::Connected {
+            full_screen,
+            mini_monitor,
+            ..
+        } = self.state
+        else {
+            return Command::none();
+        };
+
+        let mut commands = Vec::new();
+        if full_screen {
+            commands.push(window::change_mode(window::Mode::Windowed));
+        }
+
+        if mini_monitor {
+            let (width, height) = geometry.size;
+            commands.push(window::resize(width, height));
+            commands.push(window::change_always_on_top(false));
+        }
+
+        Command::batch(commands)
+    }
+
+    /// Starts a background metric computation, if one is due
+    pub fn analyze(&mut self) -> Command<super::Message> {
+        let State::Connected {
+            graph,
+            workers,
+            analysis,
+            sampling_interval,
+            output_interval,
+            ..
+        } = &mut self.state
+        else {
+            return Command::none();
+        };
+
+        let (input, output) = graph.data();
+        let available = output.lock().len();
+        let finished = !workers.is_running();
+
+        if !analysis.due(available, finished) {
+            return Command::none();
+        }
+
+        analysis.start();
+        Command::perform(
+            analysis::compute(
+                input,
+                output,
+                *sampling_interval,
+                *output_interval,
+                finished,
+                graph.detrending(),
+            ),
+            |metrics| App(Message::Analyzed(metrics)),
+        )
+    }
+
     pub fn view(&self) -> Element<'_, super::Message> {
         let title = text("Online filtering")
             .width(Length::Fill)
@@ -232,7 +1207,32 @@ impl Filter {
 
         let content: Element<'_, Message> = match &self.state {
             State::Connected {
-                graph, receiver, ..
+                graph,
+                bode,
+                impulse,
+                zplane,
+                scalogram,
+                view,
+                control,
+                workers,
+                outcome,
+                analysis,
+                export,
+                exported,
+                excursion,
+                aliased,
+                sampling_interval,
+                output_interval,
+                throughput,
+                telemetry,
+                log,
+                log_panel,
+                clock,
+                inspector,
+                panes,
+                full_screen,
+                mini_monitor,
+                ..
             } => {
                 let finish = button(
                     text("Ok")
@@ -242,9 +1242,166 @@ impl Filter {
                 .width(Length::Fill)
                 .on_press(Message::Finish);
 
-                let graph = graph.view();
+                let output = *output_interval;
+                let output_interval = output_interval.unwrap_or(*sampling_interval);
+                let spectra = analysis
+                    .metrics
+                    .as_ref()
+                    .and_then(|metrics| metrics.spectra.as_ref());
+
+                let dropped = graph.dropped();
+                let crosshair = graph.crosshair();
 
-                if receiver.is_none() {
+                let plot = || match view {
+                    View::TimeSeries => {
+                        match telemetry::view(telemetry, dropped, output_interval, crosshair) {
+                            Some(telemetry) => column![
+                                container(graph.view()).height(Length::FillPortion(3)),
+                                container(telemetry).height(Length::FillPortion(1)),
+                            ]
+                            .spacing(10)
+                            .into(),
+                            None => graph.view(),
+                        }
+                    }
+                    View::Response => bode.view(spectra).map(Message::Bode),
+                    View::Impulse => impulse.view(spectra).map(Message::Impulse),
+                    View::PoleZero => {
+                        let coefficients = match zplane.source() {
+                            zplane::Source::Designed => self.designed.clone(),
+                            zplane::Source::Estimated => spectra
+                                .map(|spectra| Coefficients::fir(&impulse.coefficients(spectra))),
+                        };
+
+                        zplane.view(coefficients.as_ref()).map(Message::ZPlane)
+                    }
+                    View::Scalogram => scalogram.view(),
+                    View::Log => log_panel.view(log, output_interval).map(Message::Log),
+                    View::Inspector => inspector::view(inspector),
+                };
+
+                let stats = || {
+                    let mut stats = column![metrics(
+                        analysis.metrics.as_ref(),
+                        &self.profile.unit,
+                        *sampling_interval,
+                    )]
+                    .spacing(10);
+
+                    if let Some(Excursion {
+                        count,
+                        peak,
+                        clamped,
+                    }) = excursion
+                    {
+                        let Profile {
+                            full_scale, unit, ..
+                        } = &self.profile;
+
+                        stats = stats.push(text(format!(
+                            "Warning: {count} input samples exceed ±{full_scale} {unit} \
+                             (peak {peak:.3} {unit}){}",
+                            if *clamped { ", clamped" } else { "" }
+                        )));
+                    }
+
+                    if let Some(bandwidth) = aliased {
+                        stats = stats.push(text(format!(
+                            "Warning: the input extends to {bandwidth:.1} Hz, beyond the \
+                             Nyquist frequency of {:.1} Hz, and is aliased",
+                            0.5 / sampling_interval
+                        )));
+                    }
+
+                    if let Some(synchronization) = synchronization(&crate::perf::lock(clock)) {
+                        stats = stats.push(synchronization);
+                    }
+
+                    if let Some(rate) = throughput.rate() {
+                        let device = match output {
+                            None => format!("{:.0} samples/s", sampling_interval.recip()),
+                            Some(output) => format!(
+                                "{:.0} samples/s in, {:.0} samples/s out",
+                                sampling_interval.recip(),
+                                output.recip()
+                            ),
+                        };
+
+                        stats = stats.push(text(format!(
+                            "Transmitted at {rate:.0} samples/s (device: {device})"
+                        )));
+                    }
+
+                    if let Some(block) = self.transmission.block {
+                        let (input, _) = graph.data();
+                        stats = stats.push(text(format!(
+                            "Block {} of {} processed ({} inputs, {} outputs each)",
+                            throughput.blocks(),
+                            input.len() / block.input,
+                            block.input,
+                            block.output
+                        )));
+                    }
+
+                    scrollable(stats).into()
+                };
+
+                let chart = PaneGrid::new(panes, |_, panel, _| {
+                    let content: Element<'_, Message> = match panel {
+                        Panel::Chart => plot(),
+                        Panel::Stats => stats(),
+                        Panel::Log => log_panel.view(log, output_interval).map(Message::Log),
+                        Panel::Spectrum => bode.view(spectra).map(Message::Bode),
+                    };
+
+                    pane_grid::Content::new(content)
+                        .title_bar(pane_grid::TitleBar::new(text(panel.to_string())).padding(5))
+                })
+                .height(Length::Fill)
+                .spacing(10)
+                .on_drag(Message::PaneDragged)
+                .on_resize(10, Message::PaneResized);
+
+                let expand = button("Full screen").on_press(Message::ToggleFullScreen);
+                let shrink = button("Mini monitor").on_press(Message::ToggleMiniMonitor);
+
+                // Outputs of the whole run
+                let expected = || {
+                    let (input, _) = graph.data();
+                    (input.len() as f32 * *sampling_interval / output_interval).round() as usize
+                };
+
+                if *mini_monitor {
+                    let fraction = (throughput.received() as f32 / expected() as f32).min(1.0);
+
+                    let restore = button("Restore").on_press(Message::ToggleMiniMonitor);
+
+                    column![
+                        graph.trace(),
+                        row![
+                            progress_bar(0.0..=1.0, fraction),
+                            text(format!("{:.0}%", fraction * 100.0)),
+                            restore
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                    ]
+                } else if *full_screen {
+                    column![plot()]
+                } else if let Some(progress) = export {
+                    let fraction = progress.fraction();
+                    let cancel = button("Cancel").on_press(Message::CancelExport);
+
+                    let dialog = row![
+                        text(format!("Exporting... {:.0}%", fraction * 100.0)),
+                        progress_bar(0.0..=1.0, fraction),
+                        cancel,
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center);
+
+                    column![title, chart, dialog]
+                } else if !workers.is_running() {
                     let export = button(
                         text("Export")
                             .width(Length::Fill)
@@ -253,25 +1410,102 @@ impl Filter {
                     .width(Length::Fill)
                     .on_press(Message::Export);
 
+                    let format = pick_list(
+                        &export::Format::ALL[..],
+                        Some(self.format),
+                        Message::FormatSelected,
+                    );
+
+                    let export_image = button(
+                        text("Export image")
+                            .width(Length::Fill)
+                            .horizontal_alignment(Horizontal::Center),
+                    )
+                    .width(Length::Fill)
+                    .on_press(Message::ExportImage);
+
+                    let export_animation = button(
+                        text("Export animation")
+                            .width(Length::Fill)
+                            .horizontal_alignment(Horizontal::Center),
+                    )
+                    .width(Length::Fill)
+                    .on_press(Message::ExportAnimation);
+
+                    let resolution = graph.resolution_picker().map(Message::Graph);
+
+                    let view = pick_list(&View::ALL[..], Some(*view), Message::ViewSelected);
+
+                    let controls = column![row![
+                        finish,
+                        export,
+                        format,
+                        export_image,
+                        export_animation,
+                        resolution,
+                        view,
+                        expand,
+                        shrink
+                    ]
+                    .spacing(10)
+                    .width(Length::Fill)];
+
+                    let controls = match *crate::perf::lock(outcome) {
+                        Some(ending) => controls
+                            .push(text(format!("Reception: {ending}")))
+                            .spacing(10),
+                        None => controls,
+                    };
+
+                    let controls = match exported {
+                        Some(path) => controls
+                            .push(text(format!("Exported to {}", path.display())))
+                            .spacing(10),
+                        None => controls,
+                    };
+
+                    column![title, chart, controls]
+                } else {
+                    let pause = button(if control.is_paused() {
+                        "Resume"
+                    } else {
+                        "Pause"
+                    })
+                    .on_press(Message::TogglePause);
+
+                    let expected = expected();
+                    let received = throughput.received().min(expected);
+                    let remaining = throughput
+                        .remaining(expected)
+                        .map_or_else(String::new, |remaining| {
+                            format!(", {} s remaining", remaining.as_secs_f32().ceil())
+                        });
+
+                    let progress = row![
+                        progress_bar(0.0..=1.0, received as f32 / expected.max(1) as f32),
+                        text(format!("{received} / {expected} samples{remaining}")),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center);
+
                     column![
                         title,
-                        graph,
-                        row![finish, export].spacing(10).width(Length::Fill)
+                        chart,
+                        progress,
+                        row![finish, pause, expand, shrink].spacing(10)
                     ]
-                } else {
-                    column![title, graph, finish]
                 }
             }
 
-            State::Errored => {
-                let message = text("Unable to connect...")
+            State::Errored(reason) => {
+                let message = text(reason)
                     .size(32)
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .vertical_alignment(Vertical::Center)
                     .horizontal_alignment(Horizontal::Center);
 
-                let button = button(
+                let ok = button(
                     text("Ok")
                         .width(Length::Fill)
                         .horizontal_alignment(Horizontal::Center),
@@ -279,11 +1513,43 @@ impl Filter {
                 .width(Length::Fill)
                 .on_press(Message::Finish);
 
-                column![title, message, button]
+                // Replays have nothing to reconnect to
+                let buttons = if self.target.is_some() {
+                    let retry = button(
+                        text("Retry")
+                            .width(Length::Fill)
+                            .horizontal_alignment(Horizontal::Center),
+                    )
+                    .width(Length::Fill)
+                    .on_press(Message::Retry);
+
+                    row![retry, ok].spacing(10)
+                } else {
+                    row![ok]
+                };
+
+                column![title, message, buttons]
             }
 
-            State::Connecting { .. } => {
-                let message = text("Establishing connection...")
+            State::Connecting(attempt) => {
+                let message = text(match attempt.load(Ordering::Relaxed) {
+                    0 => "Establishing connection...".to_owned(),
+                    attempt => format!(
+                        "Establishing connection (attempt {attempt} of {})...",
+                        self.transmission.handshakes
+                    ),
+                })
+                .size(32)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .vertical_alignment(Vertical::Center)
+                .horizontal_alignment(Horizontal::Center);
+
+                column![title, message]
+            }
+
+            State::Loading(path) => {
+                let message = text(format!("Loading {}...", path.display()))
                     .size(32)
                     .width(Length::Fill)
                     .height(Length::Fill)
@@ -301,54 +1567,433 @@ impl Filter {
         content.map(App)
     }
 
+    /// Is the window resized for the run, in full screen or as the mini monitor?
+    pub fn is_resized(&self) -> bool {
+        matches!(
+            self.state,
+            State::Connected {
+                full_screen: true,
+                ..
+            } | State::Connected {
+                mini_monitor: true,
+                ..
+            }
+        )
+    }
+
     pub fn subscription(&self) -> Subscription<super::Message> {
-        use iced::time::{self, Duration};
+        use iced::time;
 
-        match &self.state {
-            State::Connected {
-                receiver: Some(_),
-                transmitter: Some(_),
+        // F11 toggles full screen, which Esc leaves
+        let hotkeys = subscription::events_with(|event, _| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::F11,
+                ..
+            }) => Some(App(Message::ToggleFullScreen)),
+
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Escape,
                 ..
-            } => time::every(Duration::from_micros(1_000_000 / crate::FPS))
-                .map(|_| App(Message::Refresh)),
+            }) => Some(App(Message::ExitFullScreen)),
+
+            _ => None,
+        });
+
+        let refresh = match &self.state {
+            State::Connected {
+                workers, export, ..
+            } if workers.is_running() || export.is_some() => {
+                time::every(Duration::from_micros(1_000_000 / crate::FPS))
+                    .map(|_| App(Message::Refresh))
+            }
+
+            State::Connecting(_) => {
+                time::every(Duration::from_millis(100)).map(|_| App(Message::Refresh))
+            }
 
             _ => Subscription::none(),
+        };
+
+        if matches!(self.state, State::Connected { .. }) {
+            Subscription::batch([refresh, hotkeys])
+        } else {
+            refresh
         }
     }
+}
 
-    fn compute_tensors(&self, sampling_interval: f32) -> (Vec<f32>, Vec<f32>) {
-        let State::Connecting { function, stop_time, .. } = &self.state else {
-            panic!();
-        };
+/// Capabilities announced by a device in the handshake
+#[derive(Debug, Clone, Copy)]
+struct Announcement {
+    /// Sampling frequency, in Hz
+    input: u32,
+    /// Output sampling frequency in Hz, differing from `input` for decimating or interpolating
+    /// devices
+    output: u32,
+    /// Number of inputs processed at once, by block-based devices
+    block: Option<u32>,
+    /// Number of interleaved output channels
+    channels: u32,
+}
 
-        Python::with_gil(|py| -> PyResult<_> {
-            let numpy = py.import("numpy")?;
-            let locals = crate::NUMPY_IMPORTS
-                .iter()
-                .map(|&member| (member, numpy.getattr(member).expect("valid member")))
-                .into_py_dict(py);
+/// Opens `target` and requests its capabilities, checking them against `profile`, in up to
+/// `attempts` handshakes counted in `attempt`, unless it speaks the ASCII `protocol`
+///
+/// Emulated devices generate their noise from `seed`, and only speak the binary protocol. Other
+/// devices are reached through the link kept open by the last run on them, if any, and their
+/// link is kept open after this run if `keep_open`. Any other link kept open is closed
+fn connect(
+    target: Target,
+    profile: &Profile,
+    seed: u32,
+    attempts: u16,
+    keep_open: bool,
+    protocol: Protocol,
+    attempt: &AtomicU16,
+) -> io::Result<(Announcement, Link)> {
+    let device = target.to_string();
+    let keep_open = keep_open && !matches!(target, Target::Emulated(..));
 
-            locals.set_item("np", numpy)?;
-            let t = {
-                let code = format!("np.arange(0, {stop_time}, {sampling_interval})");
-                py.eval(&code, None, Some(locals))?
-            };
+    if protocol == Protocol::Ascii && matches!(target, Target::Emulated(..)) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "emulated devices don't speak the ASCII protocol",
+        ));
+    }
 
-            locals.set_item("t", t)?;
-            let f = py.eval(function, None, Some(locals))?;
+    // Devices await another handshake once a run ends, without reopening their port
+    let reopened = match Link::reopen(&device) {
+        Some(mut link) if keep_open => match greet(&mut link, protocol, profile, 1, attempt) {
+            Ok(announcement) => {
+                tracing::info!("Reusing the open connection to {device}");
+                Some((announcement, link))
+            }
 
-            Ok((t.extract()?, f.extract()?))
-        })
-        .expect("vectors")
+            Err(e) => {
+                tracing::warn!("Reconnecting to {device}, as it didn't answer: {e}");
+                None
+            }
+        },
+
+        _ => None,
+    };
+    Link::close_idle();
+
+    let (announcement, link) = match (reopened, target) {
+        (Some(reopened), _) => reopened,
+
+        (None, Target::Serial(port_name)) => {
+            let lock = PortLock::acquire(&port_name)?;
+
+            // Short reads let the handshake check its deadline while the device is silent
+            let mut serial = serialport::new(port_name, crate::BAUD_RATE)
+                .timeout(Duration::from_millis(100))
+                .open_native()?;
+
+            thread::sleep(Duration::from_millis(250));
+            let announcement = greet(&mut serial, protocol, profile, attempts, attempt)?;
+
+            (announcement, Link::serial(serial, lock)?)
+        }
+
+        (None, Target::Tcp(address)) => {
+            let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("{address} not found"))
+            })?;
+            let stream = TcpStream::connect_timeout(
+                &address,
+                Duration::from_millis(crate::TCP_CONNECT_TIMEOUT),
+            )?;
+
+            // As with serial ports, short reads let the handshake check its deadline
+            stream.set_read_timeout(Some(Duration::from_millis(100)))?;
+            stream.set_nodelay(true)?;
+
+            let mut link = Link::tcp(stream)?;
+            let announcement = greet(&mut link, protocol, profile, attempts, attempt)?;
+
+            (announcement, link)
+        }
+
+        (None, Target::Emulated(device, artifacts)) => {
+            let mut link = emulator::spawn(device, artifacts, profile, seed);
+            (handshake(&mut link, attempts, attempt)?, link)
+        }
+    };
+
+    profile.check(announcement.input)?;
+    Ok((
+        announcement,
+        if keep_open {
+            link.keep_open(device)
+        } else {
+            link
+        },
+    ))
+}
+
+/// Capabilities of the device on the other end of `port`, speaking `protocol`
+///
+/// ASCII devices don't handshake, so they're assumed to sample at the highest rate of their
+/// `profile`, with one output per input
+fn greet(
+    port: &mut (impl Read + Write),
+    protocol: Protocol,
+    profile: &Profile,
+    attempts: u16,
+    attempt: &AtomicU16,
+) -> io::Result<Announcement> {
+    match protocol {
+        Protocol::Binary => handshake(port, attempts, attempt),
+
+        Protocol::Ascii => Ok(Announcement {
+            input: profile.max_sampling_rate,
+            output: profile.max_sampling_rate,
+            block: None,
+            channels: 1,
+        }),
     }
 }
 
+/// Requests the capabilities of the device on the other end of `port`
+///
+/// Devices whose output rate differs announce it with [`crate::MULTI_RATE`], block-based
+/// devices their block size with [`crate::BLOCK_MODE`], and multi-channel devices their number
+/// of channels with [`crate::MULTI_CHANNEL`]
+///
+/// The response is found by scanning for [`crate::ACK`], skipping banners and bootloader
+/// output, and must arrive within [`crate::HANDSHAKE_TIMEOUT`], however slowly. Devices that
+/// don't answer, such as ones still booting, are sent [`crate::SYN`] up to `attempts` times,
+/// waiting twice as long for each response, the current attempt being counted in `attempt`
+fn handshake(
+    port: &mut (impl Read + Write),
+    attempts: u16,
+    attempt: &AtomicU16,
+) -> io::Result<Announcement> {
+    fn read_u32(port: &mut impl Read, deadline: Instant) -> io::Result<u32> {
+        let mut buf = [0u8; mem::size_of::<u32>()];
+        read_before(port, &mut buf, deadline)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    let mut timeout = Duration::from_millis(crate::HANDSHAKE_TIMEOUT);
+    let deadline = loop {
+        let current = attempt.fetch_add(1, Ordering::Relaxed) + 1;
+        let deadline = Instant::now() + timeout;
+        port.write_all(crate::SYN)?;
+
+        match acknowledgement(port, deadline) {
+            Ok(()) => break deadline,
+
+            Err(e) if e.kind() == io::ErrorKind::TimedOut && current < attempts => {
+                tracing::warn!("No handshake response to attempt {current} of {attempts}");
+                timeout *= 2;
+            }
+
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no handshake response after {current} attempts"),
+                ));
+            }
+
+            Err(e) => return Err(e),
+        }
+    };
+
+    let announced = read_u32(port, deadline)?;
+    let input = announced & !(crate::MULTI_RATE | crate::BLOCK_MODE | crate::MULTI_CHANNEL);
+    let output = if announced & crate::MULTI_RATE == 0 {
+        input
+    } else {
+        read_u32(port, deadline)?
+    };
+    let block = if announced & crate::BLOCK_MODE == 0 {
+        None
+    } else {
+        Some(read_u32(port, deadline)?)
+    };
+    let channels = if announced & crate::MULTI_CHANNEL == 0 {
+        1
+    } else {
+        read_u32(port, deadline)?
+    };
+
+    if channels == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid channel count of 0",
+        ));
+    }
+
+    if block == Some(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid block size of 0",
+        ));
+    }
+
+    if input == 0 || output == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid sampling frequencies: {input} Hz in, {output} Hz out"),
+        ));
+    }
+
+    tracing::info!("Sampling frequency: {input}");
+    if output != input {
+        tracing::info!("Output sampling frequency: {output}");
+    }
+    if let Some(block) = block {
+        tracing::info!("Block size: {block}");
+    }
+    if channels > 1 {
+        tracing::info!("Output channels: {channels}");
+    }
+
+    Ok(Announcement {
+        input,
+        output,
+        block,
+        channels,
+    })
+}
+
+/// Scans `port` for [`crate::ACK`] until `deadline`, discarding what precedes it
+fn acknowledgement(port: &mut impl Read, deadline: Instant) -> io::Result<()> {
+    // Last bytes received, compared against the marker as each new one arrives
+    let mut window = [0u8; crate::ACK.len()];
+    let mut discarded = 0;
+
+    read_before(port, &mut window, deadline)?;
+    while window != crate::ACK {
+        if discarded == crate::HANDSHAKE_SCAN_LIMIT {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no handshake response in the first {discarded} bytes received"),
+            ));
+        }
+
+        window.rotate_left(1);
+        read_before(port, &mut window[crate::ACK.len() - 1..], deadline)?;
+        discarded += 1;
+    }
+
+    if discarded > 0 {
+        tracing::warn!("Discarded {discarded} bytes before the handshake response");
+    }
+
+    Ok(())
+}
+
+/// Fills `buf` from `port`, however the bytes trickle in, unless `deadline` passes first
+fn read_before(port: &mut impl Read, buf: &mut [u8], deadline: Instant) -> io::Result<()> {
+    let mut filled = 0;
+
+    while filled < buf.len() {
+        match port.read(&mut buf[filled..]) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => filled += read,
+            // Silence until the deadline, as the device may still be starting up
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+
+        if filled < buf.len() && Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "handshake response timed out",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Summary of the time synchronization exchanges, if enough took place
+fn synchronization<'a>(clock: &clock::Clock) -> Option<Element<'a, Message>> {
+    let rate = clock.rate()?;
+    let round_trip = clock.round_trip()?;
+    let start = clock.index_at(Duration::ZERO)?;
+
+    Some(
+        text(format!(
+            "Clock sync: {} exchanges, device at {rate:.2} samples/s, \
+             run start at sample {start:.1} (±{:.1} ms)",
+            clock.exchanges().len(),
+            round_trip.as_secs_f32() * 500.0
+        ))
+        .into(),
+    )
+}
+
+fn metrics<'a>(
+    metrics: Option<&Metrics>,
+    unit: &str,
+    sampling_interval: f32,
+) -> Element<'a, Message> {
+    let Some(Metrics {
+        samples,
+        input,
+        output,
+        gain,
+        lag,
+        ..
+    }) = metrics
+    else {
+        return text("Computing metrics...").into();
+    };
+
+    let statistics = |name, statistics: &analysis::Statistics| {
+        text(format!(
+            "{name}: [{:.3}, {:.3}] {unit}, mean {:.3} {unit}, RMS {:.3} {unit}",
+            statistics.min, statistics.max, statistics.mean, statistics.rms
+        ))
+    };
+
+    let gain = gain.map_or_else(|| "—".to_owned(), |gain| format!("{gain:.2} dB"));
+
+    let mut row = row![
+        text(format!("{samples} samples")),
+        statistics("Input", input),
+        statistics("Output", output),
+        text(format!("Gain: {gain}")),
+    ]
+    .spacing(20);
+
+    if let Some(lag) = lag {
+        row = row.push(text(format!(
+            "Lag: {lag} samples ({:.2} ms)",
+            *lag as f32 * sampling_interval * 1e3
+        )));
+    }
+
+    row.into()
+}
+
 impl Clone for Message {
     fn clone(&self) -> Self {
         match &self {
+            Message::Retry => Message::Retry,
             Message::Finish => Message::Finish,
+            Message::TogglePause => Message::TogglePause,
             Message::Export => Message::Export,
+            Message::ExportImage => Message::ExportImage,
+            Message::ExportAnimation => Message::ExportAnimation,
+            Message::CancelExport => Message::CancelExport,
+            Message::FormatSelected(format) => Message::FormatSelected(*format),
             Message::Graph(message) => Message::Graph(*message),
+            Message::Bode(message) => Message::Bode(*message),
+            Message::Impulse(message) => Message::Impulse(*message),
+            Message::ZPlane(message) => Message::ZPlane(*message),
+            Message::Log(message) => Message::Log(message.clone()),
+            Message::ViewSelected(view) => Message::ViewSelected(*view),
+            Message::PaneDragged(event) => Message::PaneDragged(*event),
+            Message::PaneResized(event) => Message::PaneResized(*event),
+            Message::ToggleFullScreen => Message::ToggleFullScreen,
+            Message::ExitFullScreen => Message::ExitFullScreen,
+            Message::ToggleMiniMonitor => Message::ToggleMiniMonitor,
             _ => unreachable!(),
         }
     }
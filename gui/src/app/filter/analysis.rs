@@ -0,0 +1,266 @@
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{response::Spectra, storage::Samples};
+
+/// Component removed from a series before it is shown and measured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Detrend {
+    #[default]
+    None,
+    Mean,
+    Linear,
+}
+
+impl Detrend {
+    pub const ALL: [Self; 3] = [Self::None, Self::Mean, Self::Linear];
+}
+
+impl fmt::Display for Detrend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "Raw",
+            Self::Mean => "Remove mean",
+            Self::Linear => "Remove linear trend",
+        })
+    }
+}
+
+/// [`Detrend`] of each series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Detrending {
+    pub input: Detrend,
+    pub output: Detrend,
+}
+
+/// Least squares fit of `offset + slope * i` over sample indices `i`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Trend {
+    pub offset: f32,
+    pub slope: f32,
+}
+
+impl Trend {
+    fn fit(samples: &[f32], detrend: Detrend) -> Self {
+        let n = samples.len() as f64;
+
+        let (sum, weighted) =
+            samples
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(sum, weighted), (i, &x)| {
+                    let x = f64::from(x);
+                    (sum + x, (i as f64).mul_add(x, weighted))
+                });
+
+        let (offset, slope) = match detrend {
+            Detrend::None => (0.0, 0.0),
+            Detrend::Mean => (sum / n, 0.0),
+            Detrend::Linear => {
+                // Closed forms of the sums of indices and of their squares
+                let indices = n * (n - 1.0) / 2.0;
+                let squares = n * (n - 1.0) * (2.0 * n - 1.0) / 6.0;
+                let denominator = n.mul_add(squares, -indices * indices);
+
+                let slope = if denominator > 0.0 {
+                    n.mul_add(weighted, -indices * sum) / denominator
+                } else {
+                    0.0
+                };
+
+                ((sum - slope * indices) / n, slope)
+            }
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            offset: offset as f32,
+            slope: slope as f32,
+        }
+    }
+
+    fn at(self, i: usize) -> f32 {
+        self.slope.mul_add(i as f32, self.offset)
+    }
+
+    /// `samples` with the trend removed, the first being at index `first`
+    pub fn remove(self, samples: &[f32], first: usize) -> Vec<f32> {
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x - self.at(first + i))
+            .collect()
+    }
+}
+
+/// Summary statistics of a series
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Statistics {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub rms: f32,
+    /// Removed before computing the other statistics
+    pub trend: Trend,
+}
+
+impl Statistics {
+    fn of(samples: &[f32], detrend: Detrend) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let trend = Trend::fit(samples, detrend);
+        let residuals = samples
+            .iter()
+            .enumerate()
+            .map(|(i, x)| f64::from(x - trend.at(i)));
+
+        let (min, max, sum, squares) = residuals.fold(
+            (f64::INFINITY, f64::NEG_INFINITY, 0.0, 0.0),
+            |(min, max, sum, squares), x| (min.min(x), max.max(x), sum + x, x.mul_add(x, squares)),
+        );
+        let n = samples.len() as f64;
+
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            min: min as f32,
+            max: max as f32,
+            mean: (sum / n) as f32,
+            rms: (squares / n).sqrt() as f32,
+            trend,
+        }
+    }
+}
+
+/// Metrics of a run
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    /// Number of received samples covered
+    pub samples: usize,
+    pub input: Statistics,
+    pub output: Statistics,
+    /// Output to input RMS ratio, in dB, unless either RMS is zero or not finite
+    pub gain: Option<f32>,
+    /// Spectra of the run, estimated once reception has finished
+    pub spectra: Option<Spectra>,
+    /// Delay of the output behind the input in samples, from the spectra
+    pub lag: Option<isize>,
+}
+
+/// Computes [`Metrics`] on the blocking thread pool
+pub async fn compute(
+    input: Arc<Samples>,
+    output: Arc<Mutex<Samples>>,
+    sampling_interval: f32,
+    output_interval: Option<f32>,
+    finished: bool,
+    detrending: Detrending,
+) -> Metrics {
+    tokio::task::spawn_blocking(move || {
+        // Samples received so far, read without holding up the receiver
+        let output = crate::perf::lock(&output).extent();
+        let samples = output.len();
+        let multi_rate = output_interval.is_some();
+
+        // Inputs spanning the same time as the received outputs
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let covered = output_interval.map_or(samples, |output_interval| {
+            (samples as f32 * output_interval / sampling_interval).round() as usize
+        });
+        let input = &input[..covered.min(input.len())];
+
+        let input_statistics = Statistics::of(input, detrending.input);
+        let output_statistics = Statistics::of(&output, detrending.output);
+
+        // Cross-spectra need both series at the same rate
+        let spectra = (finished && !multi_rate)
+            .then(|| Spectra::estimate(input, &output, sampling_interval))
+            .flatten();
+
+        Metrics {
+            samples,
+            input: input_statistics,
+            output: output_statistics,
+            gain: gain(input_statistics.rms, output_statistics.rms),
+            lag: spectra.as_ref().map(Spectra::lag),
+            spectra,
+        }
+    })
+    .await
+    .expect("analysis task ran")
+}
+
+/// Ratio of `output` to `input` RMS in dB, if both are finite and nonzero
+fn gain(input: f32, output: f32) -> Option<f32> {
+    let measurable = |rms: f32| rms.is_finite() && rms != 0.0;
+    (measurable(input) && measurable(output)).then(|| 20.0 * (output / input).log10())
+}
+
+/// Schedules background [`compute`] runs
+pub struct Analysis {
+    /// Latest results
+    pub metrics: Option<Metrics>,
+    /// Is a computation in flight?
+    pending: bool,
+    /// When the last computation was started
+    started: Option<Instant>,
+    /// Have the latest results been invalidated?
+    stale: bool,
+}
+
+impl Analysis {
+    pub const fn new() -> Self {
+        Self {
+            metrics: None,
+            pending: false,
+            started: None,
+            stale: false,
+        }
+    }
+}
+
+impl Analysis {
+    /// Should a computation be started, given the number of `available` samples?
+    ///
+    /// Recomputed periodically while streaming, and once more after reception has `finished`
+    pub fn due(&self, available: usize, finished: bool) -> bool {
+        if self.pending || available == 0 {
+            return false;
+        }
+
+        if self.stale {
+            return true;
+        }
+
+        if finished {
+            self.metrics
+                .as_ref()
+                .map_or(true, |metrics| metrics.samples != available)
+        } else {
+            self.started.map_or(true, |started| {
+                started.elapsed() >= Duration::from_millis(crate::ANALYSIS_PERIOD)
+            })
+        }
+    }
+
+    /// Recomputes as soon as possible, e.g. after the detrending changed
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    pub fn start(&mut self) {
+        self.pending = true;
+        self.stale = false;
+        self.started = Some(Instant::now());
+    }
+
+    pub fn finish(&mut self, metrics: Metrics) {
+        self.pending = false;
+        self.metrics = Some(metrics);
+    }
+}
@@ -0,0 +1,210 @@
+use iced::{
+    widget::{column, row, slider, text},
+    Element, Length,
+};
+use plotters::prelude::*;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget};
+
+use super::{
+    super::theme,
+    response::{Band, Mask, Response, Spectra},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Message {
+    PassbandEdgeUpdated(f32),
+    RippleUpdated(f32),
+    StopbandEdgeUpdated(f32),
+    AttenuationUpdated(f32),
+}
+
+/// Magnitude plot of the measured frequency response, against a user defined [`Mask`]
+pub struct Bode {
+    mask: Mask,
+    /// In Hz
+    nyquist: f32,
+}
+
+impl Bode {
+    pub fn new(sampling_interval: f32) -> Self {
+        let nyquist = 0.5 / sampling_interval;
+
+        Self {
+            mask: Mask::new(nyquist),
+            nyquist,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::PassbandEdgeUpdated(frequency) => self.mask.passband_edge = frequency,
+            Message::RippleUpdated(ripple) => self.mask.ripple = ripple,
+            Message::StopbandEdgeUpdated(frequency) => self.mask.stopband_edge = frequency,
+            Message::AttenuationUpdated(attenuation) => self.mask.attenuation = attenuation,
+        }
+    }
+
+    pub fn view(&self, spectra: Option<&Spectra>) -> Element<'_, Message> {
+        let Some(response) = spectra.map(Spectra::response) else {
+            return text("Not enough samples to estimate the frequency response")
+                .height(Length::Fill)
+                .into();
+        };
+
+        let compliance = match self.mask.check(&response) {
+            None => "Response within mask".to_owned(),
+            Some(violation) => format!(
+                "Response violates the {} limit by {:.2} dB at {:.1} Hz",
+                match violation.band {
+                    Band::Passband => "passband",
+                    Band::Stopband => "stopband",
+                },
+                violation.excess,
+                violation.frequency
+            ),
+        };
+
+        let chart = ChartWidget::new(Plot {
+            response,
+            mask: self.mask,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let Mask {
+            passband_edge,
+            ripple,
+            stopband_edge,
+            attenuation,
+        } = self.mask;
+
+        let step = self.nyquist / 1000.0;
+
+        let controls = column![
+            control(
+                format!("Passband edge: {passband_edge:.1} Hz"),
+                slider(
+                    step..=self.nyquist,
+                    passband_edge,
+                    Message::PassbandEdgeUpdated
+                )
+                .step(step)
+            ),
+            control(
+                format!("Passband ripple: ±{ripple:.1} dB"),
+                slider(0.1..=6.0, ripple, Message::RippleUpdated).step(0.1)
+            ),
+            control(
+                format!("Stopband edge: {stopband_edge:.1} Hz"),
+                slider(
+                    step..=self.nyquist,
+                    stopband_edge,
+                    Message::StopbandEdgeUpdated
+                )
+                .step(step)
+            ),
+            control(
+                format!("Stopband attenuation: {attenuation:.0} dB"),
+                slider(3.0..=100.0, attenuation, Message::AttenuationUpdated).step(1.0)
+            ),
+        ]
+        .spacing(5);
+
+        column![chart, controls, text(compliance)]
+            .spacing(10)
+            .into()
+    }
+}
+
+/// Labelled mask slider
+fn control<'a>(label: String, slider: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    row![text(label).width(Length::FillPortion(1)), slider.into()]
+        .width(Length::Fill)
+        .into()
+}
+
+struct Plot {
+    response: Response,
+    mask: Mask,
+}
+
+impl Chart<Message> for Plot {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        _state: &Self::State,
+        mut builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let colors = theme::colors();
+        let Response {
+            frequency,
+            magnitude,
+        } = &self.response;
+
+        let (Some(&low), Some(&high)) = (frequency.first(), frequency.last()) else {
+            return;
+        };
+
+        let top = magnitude.iter().copied().fold(self.mask.ripple, f32::max) + 5.0;
+        let bottom = magnitude
+            .iter()
+            .copied()
+            .fold(-self.mask.attenuation, f32::min)
+            - 10.0;
+
+        let mut chart = builder
+            .x_label_area_size(24)
+            .y_label_area_size(36)
+            .margin(10)
+            .build_cartesian_2d((low..high).log_scale(), bottom..top)
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(colors.foreground)
+            .label_style(("sans-serif", 18).into_font().color(&colors.foreground))
+            .x_desc("Hz")
+            .y_desc("dB")
+            .light_line_style(colors.foreground.mix(0.10))
+            .bold_line_style(colors.foreground.mix(0.30))
+            .draw()
+            .expect("drawn mesh");
+
+        // Forbidden regions
+        let Mask {
+            passband_edge,
+            ripple,
+            stopband_edge,
+            attenuation,
+        } = self.mask;
+
+        let (passband, stopband) = if self.mask.is_low_pass() {
+            (low..passband_edge, stopband_edge..high)
+        } else {
+            (passband_edge..high, low..stopband_edge)
+        };
+
+        let forbidden = RED.mix(0.25).filled();
+        chart
+            .draw_series([
+                Rectangle::new([(passband.start, ripple), (passband.end, top)], forbidden),
+                Rectangle::new(
+                    [(passband.start, bottom), (passband.end, -ripple)],
+                    forbidden,
+                ),
+                Rectangle::new(
+                    [(stopband.start, -attenuation), (stopband.end, top)],
+                    forbidden,
+                ),
+            ])
+            .expect("drawn mask");
+
+        chart
+            .draw_series(LineSeries::new(
+                frequency.iter().copied().zip(magnitude.iter().copied()),
+                colors.input.stroke_width(2),
+            ))
+            .expect("drawn response");
+    }
+}
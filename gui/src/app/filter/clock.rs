@@ -0,0 +1,100 @@
+use parking_lot::Mutex;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Time synchronization exchange with the device
+///
+/// The host sends [`crate::TIME_SYNC`] and its timestamp in place of a sample, which the device
+/// answers in place of an output with [`crate::TIME_SYNC`], the echoed timestamp and the number
+/// of inputs it received before, all as little-endian `u32`s
+#[derive(Debug, Clone, Copy)]
+pub struct Exchange {
+    /// Host timestamp of the request, in microseconds since the run started
+    pub sent: u32,
+    /// Host timestamp of the reply, in microseconds since the run started
+    pub received: u32,
+    /// Device sample index at the request
+    pub index: u32,
+}
+
+impl Exchange {
+    /// Host time at which the device most likely handled the request, assuming symmetric delays
+    fn midpoint(self) -> f64 {
+        (f64::from(self.sent) + f64::from(self.received)) / 2.0
+    }
+}
+
+/// Host timestamp of an [`Exchange`]: microseconds since `epoch`, wrapping every ~71 minutes
+#[allow(clippy::cast_possible_truncation)]
+pub fn timestamp(epoch: Instant) -> u32 {
+    epoch.elapsed().as_micros() as u32
+}
+
+/// Mapping between host time and device sample indices, from [`Exchange`]s
+#[derive(Debug, Default)]
+pub struct Clock {
+    exchanges: Vec<Exchange>,
+}
+
+/// [`Clock`] shared with the receiver
+pub type Shared = Arc<Mutex<Clock>>;
+
+impl Clock {
+    pub fn record(&mut self, exchange: Exchange) {
+        self.exchanges.push(exchange);
+    }
+
+    pub fn exchanges(&self) -> &[Exchange] {
+        &self.exchanges
+    }
+
+    /// Least squares fit of `index = offset + rate * midpoint`, with the rate per microsecond
+    fn fit(&self) -> Option<(f64, f64)> {
+        let n = self.exchanges.len() as f64;
+        if self.exchanges.len() < 2 {
+            return None;
+        }
+
+        let (mean_t, mean_i) = self.exchanges.iter().fold((0.0, 0.0), |(t, i), exchange| {
+            (
+                t + exchange.midpoint() / n,
+                i + f64::from(exchange.index) / n,
+            )
+        });
+        let (covariance, variance) =
+            self.exchanges
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), exchange| {
+                    let dt = exchange.midpoint() - mean_t;
+                    let di = f64::from(exchange.index) - mean_i;
+                    (dt.mul_add(di, covariance), dt.mul_add(dt, variance))
+                });
+
+        (variance > 0.0).then(|| {
+            let rate = covariance / variance;
+            (rate.mul_add(-mean_t, mean_i), rate)
+        })
+    }
+
+    /// Fractional device sample index at host time `elapsed` since the run started
+    pub fn index_at(&self, elapsed: Duration) -> Option<f64> {
+        let (offset, rate) = self.fit()?;
+        Some(rate.mul_add(elapsed.as_micros() as f64, offset))
+    }
+
+    /// Device sampling rate as measured by the host clock, in samples per second
+    pub fn rate(&self) -> Option<f64> {
+        self.fit().map(|(_, rate)| rate * 1e6)
+    }
+
+    /// Shortest round trip of an exchange, bounding the mapping's uncertainty
+    pub fn round_trip(&self) -> Option<Duration> {
+        self.exchanges
+            .iter()
+            .map(|exchange| exchange.received.wrapping_sub(exchange.sent))
+            .min()
+            .map(|micros| Duration::from_micros(micros.into()))
+    }
+}
@@ -0,0 +1,221 @@
+use rustfft::num_complex::Complex64;
+use std::{f64::consts::PI, fmt};
+
+/// Family of a reference filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    /// Maximally flat passband
+    Butterworth,
+    /// Type I Chebyshev, trading passband ripple for a steeper roll-off
+    Chebyshev,
+    /// Average of the latest inputs
+    MovingAverage,
+}
+
+impl Family {
+    pub const ALL: [Self; 3] = [Self::Butterworth, Self::Chebyshev, Self::MovingAverage];
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Butterworth => "Butterworth",
+            Self::Chebyshev => "Chebyshev",
+            Self::MovingAverage => "Moving average",
+        })
+    }
+}
+
+/// Band passed by a reference filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    LowPass,
+    HighPass,
+}
+
+impl Response {
+    pub const ALL: [Self; 2] = [Self::LowPass, Self::HighPass];
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LowPass => "Low-pass",
+            Self::HighPass => "High-pass",
+        })
+    }
+}
+
+/// Reference filter design, computed on the host to compare against the device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Design {
+    pub family: Family,
+    pub response: Response,
+    /// Order of IIR filters, or number of taps of moving averages
+    pub order: u8,
+    /// Cutoff frequency in Hz, at -3 dB for Butterworth filters and at the edge of the ripple
+    /// band for Chebyshev ones. Unused by moving averages
+    pub cutoff: f32,
+    /// Passband ripple of Chebyshev filters, in dB
+    pub ripple: f32,
+}
+
+impl Design {
+    /// Highest order of IIR designs
+    pub const MAX_ORDER: u8 = 8;
+    /// Most taps of moving averages
+    pub const MAX_TAPS: u8 = 64;
+
+    /// 2nd-order Butterworth low-pass cutting off at a tenth of the sampling frequency, like the
+    /// emulated devices
+    pub fn new(sampling_interval: f32) -> Self {
+        Self {
+            family: Family::Butterworth,
+            response: Response::LowPass,
+            order: 2,
+            cutoff: 0.1 / sampling_interval,
+            ripple: 1.0,
+        }
+    }
+
+    /// Largest [`Self::order`] of the design's family
+    pub const fn max_order(&self) -> u8 {
+        match self.family {
+            Family::MovingAverage => Self::MAX_TAPS,
+            Family::Butterworth | Family::Chebyshev => Self::MAX_ORDER,
+        }
+    }
+
+    /// Filters `input`, sampled every `sampling_interval`, from a state of rest
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn filter(&self, input: &[f32], sampling_interval: f32) -> Vec<f32> {
+        if self.family == Family::MovingAverage {
+            return moving_average(input, usize::from(self.order.max(1)));
+        }
+
+        let mut sections = self.sections(sampling_interval);
+
+        input
+            .iter()
+            .map(|&x| {
+                sections
+                    .iter_mut()
+                    .fold(f64::from(x), |x, section| section.process(x)) as f32
+            })
+            .collect()
+    }
+
+    /// Cascade of second-order sections, through the bilinear transform of the analog prototype
+    fn sections(&self, sampling_interval: f32) -> Vec<Section> {
+        let order = usize::from(self.order.clamp(1, Self::MAX_ORDER));
+        let nyquist = 0.5 / f64::from(sampling_interval);
+        let cutoff = f64::from(self.cutoff).clamp(1e-6 * nyquist, 0.999 * nyquist);
+
+        // Prewarped, so that the digital filter cuts off at the requested frequency
+        let warped = (0.5 * PI * cutoff / nyquist).tan();
+
+        let (stretch, gain) = match self.family {
+            Family::Chebyshev => {
+                let epsilon = (10f64.powf(f64::from(self.ripple.max(0.01)) / 10.0) - 1.0).sqrt();
+                let mu = epsilon.recip().asinh() / order as f64;
+
+                // Even orders start from the bottom of the ripple
+                let gain = if order % 2 == 0 {
+                    epsilon.hypot(1.0).recip()
+                } else {
+                    1.0
+                };
+
+                ((mu.sinh(), mu.cosh()), gain)
+            }
+            Family::Butterworth | Family::MovingAverage => ((1.0, 1.0), 1.0),
+        };
+
+        let mut sections: Vec<_> = (0..(order + 1) / 2)
+            .map(|k| {
+                let theta = PI * (2 * k + 1) as f64 / (2 * order) as f64;
+                let prototype = Complex64::new(-stretch.0 * theta.sin(), stretch.1 * theta.cos());
+
+                let pole = match self.response {
+                    Response::LowPass => prototype * warped,
+                    Response::HighPass => warped / prototype,
+                };
+                let pole = (1.0 + pole) / (1.0 - pole);
+
+                Section::new(self.response, pole, 2 * k + 1 == order)
+            })
+            .collect();
+
+        if let Some(section) = sections.first_mut() {
+            section.b.iter_mut().for_each(|b| *b *= gain);
+        }
+
+        sections
+    }
+}
+
+/// Biquad section, in transposed direct form II
+#[derive(Debug)]
+struct Section {
+    b: [f64; 3],
+    a: [f64; 2],
+    /// Internal state
+    z: [f64; 2],
+}
+
+impl Section {
+    /// Section with the `pole` and its conjugate, or with the real part of `pole` alone if `real`,
+    /// and all of its zeros at the frequency stopped by `response`
+    ///
+    /// Normalized to unity gain at the center of the passband
+    fn new(response: Response, pole: Complex64, real: bool) -> Self {
+        // Value of z^-1 at the center of the passband
+        let (zero, center) = match response {
+            Response::LowPass => (1.0, 1.0),
+            Response::HighPass => (-1.0, -1.0),
+        };
+
+        let (b, a) = if real {
+            ([1.0, zero, 0.0], [-pole.re, 0.0])
+        } else {
+            ([1.0, 2.0 * zero, 1.0], [-2.0 * pole.re, pole.norm_sqr()])
+        };
+
+        let evaluate = |c: [f64; 3]| c[2].mul_add(center * center, c[1].mul_add(center, c[0]));
+        let gain = evaluate([1.0, a[0], a[1]]) / evaluate(b);
+
+        Self {
+            b: b.map(|b| b * gain),
+            a,
+            z: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0].mul_add(x, self.z[0]);
+
+        self.z[0] = self.b[1].mul_add(x, self.a[0].mul_add(-y, self.z[1]));
+        self.z[1] = self.b[2].mul_add(x, -self.a[1] * y);
+
+        y
+    }
+}
+
+/// Average of the latest `taps` inputs, those before the first one being zeros
+#[allow(clippy::cast_possible_truncation)]
+fn moving_average(input: &[f32], taps: usize) -> Vec<f32> {
+    let mut sum = 0.0f64;
+
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            sum += f64::from(x);
+            if let Some(oldest) = i.checked_sub(taps) {
+                sum -= f64::from(input[oldest]);
+            }
+
+            (sum / taps as f64) as f32
+        })
+        .collect()
+}
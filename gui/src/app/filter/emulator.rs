@@ -0,0 +1,575 @@
+use std::{
+    f32::consts::{FRAC_1_SQRT_2, PI},
+    fmt,
+    io::{self, Read, Write},
+    mem,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use super::{
+    super::profile::Profile,
+    framing::{Deframer, Framer},
+    link::{Link, Source},
+    log::Severity,
+    zplane::Coefficients,
+};
+
+/// Sampling frequency reported by emulated devices, in Hz
+const SAMPLING_FREQUENCY: u32 = 1000;
+/// Number of inputs between telemetry reports
+const TELEMETRY_PERIOD: usize = 100;
+
+/// Emulated filtering device, standing in for the microcontroller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    /// 2nd-order Butterworth low-pass, cutting off at a tenth of the sampling frequency
+    Butterworth,
+    /// 50 Hz notch
+    Notch,
+    /// 8-tap moving average
+    MovingAverage,
+    /// Butterworth low-pass whose output saturates at ±1, as an overflowing fixed-point
+    /// implementation would
+    Saturating,
+    /// Butterworth low-pass with the sign of its first feedback coefficient flipped
+    WrongSign,
+    /// Moving average that drops every 64th sample
+    Dropping,
+    /// Moving average keeping every other output, at half the sampling frequency
+    Decimating,
+    /// Moving average processing blocks of 64 inputs at once
+    Blockwise,
+    /// Echoes its inputs unchanged, for the round-trip self-test
+    PassThrough,
+    /// Butterworth low-pass also streaming its raw input and error, the input minus the output
+    MultiChannel,
+}
+
+impl Device {
+    pub const ALL: [Self; 10] = [
+        Self::Butterworth,
+        Self::Notch,
+        Self::MovingAverage,
+        Self::Saturating,
+        Self::WrongSign,
+        Self::Dropping,
+        Self::Decimating,
+        Self::Blockwise,
+        Self::PassThrough,
+        Self::MultiChannel,
+    ];
+
+    /// Output sampling frequency, in Hz
+    const fn output_rate(self) -> u32 {
+        match self {
+            Self::Decimating => SAMPLING_FREQUENCY / 2,
+            _ => SAMPLING_FREQUENCY,
+        }
+    }
+
+    /// Number of inputs processed at once, by block-based devices
+    const fn block_size(self) -> Option<u32> {
+        match self {
+            Self::Blockwise => Some(64),
+            _ => None,
+        }
+    }
+
+    /// Number of interleaved output channels
+    const fn channels(self) -> u32 {
+        match self {
+            Self::MultiChannel => 3,
+            _ => 1,
+        }
+    }
+
+    /// Coefficients the device was designed with, ignoring its nonlinearities
+    pub fn coefficients(self) -> Coefficients {
+        let fs = SAMPLING_FREQUENCY as f32;
+
+        let biquad = match self {
+            Self::Butterworth | Self::Saturating | Self::MultiChannel => {
+                Biquad::low_pass(fs / 10.0, fs)
+            }
+            Self::Notch => Biquad::notch(50.0, fs),
+            Self::WrongSign => {
+                let mut biquad = Biquad::low_pass(fs / 10.0, fs);
+                biquad.a1 = -biquad.a1;
+                biquad
+            }
+            Self::MovingAverage | Self::Dropping | Self::Decimating | Self::Blockwise => {
+                return Coefficients::fir(&[1.0 / 8.0; 8]);
+            }
+            Self::PassThrough => return Coefficients::fir(&[1.0]),
+        };
+
+        Coefficients {
+            b: [biquad.b0, biquad.b1, biquad.b2].map(f64::from).to_vec(),
+            a: [1.0, biquad.a1, biquad.a2].map(f64::from).to_vec(),
+        }
+    }
+
+    fn filter(self) -> Box<dyn FnMut(f32) -> Option<f32> + Send> {
+        let fs = SAMPLING_FREQUENCY as f32;
+
+        match self {
+            Self::Butterworth | Self::MultiChannel => {
+                let mut biquad = Biquad::low_pass(fs / 10.0, fs);
+                Box::new(move |x| Some(biquad.process(x)))
+            }
+
+            Self::Notch => {
+                let mut biquad = Biquad::notch(50.0, fs);
+                Box::new(move |x| Some(biquad.process(x)))
+            }
+
+            Self::MovingAverage | Self::Blockwise => {
+                let mut average = MovingAverage::<8>::default();
+                Box::new(move |x| Some(average.process(x)))
+            }
+
+            Self::Saturating => {
+                let mut biquad = Biquad::low_pass(fs / 10.0, fs);
+                Box::new(move |x| Some(biquad.process(x).clamp(-1.0, 1.0)))
+            }
+
+            Self::WrongSign => {
+                let mut biquad = Biquad::low_pass(fs / 10.0, fs);
+                biquad.a1 = -biquad.a1;
+                Box::new(move |x| Some(biquad.process(x)))
+            }
+
+            Self::Dropping => {
+                let mut average = MovingAverage::<8>::default();
+                let mut count = 0usize;
+                Box::new(move |x| {
+                    count += 1;
+                    let y = average.process(x);
+                    (count % 64 != 0).then_some(y)
+                })
+            }
+
+            Self::Decimating => {
+                let mut average = MovingAverage::<8>::default();
+                let mut count = 0usize;
+                Box::new(move |x| {
+                    count += 1;
+                    let y = average.process(x);
+                    (count % 2 == 0).then_some(y)
+                })
+            }
+
+            Self::PassThrough => Box::new(Some),
+        }
+    }
+}
+
+/// Analog front-end and ADC imperfections of an emulated device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Artifacts {
+    /// ADC resolution in bits, or [`None`] for an ideal converter
+    pub resolution: Option<u8>,
+    /// DC offset added to the input
+    pub offset: f32,
+    /// RMS of the white noise added to the input
+    pub noise: f32,
+}
+
+impl Artifacts {
+    pub const NONE: Self = Self {
+        resolution: None,
+        offset: 0.0,
+        noise: 0.0,
+    };
+}
+
+impl Default for Artifacts {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Applies [`Artifacts`] to inputs before they are filtered
+struct FrontEnd {
+    artifacts: Artifacts,
+    /// Inputs are converted over `-full_scale..full_scale`
+    full_scale: f32,
+    /// xorshift64 state, never 0
+    state: u64,
+}
+
+impl FrontEnd {
+    fn new(artifacts: Artifacts, full_scale: f32, seed: u32) -> Self {
+        Self {
+            artifacts,
+            full_scale,
+            state: (u64::from(seed) << 32) | 0x9E37_79B9,
+        }
+    }
+
+    fn sample(&mut self, x: f32) -> f32 {
+        let Artifacts {
+            resolution,
+            offset,
+            noise,
+        } = self.artifacts;
+
+        let x = x + offset + noise * self.gaussian();
+
+        let Some(bits) = resolution else {
+            return x;
+        };
+
+        let step = 2.0 * self.full_scale / f32::from(bits).exp2();
+        (x.clamp(-self.full_scale, self.full_scale - step) / step).round() * step
+    }
+
+    /// Uniform in `0.0..1.0`
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        (self.state >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Standard normal, through the Box-Muller transform
+    fn gaussian(&mut self) -> f32 {
+        let (u1, u2) = (1.0 - self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Butterworth => "Butterworth low-pass",
+            Self::Notch => "50 Hz notch",
+            Self::MovingAverage => "Moving average",
+            Self::Saturating => "Faulty: saturating low-pass",
+            Self::WrongSign => "Faulty: feedback sign error",
+            Self::Dropping => "Faulty: lossy moving average",
+            Self::Decimating => "Decimating moving average",
+            Self::Blockwise => "Block-based moving average",
+            Self::PassThrough => "Pass-through",
+            Self::MultiChannel => "Multi-channel low-pass",
+        })
+    }
+}
+
+/// Inputs as received by an emulated device with `artifacts`, through its front end
+///
+/// The device's noise is reproduced from the same `seed`, so the result is exact
+pub fn condition(artifacts: Artifacts, full_scale: f32, seed: u32, samples: &[f32]) -> Vec<f32> {
+    let mut front_end = FrontEnd::new(artifacts, full_scale, seed);
+    samples.iter().map(|&x| front_end.sample(x)).collect()
+}
+
+/// Starts emulating `device`, returning a link to it
+///
+/// The emulator speaks the serial protocol: it answers [`crate::SYN`] with [`crate::ACK`] and
+/// its sampling frequency, then filters framed samples until the EOT, which it echoes back,
+/// sending the outputs of each frame, or block, in one frame. It answers [`crate::TIME_SYNC`]
+/// requests, logs the start and end of filtering, and every [`TELEMETRY_PERIOD`] inputs
+/// reports the RMS of its inputs and outputs over the period on telemetry channels 0 and 1.
+/// Its ADC converts over the profile's full scale, and noise is generated from `seed`, so runs
+/// are reproducible
+pub fn spawn(device: Device, artifacts: Artifacts, profile: &Profile, seed: u32) -> Link {
+    let (tx, mut input) = pipe();
+    let (mut output, rx) = pipe();
+    let front_end = FrontEnd::new(artifacts, profile.full_scale, seed);
+
+    thread::spawn(move || {
+        if let Err(e) = emulate(device, front_end, &mut input, &mut output) {
+            tracing::error!("Emulated device failed: {e}");
+        }
+    });
+
+    Link {
+        tx: Box::new(tx),
+        rx: Box::new(rx),
+    }
+}
+
+fn emulate(
+    device: Device,
+    mut front_end: FrontEnd,
+    input: &mut PipeReader,
+    output: &mut PipeWriter,
+) -> io::Result<()> {
+    const SAMPLE_SIZE: usize = mem::size_of::<f32>();
+
+    let mut syn = [0u8; SAMPLE_SIZE];
+
+    input.read_exact(&mut syn)?;
+    if syn != crate::SYN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "expected SYN"));
+    }
+
+    let mut announced = SAMPLING_FREQUENCY;
+    let mut extensions = Vec::new();
+
+    if device.output_rate() != SAMPLING_FREQUENCY {
+        announced |= crate::MULTI_RATE;
+        extensions.push(device.output_rate());
+    }
+
+    if let Some(block) = device.block_size() {
+        announced |= crate::BLOCK_MODE;
+        extensions.push(block);
+    }
+
+    if device.channels() > 1 {
+        announced |= crate::MULTI_CHANNEL;
+        extensions.push(device.channels());
+    }
+
+    output.write_all(crate::ACK)?;
+    output.write_all(&announced.to_le_bytes())?;
+    for extension in extensions {
+        output.write_all(&extension.to_le_bytes())?;
+    }
+
+    let mut output = Framer::new(output);
+    log(
+        &mut output,
+        Severity::Info,
+        &format!("Filtering as {device} at {SAMPLING_FREQUENCY} Hz"),
+    )?;
+
+    let mut buffer = [0u8; crate::RX_BUFFER_SIZE];
+    let mut deframer = Deframer::default();
+    let mut filter = device.filter();
+    // Sums of squared inputs and outputs over the current telemetry period
+    let mut energy = [0.0f32; 2];
+    let mut count = 0usize;
+    // Outputs and reports of the frame, or block, being processed
+    let mut processed = Vec::new();
+
+    loop {
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        deframer.push(&buffer[..read]);
+
+        while let Some(payload) = deframer.next_payload() {
+            if payload.is_empty() {
+                log(
+                    &mut output,
+                    Severity::Info,
+                    &format!("Filtered {count} samples"),
+                )?;
+                return output.end();
+            }
+
+            let mut words = payload.chunks_exact(SAMPLE_SIZE);
+            while let Some(word) = words.next() {
+                if word == crate::TIME_SYNC {
+                    let timestamp = words.next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "expected a timestamp")
+                    })?;
+
+                    let index = u32::try_from(count).unwrap_or(u32::MAX);
+                    processed.extend_from_slice(crate::TIME_SYNC);
+                    processed.extend_from_slice(timestamp);
+                    processed.extend_from_slice(&index.to_le_bytes());
+                    continue;
+                }
+
+                let x = front_end.sample(f32::from_le_bytes(
+                    word.try_into().expect("sample sized chunk"),
+                ));
+                energy[0] += x * x;
+
+                if let Some(y) = filter(x) {
+                    energy[1] += y * y;
+                    processed.extend_from_slice(&y.to_le_bytes());
+
+                    if device.channels() > 1 {
+                        processed.extend_from_slice(&x.to_le_bytes());
+                        processed.extend_from_slice(&(x - y).to_le_bytes());
+                    }
+                }
+
+                count += 1;
+                if device
+                    .block_size()
+                    .map_or(false, |block| count % block as usize == 0)
+                {
+                    output.send(&processed)?;
+                    processed.clear();
+                }
+
+                if count % TELEMETRY_PERIOD == 0 {
+                    for (channel, energy) in (0u32..).zip(&mut energy) {
+                        let rms = (*energy / TELEMETRY_PERIOD as f32).sqrt();
+                        *energy = 0.0;
+
+                        processed.extend_from_slice(crate::TELEMETRY);
+                        processed.extend_from_slice(&channel.to_le_bytes());
+                        processed.extend_from_slice(&rms.to_le_bytes());
+                    }
+                }
+            }
+
+            if device.block_size().is_none() && !processed.is_empty() {
+                output.send(&processed)?;
+                processed.clear();
+            }
+        }
+    }
+}
+
+/// Sends a device log frame with the given severity
+fn log(output: &mut Framer<&mut PipeWriter>, severity: Severity, message: &str) -> io::Result<()> {
+    let severity = severity as u32;
+    let length = u32::try_from(message.len()).expect("short message");
+    let mut frame = [crate::LOG, &severity.to_le_bytes(), &length.to_le_bytes()].concat();
+
+    frame.extend_from_slice(message.as_bytes());
+    frame.resize((frame.len() + 3) / 4 * 4, 0);
+
+    output.send(&frame)
+}
+
+/// Direct form I biquad section
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    /// Previous inputs
+    x: [f32; 2],
+    /// Previous outputs
+    y: [f32; 2],
+}
+
+impl Biquad {
+    fn new([b0, b1, b2]: [f32; 3], [a0, a1, a2]: [f32; 3]) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Butterworth low-pass, through the bilinear transform
+    fn low_pass(cutoff: f32, fs: f32) -> Self {
+        let k = (PI * cutoff / fs).tan();
+        let k2 = k * k;
+
+        Self::new(
+            [k2, 2.0 * k2, k2],
+            [
+                1.0 + k / FRAC_1_SQRT_2 + k2,
+                2.0 * (k2 - 1.0),
+                1.0 - k / FRAC_1_SQRT_2 + k2,
+            ],
+        )
+    }
+
+    fn notch(frequency: f32, fs: f32) -> Self {
+        const Q: f32 = 10.0;
+
+        let w0 = 2.0 * PI * frequency / fs;
+        let alpha = w0.sin() / (2.0 * Q);
+        let cos = w0.cos();
+
+        Self::new(
+            [1.0, -2.0 * cos, 1.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x[0] + self.b2 * self.x[1]
+            - self.a1 * self.y[0]
+            - self.a2 * self.y[1];
+
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+
+        y
+    }
+}
+
+#[derive(Default)]
+struct MovingAverage<const N: usize> {
+    window: [f32; N],
+    next: usize,
+}
+
+impl<const N: usize> MovingAverage<N> {
+    fn process(&mut self, x: f32) -> f32 {
+        self.window[self.next] = x;
+        self.next = (self.next + 1) % N;
+
+        self.window.iter().sum::<f32>() / N as f32
+    }
+}
+
+/// In-memory byte stream between the host and an emulated device
+fn pipe() -> (PipeWriter, PipeReader) {
+    let (sender, receiver) = mpsc::channel();
+
+    (
+        PipeWriter(sender),
+        PipeReader {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        },
+    )
+}
+
+struct PipeWriter(Sender<Vec<u8>>);
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    /// Partially read chunk
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunk.len() {
+            // Reads 0 bytes once the writer is gone
+            let Ok(chunk) = self.receiver.recv() else {
+                return Ok(0);
+            };
+
+            self.chunk = chunk;
+            self.position = 0;
+        }
+
+        let read = (&self.chunk[self.position..]).read(buf)?;
+        self.position += read;
+
+        Ok(read)
+    }
+}
+
+impl Source for PipeReader {}
@@ -0,0 +1,532 @@
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    super::{config::Config, persist, schema::Schema, signal::TimeBase},
+    emulator::{self, Artifacts},
+    graph::Graph,
+    log::Entry,
+    storage::Samples,
+};
+
+/// Layout of exported files
+///
+/// - 1.0: the original layout
+/// - 1.1: adds the application version, sampling rate, generated and received inputs, and a
+///   checksum
+/// - 1.2: adds the function, stop time, device and timestamps of the run
+pub const SCHEMA: Schema = Schema::new(1, 2);
+
+/// Exported file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub const ALL: [Self; 2] = [Self::Json, Self::Csv];
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+        })
+    }
+}
+
+/// Describes how a run's input was generated
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub seed: u32,
+    /// Gain applied to normalize the input, if it was
+    pub gain: Option<f32>,
+    pub time_base: TimeBase,
+    /// Samples per repetition of the input
+    pub cycle: usize,
+    /// Input sampling frequency in Hz
+    pub sampling_rate: f32,
+    /// Output sampling frequency in Hz, if it differs from the input's, in which case inputs
+    /// and outputs sharing an index aren't simultaneous
+    pub output_rate: Option<f32>,
+    pub provenance: Provenance,
+}
+
+/// Where a run comes from, unknown for runs exported before it was recorded
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    /// Expression evaluated for the input, or description of its preset
+    pub function: Option<String>,
+    /// Simulated time of one cycle, in seconds
+    pub stop_time: Option<f32>,
+    /// Serial port, or emulated device, filtering the run
+    pub device: Option<String>,
+    /// When transmission started, in seconds since the Unix epoch
+    pub started: Option<f64>,
+    /// When the last sample was sent, in seconds since the Unix epoch
+    pub ended: Option<f64>,
+}
+
+impl Provenance {
+    /// Seconds since the Unix epoch at `time`
+    pub fn timestamp(time: SystemTime) -> f64 {
+        time.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+}
+
+/// Inputs of a run, from their generation to their reception by the device
+pub struct Inputs {
+    /// Values of the function over one cycle, before normalization and clamping
+    pub generated: Arc<Samples>,
+    /// Samples prepared for transmission
+    pub transmitted: Arc<Samples>,
+    /// Number of [`Self::transmitted`] samples actually sent, fewer if the run was cancelled
+    pub sent: usize,
+    /// Front end of the emulated device, with its full scale, if the run was emulated
+    pub front_end: Option<(Artifacts, f32)>,
+}
+
+/// Export progress, shared with the exporting task
+///
+/// Counts values written, or frames rendered for animations
+#[derive(Default)]
+pub struct Progress {
+    /// Number of values to write
+    total: AtomicUsize,
+    /// Number of values written
+    written: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Values are accounted for in batches of this size
+    const BATCH: usize = 4096;
+
+    pub fn fraction(&self) -> f32 {
+        let total = self.total.load(Ordering::Relaxed);
+        let written = self.written.load(Ordering::Relaxed);
+
+        if total == 0 {
+            0.0
+        } else {
+            written as f32 / total as f32
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Starts accounting for `total` values
+    pub fn start(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Accounts for the `i`-th value, failing if cancellation was requested
+    fn advance(&self, i: usize) -> io::Result<()> {
+        if i % Self::BATCH != 0 {
+            return Ok(());
+        }
+
+        self.add(Self::BATCH.min(i))
+    }
+
+    /// Accounts for `count` more values, failing if cancellation was requested
+    pub fn add(&self, count: usize) -> io::Result<()> {
+        self.written.fetch_add(count, Ordering::Relaxed);
+
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(io::Error::new(io::ErrorKind::Interrupted, "export cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Asks where to export in `format`, starting from the directory of the last exported file
+pub async fn pick_path(format: Format) -> Option<PathBuf> {
+    let extension = format.extension();
+    let file_name = format!("{}.{extension}", crate::FILENAME);
+
+    save_dialog("Export outputs", &file_name, &format.to_string(), extension).await
+}
+
+/// Asks where to export an animation, like [`pick_path`]
+pub async fn pick_animation_path() -> Option<PathBuf> {
+    save_dialog(
+        "Export animation",
+        crate::ANIMATION_FILENAME,
+        "Animated GIF",
+        "gif",
+    )
+    .await
+}
+
+async fn save_dialog(
+    title: &str,
+    file_name: &str,
+    filter: &str,
+    extension: &str,
+) -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .set_file_name(file_name)
+        .add_filter(filter, &[extension]);
+
+    if let Some(directory) = Config::load()
+        .recent_files
+        .first()
+        .and_then(|file| file.parent())
+    {
+        dialog = dialog.set_directory(directory);
+    }
+
+    dialog.save_file().await.map(|file| file.path().to_owned())
+}
+
+/// Exports a run to `path` on the blocking thread pool, returning it
+///
+/// Written with [`persist::write_atomic`], so cancelled or failed exports never leave
+/// truncated files behind. Files end with the checksum of their contents, for
+/// [`persist::verify`]
+pub async fn export(
+    path: PathBuf,
+    format: Format,
+    metadata: Metadata,
+    pre_trigger: Vec<f32>,
+    log: Vec<Entry>,
+    inputs: Inputs,
+    output: Arc<Mutex<Samples>>,
+    progress: Arc<Progress>,
+) -> io::Result<PathBuf> {
+    tokio::task::spawn_blocking(move || {
+        let input = &inputs.transmitted[..inputs.sent.min(inputs.transmitted.len())];
+        let received = inputs.front_end.map(|(artifacts, full_scale)| {
+            emulator::condition(artifacts, full_scale, metadata.seed, input)
+        });
+        let output = output.lock();
+
+        let total = pre_trigger.len()
+            + match format {
+                Format::Json => {
+                    inputs.generated.len()
+                        + input.len()
+                        + received.as_ref().map_or(0, Vec::len)
+                        + output.len()
+                }
+                Format::Csv => input.len().max(output.len()),
+            };
+        progress.start(total);
+
+        let series = Series {
+            pre_trigger: &pre_trigger,
+            log: &log,
+            generated: &inputs.generated,
+            input,
+            received: received.as_deref(),
+            output: &output,
+        };
+
+        persist::write_atomic(&path, |writer| {
+            let mut writer = persist::Checksummed::new(writer);
+
+            match format {
+                Format::Json => write_json(&mut writer, &progress, metadata, series)?,
+                Format::Csv => write_csv(&mut writer, &progress, metadata, series)?,
+            }
+
+            let checksum = writer.checksum();
+            match format {
+                Format::Json => write!(writer, "{}{checksum:08x}\"}}", persist::CHECKSUM_MEMBER),
+                Format::Csv => writeln!(writer, "{}{checksum:08x}", persist::CHECKSUM_COMMENT),
+            }
+        })?;
+
+        Ok(path)
+    })
+    .await
+    .expect("export task ran")
+}
+
+/// Renders `graph` into an animation at `path` on the blocking thread pool, returning it
+///
+/// Rendered next to `path` first, so cancelled or failed exports never leave truncated files
+/// behind
+pub async fn animate(graph: Graph, path: PathBuf, progress: Arc<Progress>) -> io::Result<PathBuf> {
+    tokio::task::spawn_blocking(move || {
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let file = tempfile::NamedTempFile::new_in(directory)?;
+
+        graph.export_animation(file.path(), &progress)?;
+        file.persist(&path).map_err(|e| e.error)?;
+
+        Ok(path)
+    })
+    .await
+    .expect("animation task ran")
+}
+
+/// Samples of a run
+#[derive(Clone, Copy)]
+struct Series<'a> {
+    /// Outputs received before transmission started
+    pre_trigger: &'a [f32],
+    /// Messages logged by the device
+    log: &'a [Entry],
+    /// Values of the function over one cycle
+    generated: &'a [f32],
+    /// Samples sent to the device
+    input: &'a [f32],
+    /// Inputs as received by an emulated device's front end
+    received: Option<&'a [f32]>,
+    output: &'a [f32],
+}
+
+/// Streams `{"schema": .., "version": .., "seed": .., "gain": .., "time_base": {..},
+/// "cycle": .., "sampling_rate": .., "output_rate": .., "function": .., "stop_time": ..,
+/// "device": .., "started": .., "ended": .., "log": [..], "pre_trigger": [..],
+/// "generated": [..], "input": [..], "received": [..], "output": [..]` without building it in
+/// memory, leaving the object open for its checksum
+///
+/// `received` is `null` unless the device was emulated, unknown provenance is `null` too
+fn write_json(
+    writer: &mut impl Write,
+    progress: &Progress,
+    Metadata {
+        seed,
+        gain,
+        time_base,
+        cycle,
+        sampling_rate,
+        output_rate,
+        provenance,
+    }: Metadata,
+    Series {
+        pre_trigger,
+        log,
+        generated,
+        input,
+        received,
+        output,
+    }: Series<'_>,
+) -> io::Result<()> {
+    fn array(writer: &mut impl Write, progress: &Progress, samples: &[f32]) -> io::Result<()> {
+        writer.write_all(b"[")?;
+
+        for (i, sample) in samples.iter().enumerate() {
+            progress.advance(i)?;
+
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+
+            // Matches serde_json, which has no representation for non-finite numbers
+            if sample.is_finite() {
+                write!(writer, "{sample}")?;
+            } else {
+                writer.write_all(b"null")?;
+            }
+        }
+
+        writer.write_all(b"]")
+    }
+
+    write!(
+        writer,
+        r#"{{"schema":"{SCHEMA}","version":"{}","seed":{seed},"gain":"#,
+        crate::VERSION
+    )?;
+    match gain {
+        Some(gain) => write!(writer, "{gain}")?,
+        None => writer.write_all(b"null")?,
+    }
+
+    let TimeBase { endpoint, centered } = time_base;
+    write!(
+        writer,
+        r#","time_base":{{"endpoint":{endpoint},"centered":{centered}}},"cycle":{cycle}"#
+    )?;
+    write!(writer, r#","sampling_rate":{sampling_rate}"#)?;
+
+    writer.write_all(br#","output_rate":"#)?;
+    match output_rate {
+        Some(rate) => write!(writer, "{rate}")?,
+        None => writer.write_all(b"null")?,
+    }
+
+    let Provenance {
+        function,
+        stop_time,
+        device,
+        started,
+        ended,
+    } = provenance;
+    write!(
+        writer,
+        r#","function":{},"stop_time":{},"device":{},"started":{},"ended":{}"#,
+        serde_json::to_string(&function)?,
+        serde_json::to_string(&stop_time)?,
+        serde_json::to_string(&device)?,
+        serde_json::to_string(&started)?,
+        serde_json::to_string(&ended)?
+    )?;
+
+    writer.write_all(br#","log":["#)?;
+    for (i, entry) in log.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+
+        write!(
+            writer,
+            r#"{{"index":{},"severity":"{}","message":{}}}"#,
+            entry.index,
+            entry.severity,
+            serde_json::to_string(&entry.message)?
+        )?;
+    }
+
+    writer.write_all(br#"],"pre_trigger":"#)?;
+    array(writer, progress, pre_trigger)?;
+    writer.write_all(br#","generated":"#)?;
+    array(writer, progress, generated)?;
+    writer.write_all(br#","input":"#)?;
+    array(writer, progress, input)?;
+    writer.write_all(br#","received":"#)?;
+    match received {
+        Some(received) => array(writer, progress, received)?,
+        None => writer.write_all(b"null")?,
+    }
+    writer.write_all(br#","output":"#)?;
+    array(writer, progress, output)
+}
+
+/// Writes one `index,cycle,generated,input,received,output` row per sample, leaving missing
+/// values empty
+///
+/// Outputs received before transmission started come first, at negative indices and without
+/// a cycle. Generated values only span the first cycle, and received ones are only known for
+/// emulated devices
+fn write_csv(
+    writer: &mut impl Write,
+    progress: &Progress,
+    Metadata {
+        seed,
+        gain,
+        time_base,
+        cycle,
+        sampling_rate,
+        output_rate,
+        provenance,
+    }: Metadata,
+    Series {
+        pre_trigger,
+        log,
+        generated,
+        input,
+        received,
+        output,
+    }: Series<'_>,
+) -> io::Result<()> {
+    writeln!(writer, "# schema={SCHEMA}")?;
+    writeln!(writer, "# version={}", crate::VERSION)?;
+    writeln!(writer, "# seed={seed}")?;
+
+    if let Some(gain) = gain {
+        writeln!(writer, "# gain={gain}")?;
+    }
+
+    writeln!(writer, "# endpoint={}", time_base.endpoint)?;
+    writeln!(writer, "# centered={}", time_base.centered)?;
+    writeln!(writer, "# cycle={cycle}")?;
+    writeln!(writer, "# sampling_rate={sampling_rate}")?;
+
+    if let Some(rate) = output_rate {
+        writeln!(writer, "# output_rate={rate}")?;
+    }
+
+    let Provenance {
+        function,
+        stop_time,
+        device,
+        started,
+        ended,
+    } = provenance;
+    if let Some(function) = function {
+        writeln!(writer, "# function={}", function.escape_debug())?;
+    }
+    if let Some(stop_time) = stop_time {
+        writeln!(writer, "# stop_time={stop_time}")?;
+    }
+    if let Some(device) = device {
+        writeln!(writer, "# device={}", device.escape_debug())?;
+    }
+    if let Some(started) = started {
+        writeln!(writer, "# started={started}")?;
+    }
+    if let Some(ended) = ended {
+        writeln!(writer, "# ended={ended}")?;
+    }
+
+    for Entry {
+        index,
+        severity,
+        message,
+    } in log
+    {
+        writeln!(
+            writer,
+            "# log={index},{severity},{}",
+            message.escape_debug()
+        )?;
+    }
+
+    writeln!(writer, "index,cycle,generated,input,received,output")?;
+
+    for (i, sample) in pre_trigger.iter().enumerate() {
+        progress.advance(i)?;
+        writeln!(writer, "-{},,,,,{sample}", pre_trigger.len() - i)?;
+    }
+
+    let received = received.unwrap_or_default();
+
+    for i in 0..input.len().max(output.len()) {
+        progress.advance(i)?;
+        write!(writer, "{i},{}", i / cycle.max(1))?;
+
+        for column in [generated, input, received, output] {
+            writer.write_all(b",")?;
+
+            if let Some(sample) = column.get(i) {
+                write!(writer, "{sample}")?;
+            }
+        }
+
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, Write},
+    mem,
+    num::ParseFloatError,
+};
+
+use super::inspector::{self, Frame};
+
+/// Size of a frame's start, length and sequence number, in bytes
+const HEADER: usize = crate::FRAME_START.len() + 4;
+/// Size of the CRC ending a frame, in bytes
+const CRC: usize = 2;
+/// Longest accepted line of the ASCII protocol, in bytes. Longer ones are assumed to be noise
+const MAX_LINE: usize = 256;
+
+/// How samples are carried over the link
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Protocol {
+    /// Frames of little-endian samples, as described in [`crate::FRAME_START`]
+    #[default]
+    Binary,
+    /// Lines of decimal samples, for devices that don't frame their outputs yet. An empty line
+    /// ends the transmission, and neither sideband reports nor time synchronization are carried
+    Ascii,
+}
+
+impl Protocol {
+    pub const ALL: [Self; 2] = [Self::Binary, Self::Ascii];
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Binary => "Binary frames",
+            Self::Ascii => "ASCII lines",
+        })
+    }
+}
+
+/// CRC-16/CCITT-FALSE of `bytes`
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x1021
+            }
+        })
+    })
+}
+
+/// Writes payloads as frames numbered in sequence, as described in [`crate::FRAME_START`], or
+/// as lines of their samples in the ASCII protocol
+pub struct Framer<W> {
+    inner: W,
+    /// Of the next frame
+    sequence: u16,
+    protocol: Protocol,
+}
+
+impl<W: Write> Framer<W> {
+    pub const fn new(inner: W) -> Self {
+        Self::with_protocol(inner, Protocol::Binary)
+    }
+
+    pub const fn with_protocol(inner: W, protocol: Protocol) -> Self {
+        Self {
+            inner,
+            sequence: 0,
+            protocol,
+        }
+    }
+
+    /// Sends `payload` in one frame
+    ///
+    /// # Errors
+    /// Fails if `payload` is longer than [`crate::MAX_FRAME_PAYLOAD`], or can't be written
+    pub fn send(&mut self, payload: &[u8]) -> io::Result<()> {
+        let length = u16::try_from(payload.len())
+            .ok()
+            .filter(|&length| usize::from(length) <= crate::MAX_FRAME_PAYLOAD)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("frame payload of {} bytes is too long", payload.len()),
+                )
+            })?;
+
+        if self.protocol == Protocol::Ascii {
+            return self.inner.write_all(lines(payload).as_bytes());
+        }
+
+        let mut frame = Vec::with_capacity(HEADER + payload.len() + CRC);
+        frame.extend_from_slice(crate::FRAME_START);
+        frame.extend_from_slice(&length.to_le_bytes());
+        frame.extend_from_slice(&self.sequence.to_le_bytes());
+        frame.extend_from_slice(payload);
+
+        let crc = crc16(&frame[crate::FRAME_START.len()..]);
+        frame.extend_from_slice(&crc.to_le_bytes());
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.inner.write_all(&frame)
+    }
+
+    /// Sends the empty frame ending the transmission
+    ///
+    /// # Errors
+    /// Fails if the frame can't be written
+    pub fn end(&mut self) -> io::Result<()> {
+        self.send(&[])
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Samples of `payload` as decimal lines, or the empty line ending the transmission
+fn lines(payload: &[u8]) -> String {
+    if payload.is_empty() {
+        return "\n".to_owned();
+    }
+
+    payload
+        .chunks_exact(mem::size_of::<f32>())
+        .map(|word| {
+            let sample = f32::from_le_bytes(word.try_into().expect("word sized chunk"));
+            format!("{sample}\n")
+        })
+        .collect()
+}
+
+/// Reads payloads out of a stream of frames, as described in [`crate::FRAME_START`], or of
+/// lines in the ASCII protocol
+///
+/// Corrupted frames are skipped by resynchronizing on the next start, and counted as missing
+/// from the sequence once it resumes. Payloads are borrowed from the received bytes, which are
+/// only moved once most of them were deframed
+#[derive(Debug, Default)]
+pub struct Deframer {
+    protocol: Protocol,
+    /// Received bytes, deframed up to [`Self::cursor`]
+    buffer: Vec<u8>,
+    /// Start of the bytes of [`Self::buffer`] not deframed yet
+    cursor: usize,
+    /// Samples of the last line in the ASCII protocol, reused from line to line
+    line: Vec<u8>,
+    /// Sequence number of the next frame
+    sequence: u16,
+    /// Frames missing from the sequence
+    lost: usize,
+    /// Skipping invalid frames, since the last valid one
+    resynchronizing: bool,
+    /// Where decoded frames are recorded, if anywhere
+    inspector: Option<inspector::Shared>,
+}
+
+impl Deframer {
+    pub fn with_protocol(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            ..Self::default()
+        }
+    }
+
+    /// Records the frames decoded from now on into `inspector`, corrupted ones included
+    pub fn inspect(&mut self, inspector: inspector::Shared) {
+        self.inspector = Some(inspector);
+    }
+
+    /// Queues received `bytes`
+    pub fn push(&mut self, bytes: &[u8]) {
+        // Moving the bytes left once they're outnumbered by those deframed keeps it amortized
+        if self.cursor > self.buffer.len() / 2 {
+            self.buffer.drain(..self.cursor);
+            self.cursor = 0;
+        }
+
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Received bytes not deframed yet
+    fn pending(&self) -> &[u8] {
+        &self.buffer[self.cursor..]
+    }
+
+    /// Payload of the next complete frame, if any, which is empty at the end of the transmission
+    pub fn next_payload(&mut self) -> Option<&[u8]> {
+        if self.protocol == Protocol::Ascii {
+            return self.next_line();
+        }
+
+        loop {
+            let pending = self.pending();
+            let Some(start) = pending
+                .windows(crate::FRAME_START.len())
+                .position(|window| window == crate::FRAME_START)
+            else {
+                // Short of a trailing partial start
+                let partial = pending.last() == crate::FRAME_START.first();
+                let skipped = pending.len() - usize::from(partial);
+                self.skip(skipped);
+                return None;
+            };
+            self.skip(start);
+
+            let pending = self.pending();
+            if pending.len() < HEADER {
+                return None;
+            }
+
+            let length = usize::from(field(pending, crate::FRAME_START.len()));
+            let sequence = field(pending, crate::FRAME_START.len() + 2);
+
+            if length > crate::MAX_FRAME_PAYLOAD {
+                self.reject(&format!("invalid length of {length} bytes"));
+                continue;
+            }
+
+            let end = HEADER + length + CRC;
+            if pending.len() < end {
+                return None;
+            }
+
+            let crc = field(pending, end - CRC);
+            if crc16(&pending[crate::FRAME_START.len()..end - CRC]) != crc {
+                self.reject("CRC mismatch");
+                continue;
+            }
+
+            if sequence != self.sequence {
+                let lost = sequence.wrapping_sub(self.sequence);
+                tracing::warn!("Lost {lost} frames before frame {sequence}");
+                self.lost += usize::from(lost);
+            }
+            self.sequence = sequence.wrapping_add(1);
+            self.resynchronizing = false;
+            self.record(|| {
+                Frame::binary(sequence, length, &self.pending()[HEADER..end - CRC], None)
+            });
+
+            let frame = self.cursor;
+            self.cursor += end;
+
+            return Some(&self.buffer[frame + HEADER..frame + end - CRC]);
+        }
+    }
+
+    /// Samples of the next complete line as a payload, which is empty at the end of the
+    /// transmission
+    ///
+    /// Samples are separated by commas or whitespace, and lines ending in `\r\n` are accepted.
+    /// Lines that aren't samples, such as banners, are dropped and counted as lost
+    fn next_line(&mut self) -> Option<&[u8]> {
+        loop {
+            let Some(end) = self.pending().iter().position(|&byte| byte == b'\n') else {
+                if self.pending().len() > MAX_LINE {
+                    tracing::warn!("Dropping a line longer than {MAX_LINE} bytes");
+                    self.record(|| {
+                        let line = String::from_utf8_lossy(self.pending());
+                        Frame::line(&line, Some("too long".to_owned()))
+                    });
+                    self.cursor = self.buffer.len();
+                    self.lost += 1;
+                }
+
+                return None;
+            };
+
+            let start = self.cursor;
+            self.cursor += end + 1;
+            let line = String::from_utf8_lossy(&self.buffer[start..self.cursor]);
+            let line = line.trim();
+
+            if line.is_empty() {
+                self.record(|| Frame::line(line, None));
+                return Some(&[]);
+            }
+
+            self.line.clear();
+            let samples = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .try_for_each(|value| {
+                    let sample: f32 = value.parse()?;
+                    self.line.extend_from_slice(&sample.to_le_bytes());
+                    Ok::<_, ParseFloatError>(())
+                });
+
+            match samples {
+                Ok(()) => {
+                    self.record(|| Frame::line(line, None));
+                    return Some(&self.line);
+                }
+
+                Err(e) => {
+                    tracing::warn!("Dropping line {line:?}: {e}");
+                    self.record(|| Frame::line(line, Some(e.to_string())));
+                    self.lost += 1;
+                }
+            }
+        }
+    }
+
+    /// Number of frames corrupted or lost so far, as far as the sequence went
+    pub const fn lost(&self) -> usize {
+        self.lost
+    }
+
+    /// Discards `bytes` preceding the next frame
+    fn skip(&mut self, bytes: usize) {
+        if bytes > 0 {
+            tracing::debug!("Skipping {bytes} bytes outside of frames");
+            self.cursor += bytes;
+        }
+    }
+
+    /// Drops the frame at the start of the buffer, resynchronizing from its next byte
+    ///
+    /// Starts found while resynchronizing may well be payload bytes, so only the first invalid
+    /// frame is reported
+    fn reject(&mut self, reason: &str) {
+        if !self.resynchronizing {
+            tracing::warn!(
+                "Dropping corrupted frame, expecting frame {}: {reason}",
+                self.sequence
+            );
+            self.record(|| {
+                let pending = self.pending();
+                let length = usize::from(field(pending, crate::FRAME_START.len()));
+                let sequence = field(pending, crate::FRAME_START.len() + 2);
+                let payload = &pending[HEADER..pending.len().min(HEADER + length)];
+
+                Frame::binary(sequence, length, payload, Some(reason.to_owned()))
+            });
+            self.resynchronizing = true;
+        }
+
+        self.cursor += 1;
+    }
+
+    /// Records the frame made by `frame` into the inspector, if any
+    fn record(&self, frame: impl FnOnce() -> Frame) {
+        if let Some(inspector) = &self.inspector {
+            crate::perf::lock(inspector).record(frame());
+        }
+    }
+}
+
+/// Little-endian `u16` at `at` in `bytes`
+fn field(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
@@ -1,20 +1,65 @@
 use iced::{
     alignment::Horizontal,
-    widget::{button, column, row, slider, text},
-    Element, Length,
+    mouse,
+    widget::{
+        button,
+        canvas::{self, event::Status, Cursor},
+        checkbox, column, pick_list, row, slider, text,
+    },
+    Alignment, Element, Length, Rectangle,
 };
 use parking_lot::Mutex;
+use plotters::{coord::ReverseCoordTranslate, prelude::*};
 use plotters_iced::{Chart, ChartBuilder, ChartWidget};
-use std::{fs::File, io, sync::Arc};
+use std::{
+    cell::Cell, fmt, io, iter, ops::Range, path::Path, sync::Arc, time::Instant,
+};
+
+use super::{
+    super::{profile::Profile, theme},
+    analysis::{Detrend, Detrending, Trend},
+    dsp::{self, Design, Family},
+    export::Progress,
+    hilbert,
+    snapshot::Snapshot,
+    spectrum,
+    storage::Samples,
+    workers::{Channels, PreTrigger},
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Message {
+    TabSelected(Tab),
     SwitchMode,
+    ToggleSettings,
+    XLinesUpdated(u8),
+    YLinesUpdated(u8),
+    MinorLinesToggled(bool),
+    AlignToggled(bool),
+    InputDetrendSelected(Detrend),
+    OutputDetrendSelected(Detrend),
+    EnvelopeToggled(bool),
+    FrequencyToggled(bool),
+    ReferenceToggled(bool),
+    ReferenceFamilySelected(Family),
+    ReferenceResponseSelected(dsp::Response),
+    ReferenceOrderUpdated(u8),
+    ReferenceCutoffUpdated(f32),
+    ReferenceRippleUpdated(f32),
+    /// Shows or hides the numbered output channel
+    ChannelToggled(usize, bool),
+    Hovered(Option<f32>),
+    RenderingSelected(Rendering),
+    PaletteSelected(Palette),
+    ResolutionSelected(Resolution),
     SizeUpdated(f64),
     OffsetUpdated(f64),
+    /// Static window's new size and offset, both changed when zooming around the cursor
+    Zoomed(usize, usize),
 }
 
 /// Streaming or static modes for graph
+#[derive(Debug, Clone, Copy)]
 enum Mode {
     /// Only the latest samples will be shown
     Streaming,
@@ -27,34 +72,366 @@ enum Mode {
     },
 }
 
+/// What the chart plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    /// Samples against time
+    Time,
+    /// Magnitude spectra of the input and output
+    Spectrum,
+}
+
+impl Tab {
+    const ALL: [Self; 2] = [Self::Time, Self::Spectrum];
+}
+
+impl fmt::Display for Tab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Time => "Time",
+            Self::Spectrum => "Spectrum",
+        })
+    }
+}
+
+/// How samples are joined when drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rendering {
+    /// Straight line interpolation between samples
+    Line,
+    /// Sample-and-hold stair-steps
+    Steps,
+    /// A marker per sample, without joining lines
+    Markers,
+}
+
+impl Rendering {
+    const ALL: [Self; 3] = [Self::Line, Self::Steps, Self::Markers];
+}
+
+impl fmt::Display for Rendering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Line => "Line",
+            Self::Steps => "Sample-and-hold",
+            Self::Markers => "Markers",
+        })
+    }
+}
+
+/// Series colors and line patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    /// Input and output colors of the theme, then magenta and green
+    Classic,
+    /// Okabe-Ito colors, distinguishable with common color vision deficiencies
+    ColorblindSafe,
+    /// Shades of gray, told apart by dash pattern only
+    Monochrome,
+}
+
+impl Palette {
+    const ALL: [Self; 3] = [Self::Classic, Self::ColorblindSafe, Self::Monochrome];
+
+    /// Color and dash pattern of the `i`-th series, drawn with `colors`
+    fn series(self, i: usize, colors: &theme::Colors) -> (RGBColor, Dash) {
+        const OKABE_ITO: [RGBColor; 6] = [
+            RGBColor(86, 180, 233),
+            RGBColor(230, 159, 0),
+            RGBColor(0, 158, 115),
+            RGBColor(240, 228, 66),
+            RGBColor(213, 94, 0),
+            RGBColor(204, 121, 167),
+        ];
+        const DASHES: [Dash; 3] = [Dash::Solid, Dash::Dashed, Dash::Dotted];
+
+        // The foreground, faded by quarters towards the background
+        let fade = |quarters: i16| {
+            let RGBColor(r, g, b) = colors.foreground;
+            let RGBColor(to_r, to_g, to_b) = colors.background;
+            let mix = |from: u8, to: u8| {
+                let faded = i16::from(from) + (i16::from(to) - i16::from(from)) * quarters / 4;
+                u8::try_from(faded).expect("faded between two colors")
+            };
+
+            RGBColor(mix(r, to_r), mix(g, to_g), mix(b, to_b))
+        };
+        let classic = [colors.input, colors.output, MAGENTA, GREEN];
+        let grays = [fade(0), fade(1), fade(2)];
+
+        match self {
+            Self::Classic => (classic[i % classic.len()], Dash::Solid),
+            Self::ColorblindSafe => (OKABE_ITO[i % OKABE_ITO.len()], DASHES[i % DASHES.len()]),
+            Self::Monochrome => (grays[i % grays.len()], DASHES[i % DASHES.len()]),
+        }
+    }
+
+    /// Color and dash pattern of the extra `channel`, numbered from 1, never those of the first
+    /// four series: the input, output, pre-trigger output and reference
+    fn channel(self, channel: usize, colors: &theme::Colors) -> (RGBColor, Dash) {
+        const CLASSIC: [RGBColor; 4] = [
+            RED,
+            RGBColor(255, 165, 0),
+            RGBColor(160, 100, 255),
+            RGBColor(255, 128, 192),
+        ];
+        const DASHES: [Dash; 3] = [Dash::Solid, Dash::Dashed, Dash::Dotted];
+        // Grays and dash patterns left unused by the first series, and the dotted reference
+        const MONOCHROME: [(usize, Dash); 5] = [
+            (0, Dash::Dashed),
+            (1, Dash::Dotted),
+            (2, Dash::Solid),
+            (1, Dash::Solid),
+            (2, Dash::Dashed),
+        ];
+
+        // The rest of the Okabe-Ito colors, with the foreground standing in for black
+        let okabe_ito = [
+            RGBColor(213, 94, 0),
+            RGBColor(204, 121, 167),
+            RGBColor(0, 114, 178),
+            colors.foreground,
+        ];
+
+        let i = channel - 1;
+        match self {
+            // Dashed, unlike the solid series in the theme's colors
+            Self::Classic => (CLASSIC[i % CLASSIC.len()], Dash::Dashed),
+            Self::ColorblindSafe => (
+                okabe_ito[i % okabe_ito.len()],
+                DASHES[i / okabe_ito.len() % DASHES.len()],
+            ),
+            Self::Monochrome => {
+                let (gray, dash) = MONOCHROME[i % MONOCHROME.len()];
+                (self.series(gray, colors).0, dash)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Classic => "Classic",
+            Self::ColorblindSafe => "Colorblind-safe",
+            Self::Monochrome => "Monochrome",
+        })
+    }
+}
+
+/// Line pattern of a series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dash {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Dash {
+    /// Dash length and spacing, in pixels
+    const fn pattern(self) -> Option<(u32, u32)> {
+        match self {
+            Self::Solid => None,
+            Self::Dashed => Some((10, 6)),
+            Self::Dotted => Some((2, 5)),
+        }
+    }
+}
+
+/// Chart mesh configuration
+#[derive(Debug, Clone, Copy)]
+struct Grid {
+    /// Number of vertical gridlines, each of which is labelled
+    x_lines: u8,
+    /// Number of horizontal gridlines, each of which is labelled
+    y_lines: u8,
+    /// Draw unlabelled lines between gridlines
+    minor_lines: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            x_lines: 10,
+            y_lines: 10,
+            minor_lines: false,
+        }
+    }
+}
+
+/// Series derived from the shown samples through the Hilbert transform
+#[derive(Debug, Clone, Copy, Default)]
+struct Derived {
+    /// Instantaneous amplitude, on the sample axis
+    envelope: bool,
+    /// Instantaneous frequency, on a secondary axis
+    frequency: bool,
+}
+
+/// Image export resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub const ALL: [Self; 4] = [
+        Self::new(1920, 1080),
+        Self::new(3000, 2000),
+        Self::new(3840, 2160),
+        Self::new(6000, 4000),
+    ];
+
+    const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// Stroke and font scale relative to the on-screen chart
+    fn scale(self) -> u32 {
+        (self.height / 540).max(1)
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}×{}", self.width, self.height)
+    }
+}
+
 pub struct Graph {
+    /// Plotted domain
+    tab: Tab,
     /// Current graph mode
     mode: Mode,
-    /// Time vector
-    time: Vec<f32>,
+    /// How series are drawn
+    rendering: Rendering,
+    /// Series colors and line patterns
+    palette: Palette,
+    /// Gridline density
+    grid: Grid,
+    /// Overlaid derived series
+    derived: Derived,
+    /// Filter computed on the host from the inputs, to compare against the device
+    design: Design,
+    /// Inputs filtered by [`Self::design`], when overlaid
+    reference: Option<Vec<f32>>,
+    /// Delay of the output behind the input in samples, once measured
+    lag: Option<isize>,
+    /// Shift the output back by [`Self::lag`], overlaying it on the input that caused it
+    align: bool,
+    /// Components removed from each series before drawing
+    detrending: Detrending,
+    /// Input and output trends fitted by the latest metrics
+    trends: (Trend, Trend),
+    /// Are the display settings expanded?
+    show_settings: bool,
+    /// Hovered time, shared by every panel showing a time axis
+    crosshair: Option<f32>,
+    /// Resolution of exported images
+    resolution: Resolution,
+    /// Range of the vertical axis
+    y_range: Range<f32>,
+    /// Unit of sample values
+    unit: String,
+    /// Time of the first input, the following ones being spaced by [`Self::sampling_interval`]
+    start: f32,
+    sampling_interval: f32,
+    /// Received data, as drawn
+    snapshot: Snapshot,
     /// Received data
-    filtered_data: Arc<Mutex<Vec<f32>>>,
+    filtered_data: Arc<Mutex<Samples>>,
+    /// Data received on the channels following the first, for multi-channel devices
+    channels: Channels,
+    /// Which output channels are drawn, starting with the first
+    shown: Vec<bool>,
+    /// Data received before transmission started, shown before `t = 0`
+    pre_trigger: PreTrigger,
     /// Unfiltered data
-    unfiltered_data: Arc<Vec<f32>>,
+    unfiltered_data: Arc<Samples>,
+    /// Samples per repetition of the input, boundaries are marked between them
+    cycle: usize,
+    /// Interval between outputs of decimating or interpolating devices, which are otherwise
+    /// shown at the time of their input
+    output_interval: Option<f32>,
+}
+
+/// Interaction state of the chart widget
+#[derive(Default)]
+pub struct ChartState {
+    /// Cursor position relative to the chart, if hovering
+    cursor: Option<(i32, i32)>,
+    /// Time axis of the last drawn frame
+    axis: Cell<Option<Axis>>,
+    /// Last time reported through [`Message::Hovered`]
+    hovered: Option<f32>,
+    /// Where the static window is being dragged from, with its offset then
+    dragged: Option<(i32, usize)>,
 }
 
-#[derive(serde::Serialize)]
-struct ExportedData<'a> {
-    input: &'a Vec<f32>,
-    output: &'a Vec<f32>,
+/// Maps horizontal pixel positions of a drawn chart to time
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    pixels: (i32, i32),
+    time: (f32, f32),
+}
+
+impl Axis {
+    fn time_at(self, x: i32) -> Option<f32> {
+        let (left, right) = self.pixels;
+        let (start, end) = self.time;
+
+        if left < right && (left..=right).contains(&x) {
+            Some(start + (end - start) * (x - left) as f32 / (right - left) as f32)
+        } else {
+            None
+        }
+    }
 }
 
 impl Graph {
     pub fn new(
-        time: Vec<f32>,
-        unfiltered_data: Arc<Vec<f32>>,
-        filtered_data: Arc<Mutex<Vec<f32>>>,
+        start: f32,
+        sampling_interval: f32,
+        unfiltered_data: Arc<Samples>,
+        snapshot: Snapshot,
+        filtered_data: Arc<Mutex<Samples>>,
+        channels: Channels,
+        pre_trigger: PreTrigger,
+        cycle: usize,
+        output_interval: Option<f32>,
+        profile: &Profile,
     ) -> Self {
+        let shown = vec![true; crate::perf::lock(&channels).len() + 1];
+
         Self {
-            time,
+            channels,
+            shown,
+            design: Design::new(sampling_interval),
+            reference: None,
+            y_range: profile.range(),
+            unit: profile.unit.clone(),
+            start,
+            sampling_interval,
+            snapshot,
             filtered_data,
+            pre_trigger,
             unfiltered_data,
+            cycle,
+            output_interval,
+            tab: Tab::Time,
             mode: Mode::Streaming,
+            rendering: Rendering::Line,
+            palette: Palette::Classic,
+            grid: Grid::default(),
+            derived: Derived::default(),
+            lag: None,
+            align: false,
+            detrending: Detrending::default(),
+            trends: (Trend::default(), Trend::default()),
+            show_settings: false,
+            crosshair: None,
+            resolution: Resolution::ALL[1],
         }
     }
 }
@@ -62,6 +439,8 @@ impl Graph {
 impl Graph {
     pub fn update(&mut self, message: Message) {
         match message {
+            Message::TabSelected(tab) => self.tab = tab,
+
             Message::SwitchMode => {
                 if matches!(self.mode, Mode::Streaming) {
                     self.mode = Mode::Static {
@@ -73,6 +452,65 @@ impl Graph {
                 }
             }
 
+            Message::ToggleSettings => self.show_settings = !self.show_settings,
+
+            Message::XLinesUpdated(lines) => self.grid.x_lines = lines,
+
+            Message::YLinesUpdated(lines) => self.grid.y_lines = lines,
+
+            Message::MinorLinesToggled(minor_lines) => self.grid.minor_lines = minor_lines,
+
+            Message::AlignToggled(align) => self.align = align,
+
+            Message::InputDetrendSelected(detrend) => self.detrending.input = detrend,
+
+            Message::OutputDetrendSelected(detrend) => self.detrending.output = detrend,
+
+            Message::EnvelopeToggled(envelope) => self.derived.envelope = envelope,
+
+            Message::FrequencyToggled(frequency) => self.derived.frequency = frequency,
+
+            Message::ReferenceToggled(enabled) => {
+                self.reference = enabled.then(Vec::new);
+                self.refilter();
+            }
+
+            Message::ReferenceFamilySelected(family) => {
+                self.design.family = family;
+                self.design.order = self.design.order.min(self.design.max_order());
+                self.refilter();
+            }
+
+            Message::ReferenceResponseSelected(response) => {
+                self.design.response = response;
+                self.refilter();
+            }
+
+            Message::ReferenceOrderUpdated(order) => {
+                self.design.order = order;
+                self.refilter();
+            }
+
+            Message::ReferenceCutoffUpdated(cutoff) => {
+                self.design.cutoff = cutoff;
+                self.refilter();
+            }
+
+            Message::ReferenceRippleUpdated(ripple) => {
+                self.design.ripple = ripple;
+                self.refilter();
+            }
+
+            Message::ChannelToggled(channel, shown) => self.shown[channel] = shown,
+
+            Message::Hovered(time) => self.crosshair = time,
+
+            Message::RenderingSelected(rendering) => self.rendering = rendering,
+
+            Message::PaletteSelected(palette) => self.palette = palette,
+
+            Message::ResolutionSelected(resolution) => self.resolution = resolution,
+
             Message::SizeUpdated(value) => {
                 let Mode::Static { size, .. } = &mut self.mode else {
                     unreachable!();
@@ -88,14 +526,46 @@ impl Graph {
 
                 assign(offset, value);
             }
+
+            Message::Zoomed(size, offset) => {
+                if let Mode::Static { .. } = self.mode {
+                    self.mode = Mode::Static { size, offset };
+                }
+            }
         }
     }
 
-    pub fn view(&self) -> Element<'_, super::Message> {
+    /// Chart alone, without its controls
+    pub fn trace(&self) -> Element<'_, super::Message> {
         let chart = ChartWidget::new(self)
             .height(Length::Fill)
             .width(Length::Fill);
 
+        Element::from(chart).map(super::Message::Graph)
+    }
+
+    pub fn view(&self) -> Element<'_, super::Message> {
+        let tabs = Tab::ALL.into_iter().fold(row![].spacing(5), |tabs, tab| {
+            let tab_button = button(text(tab));
+
+            // The current tab is shown disabled
+            tabs.push(if tab == self.tab {
+                tab_button
+            } else {
+                tab_button.on_press(Message::TabSelected(tab))
+            })
+        });
+
+        let chart = column![
+            tabs,
+            ChartWidget::new(self)
+                .height(Length::Fill)
+                .width(Length::Fill),
+        ]
+        .spacing(5)
+        .height(Length::Fill)
+        .width(Length::Fill);
+
         let mode = {
             let label = if matches!(self.mode, Mode::Streaming) {
                 "Disable streaming"
@@ -112,13 +582,27 @@ impl Graph {
             .width(Length::Fill)
         };
 
+        let settings = button(if self.show_settings {
+            "Hide display settings"
+        } else {
+            "Display settings"
+        })
+        .on_press(Message::ToggleSettings);
+
+        let mode = row![mode, settings].spacing(10);
+        let mode: Element<'_, Message> = if self.show_settings {
+            column![mode, self.settings()].spacing(10).into()
+        } else {
+            mode.into()
+        };
+
         let content: Element<'_, Message> = match self.mode {
             Mode::Streaming => {
                 column![chart, mode]
             }
 
             Mode::Static { size, offset } => {
-                let total_samples = (self.filtered_data.lock().len() - 1) as f64;
+                let total_samples = (self.snapshot.len() - 1) as f64;
 
                 let offset = slider(0f64..=total_samples, offset as f64, Message::OffsetUpdated)
                     .width(Length::Fill);
@@ -148,109 +632,1039 @@ impl Graph {
         content.map(super::Message::Graph)
     }
 
-    pub fn export(&self) -> io::Result<()> {
-        let file = File::create(crate::FILENAME)?;
-        let contents = ExportedData {
-            input: &self.unfiltered_data,
-            output: &self.filtered_data.lock(),
+    fn settings(&self) -> Element<'_, Message> {
+        let Grid {
+            x_lines,
+            y_lines,
+            minor_lines,
+        } = self.grid;
+
+        let styles = row![
+            text("Rendering"),
+            pick_list(
+                &Rendering::ALL[..],
+                Some(self.rendering),
+                Message::RenderingSelected
+            ),
+            text("Palette"),
+            pick_list(
+                &Palette::ALL[..],
+                Some(self.palette),
+                Message::PaletteSelected
+            ),
+            checkbox("Minor gridlines", minor_lines, Message::MinorLinesToggled),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let grid = row![
+            text(format!("X gridlines [{x_lines}]")),
+            slider(2u8..=30, x_lines, Message::XLinesUpdated),
+            text(format!("Y gridlines [{y_lines}]")),
+            slider(2u8..=30, y_lines, Message::YLinesUpdated),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let detrending = row![
+            text("Input"),
+            pick_list(
+                &Detrend::ALL[..],
+                Some(self.detrending.input),
+                Message::InputDetrendSelected
+            ),
+            text("Output"),
+            pick_list(
+                &Detrend::ALL[..],
+                Some(self.detrending.output),
+                Message::OutputDetrendSelected
+            ),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let derived = row![
+            text("Derived series"),
+            checkbox("Envelope", self.derived.envelope, Message::EnvelopeToggled),
+            checkbox(
+                "Instantaneous frequency",
+                self.derived.frequency,
+                Message::FrequencyToggled
+            ),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let mut settings =
+            column![styles, grid, detrending, derived, self.reference_settings()].spacing(10);
+
+        if self.shown.len() > 1 {
+            let toggles = self.shown.iter().enumerate().map(|(channel, &shown)| {
+                checkbox(channel_name(channel), shown, move |shown| {
+                    Message::ChannelToggled(channel, shown)
+                })
+                .into()
+            });
+
+            settings = settings.push(
+                row(iter::once(text("Channels").into()).chain(toggles).collect())
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        if let Some(lag) = self.lag {
+            settings = settings.push(checkbox(
+                format!("Align output to input ({lag} samples)"),
+                self.align,
+                Message::AlignToggled,
+            ));
+        }
+
+        settings.into()
+    }
+
+    fn reference_settings(&self) -> Element<'_, Message> {
+        let design = self.design;
+        let enabled = checkbox(
+            "Reference",
+            self.reference.is_some(),
+            Message::ReferenceToggled,
+        );
+
+        if self.reference.is_none() {
+            return enabled.into();
+        }
+
+        let mut settings = row![
+            enabled,
+            pick_list(
+                &Family::ALL[..],
+                Some(design.family),
+                Message::ReferenceFamilySelected
+            ),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        if design.family == Family::MovingAverage {
+            return settings
+                .push(text(format!("Taps [{}]", design.order)))
+                .push(slider(
+                    1..=design.max_order(),
+                    design.order,
+                    Message::ReferenceOrderUpdated,
+                ))
+                .into();
+        }
+
+        let nyquist = 0.5 / self.sampling_interval();
+
+        settings = settings
+            .push(pick_list(
+                &dsp::Response::ALL[..],
+                Some(design.response),
+                Message::ReferenceResponseSelected,
+            ))
+            .push(text(format!("Order [{}]", design.order)))
+            .push(slider(
+                1..=design.max_order(),
+                design.order,
+                Message::ReferenceOrderUpdated,
+            ))
+            .push(text(format!("Cutoff [{:.1} Hz]", design.cutoff)))
+            .push(
+                slider(
+                    0.0..=nyquist,
+                    design.cutoff,
+                    Message::ReferenceCutoffUpdated,
+                )
+                .step(nyquist / 1000.0),
+            );
+
+        if design.family == Family::Chebyshev {
+            settings = settings
+                .push(text(format!("Ripple [{:.1} dB]", design.ripple)))
+                .push(slider(0.1..=3.0, design.ripple, Message::ReferenceRippleUpdated).step(0.1));
+        }
+
+        settings.into()
+    }
+
+    /// Filters the inputs through [`Self::design`], if the reference is overlaid
+    fn refilter(&mut self) {
+        let sampling_interval = self.sampling_interval();
+
+        if let Some(reference) = &mut self.reference {
+            *reference = self.design.filter(&self.unfiltered_data, sampling_interval);
+        }
+    }
+
+    const fn sampling_interval(&self) -> f32 {
+        self.sampling_interval
+    }
+
+    /// Draws the data received since the last refresh
+    pub fn refresh(&mut self) {
+        self.snapshot.refresh();
+    }
+
+    /// Number of display updates dropped, as they came faster than they were drawn
+    pub fn dropped(&self) -> usize {
+        self.snapshot.dropped()
+    }
+
+    /// Copy of the graph as currently drawn, sharing its data, for rendering off the interface
+    pub fn detached(&self) -> Self {
+        Self {
+            reference: self.reference.clone(),
+            y_range: self.y_range.clone(),
+            unit: self.unit.clone(),
+            snapshot: self.snapshot.frozen(),
+            filtered_data: Arc::clone(&self.filtered_data),
+            channels: Arc::clone(&self.channels),
+            shown: self.shown.clone(),
+            pre_trigger: Arc::clone(&self.pre_trigger),
+            unfiltered_data: Arc::clone(&self.unfiltered_data),
+            ..*self
+        }
+    }
+
+    /// Input and received data
+    pub fn data(&self) -> (Arc<Samples>, Arc<Mutex<Samples>>) {
+        (
+            Arc::clone(&self.unfiltered_data),
+            Arc::clone(&self.filtered_data),
+        )
+    }
+
+    /// Number of samples in one repetition of the input
+    pub const fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Copy of the data received before transmission started
+    pub fn pre_trigger(&self) -> Vec<f32> {
+        crate::perf::lock(&self.pre_trigger).clone()
+    }
+
+    /// Hovered time, for other panels showing a time axis
+    pub const fn crosshair(&self) -> Option<f32> {
+        self.crosshair
+    }
+
+    pub const fn detrending(&self) -> Detrending {
+        self.detrending
+    }
+
+    /// Sets the input and output trends to remove, as fitted by the latest metrics
+    pub fn set_trends(&mut self, input: Trend, output: Trend) {
+        self.trends = (input, output);
+    }
+
+    /// Sets the measured delay of the output behind the input, in samples
+    pub fn set_lag(&mut self, lag: isize) {
+        self.lag = Some(lag);
+    }
+
+    /// Time of the `i`-th input
+    fn input_time(&self, i: usize) -> f32 {
+        (i as f32).mul_add(self.sampling_interval, self.start)
+    }
+
+    /// Time of the `i`-th output
+    fn output_time(&self, i: usize) -> f32 {
+        match self.output_interval {
+            Some(interval) => (i as f32).mul_add(interval, self.start),
+            None => self.input_time(i),
+        }
+    }
+
+    /// Index of the first input at or after `t`, or the number of inputs if there's none
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn input_at(&self, t: f32) -> usize {
+        let i = ((t - self.start) / self.sampling_interval).ceil().max(0.0) as usize;
+        i.min(self.unfiltered_data.len())
+    }
+
+    /// Range of inputs spanning the same time as the outputs in `window`
+    fn input_window(&self, window: Range<usize>) -> Range<usize> {
+        if self.output_interval.is_none() {
+            return window;
+        }
+
+        let (start, end) = (self.output_time(window.start), self.output_time(window.end));
+        self.input_at(start)..self.input_at(end)
+    }
+
+    /// Ranges of output indices to take times from and of the `received` output samples shown
+    /// at them, for outputs in `window`
+    ///
+    /// Shifted back by the lag when aligning. Samples shifted outside of the capture are dropped
+    fn aligned(&self, received: usize, window: Range<usize>) -> (Range<usize>, Range<usize>) {
+        let lag = self.lag.filter(|_| self.align).unwrap_or(0);
+        let skip = lag.unsigned_abs();
+        let Range { start, end } = window;
+
+        if lag >= 0 {
+            let end = end.min(received.saturating_sub(skip)).max(start);
+            (start..end, start + skip..end + skip)
+        } else {
+            let start = start.max(skip).min(end);
+            (start..end, start - skip..end - skip)
+        }
+    }
+
+    /// Selector for [`Self::export_image`]'s resolution
+    pub fn resolution_picker(&self) -> Element<'_, Message> {
+        pick_list(
+            &Resolution::ALL[..],
+            Some(self.resolution),
+            Message::ResolutionSelected,
+        )
+        .into()
+    }
+
+    /// Renders the current view offscreen at the selected [`Resolution`]
+    pub fn export_image(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let Resolution { width, height } = self.resolution;
+        let root = BitMapBackend::new(path.as_ref(), (width, height)).into_drawing_area();
+
+        root.fill(&theme::colors().background)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        self.draw(ChartBuilder::on(&root), None, self.resolution.scale());
+        root.present()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    }
+
+    /// Renders the run as it was streamed into an animated GIF at the selected [`Resolution`],
+    /// accounting for frames through `progress`
+    ///
+    /// Plays back in real time, unless that would take more than [`crate::ANIMATION_MAX_FRAMES`]
+    pub fn export_animation(&self, path: impl AsRef<Path>, progress: &Progress) -> io::Result<()> {
+        let Resolution { width, height } = self.resolution;
+        let received = self.snapshot.len();
+        let sampling_interval = self.sampling_interval();
+        let output_interval = self.output_interval.unwrap_or(sampling_interval);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let real_time = (output_interval * crate::ANIMATION_FPS as f32)
+            .recip()
+            .round() as usize;
+        let step = real_time
+            .max(received / crate::ANIMATION_MAX_FRAMES + 1)
+            .max(1);
+
+        let root = BitMapBackend::gif(path.as_ref(), (width, height), 1000 / crate::ANIMATION_FPS)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?
+            .into_drawing_area();
+
+        let frames = (step..received + step).step_by(step);
+        progress.start(frames.len());
+
+        let background = theme::colors().background;
+        for frame in frames {
+            root.fill(&background)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            self.plot(
+                ChartBuilder::on(&root),
+                None,
+                self.resolution.scale(),
+                Some(frame.min(received)),
+            );
+            root.present()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            progress.add(1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the current tab, with strokes, fonts and margins multiplied by `scale`
+    fn draw<DB: DrawingBackend>(
+        &self,
+        builder: ChartBuilder<'_, '_, DB>,
+        state: Option<&ChartState>,
+        scale: u32,
+    ) {
+        match self.tab {
+            Tab::Time => self.plot(builder, state, scale, None),
+            Tab::Spectrum => self.plot_spectrum(builder, state, scale),
+        }
+    }
+
+    /// Zooms the static window with the wheel around the cursor, and pans it by dragging
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn navigate(&self, state: &mut ChartState, event: mouse::Event) -> Option<Message> {
+        /// Scrolled pixels worth a line, for touchpads
+        const LINE: f32 = 20.0;
+
+        if let mouse::Event::ButtonReleased(mouse::Button::Left) = event {
+            state.dragged = None;
+            return None;
+        }
+
+        let Mode::Static { size, offset } = self.mode else {
+            return None;
         };
+        let (x, _) = state.cursor?;
+        let (left, right) = state.axis.get()?.pixels;
+
+        let last = self.snapshot.len().saturating_sub(1);
+        let width = (right - left).max(1) as f32;
+        let per_pixel = size as f32 / width;
+
+        match event {
+            mouse::Event::WheelScrolled { delta } => {
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / LINE,
+                };
+
+                let zoomed = (size as f32 * crate::ZOOM_FACTOR.powf(-lines)).round() as usize;
+                let zoomed = zoomed.min(last).max(crate::MIN_WINDOW_SIZE);
 
-        serde_json::to_writer(file, &contents)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                // The sample under the cursor stays there
+                let fraction = ((x - left) as f32 / width).clamp(0.0, 1.0);
+                let anchor = fraction.mul_add(size as f32, offset as f32);
+                let offset = fraction.mul_add(-(zoomed as f32), anchor).round().max(0.0) as usize;
+
+                Some(Message::Zoomed(zoomed, offset.min(last)))
+            }
+
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                if (left..=right).contains(&x) {
+                    state.dragged = Some((x, offset));
+                }
+
+                None
+            }
+
+            mouse::Event::CursorMoved { .. } => {
+                let (from, start) = state.dragged?;
+                let moved = ((from - x) as f32 * per_pixel).round() as isize;
+
+                Some(Message::OffsetUpdated(
+                    start.saturating_add_signed(moved).min(last) as f64,
+                ))
+            }
+
+            _ => None,
+        }
     }
-}
 
-impl Chart<Message> for Graph {
-    type State = ();
+    /// Range of output indices shown, out of `total_samples`, given a `frame` to draw the
+    /// chart as it was streamed
+    fn window(&self, total_samples: usize, frame: Option<usize>) -> Range<usize> {
+        match (self.mode, frame) {
+            (Mode::Streaming, _) | (_, Some(_)) => {
+                total_samples - total_samples.min(crate::STREAMING_WINDOW_SIZE)..total_samples - 1
+            }
+
+            (Mode::Static { size, offset }, None) => {
+                let start = total_samples.min(offset);
+                start..(start + size).min(total_samples - 1)
+            }
+        }
+    }
 
-    fn build_chart<DB: plotters_iced::DrawingBackend>(
+    /// Draws the chart, with strokes, fonts and margins multiplied by `scale`
+    ///
+    /// Interactive overlays are only drawn on screen, i.e. when `state` is available. Given a
+    /// `frame`, the chart is drawn as it was streamed once that many outputs were received
+    fn plot<DB: DrawingBackend>(
         &self,
-        _state: &Self::State,
         mut builder: ChartBuilder<'_, '_, DB>,
+        state: Option<&ChartState>,
+        scale: u32,
+        frame: Option<usize>,
     ) {
-        use plotters::prelude::*;
-
-        let filtered = self.filtered_data.lock();
-        let unfiltered = self.unfiltered_data.as_slice();
-        let total_samples = filtered.len();
+        let colors = theme::colors();
+        let (foreground, background) = (colors.foreground, colors.background);
+        let filtered = &*self.snapshot;
+        let unfiltered = &self.unfiltered_data[..];
+        let total_samples = frame.map_or(filtered.len(), |frame| frame.min(filtered.len()));
 
         if total_samples == 0 {
             return;
         }
 
-        let start;
-        let end;
+        let Range { start, end } = self.window(total_samples, frame);
 
-        match self.mode {
-            Mode::Streaming => {
-                start = total_samples - total_samples.min(crate::STREAMING_WINDOW_SIZE);
-                end = total_samples - 1;
-            }
+        let sampling_interval = self.sampling_interval();
+        let output_interval = self.output_interval.unwrap_or(sampling_interval);
+        let nyquist = 0.5 / sampling_interval.min(output_interval);
 
-            Mode::Static { size, offset } => {
-                start = total_samples.min(offset);
-                end = (start + size).min(total_samples - 1);
-            }
+        // Pre-trigger data precedes the first sample
+        let pre_trigger = crate::perf::lock(&self.pre_trigger);
+        let pre_trigger = if start == 0 { &pre_trigger[..] } else { &[] };
+        let first = if pre_trigger.is_empty() {
+            self.output_time(start)
+        } else {
+            -(pre_trigger.len() as f32) * output_interval
+        };
+        let last = self.output_time(end);
+
+        if self.derived.frequency {
+            builder.right_y_label_area_size(36 * scale);
         }
 
         let mut chart = builder
-            .x_label_area_size(24)
-            .y_label_area_size(24)
-            .margin(10)
-            .build_cartesian_2d(self.time[start]..self.time[end], -5f32..5f32)
-            .expect("built chart");
+            .x_label_area_size(24 * scale)
+            .y_label_area_size(24 * scale)
+            .margin(10 * scale)
+            .build_cartesian_2d(first..last, self.y_range.clone())
+            .expect("built chart")
+            .set_secondary_coord(first..last, 0f32..nyquist);
+
+        if let Some(state) = state {
+            let (pixels, _) = chart.plotting_area().get_pixel_range();
+            state.axis.set(Some(Axis {
+                pixels: (pixels.start, pixels.end),
+                time: (first, last),
+            }));
+        }
 
         chart
             .configure_mesh()
-            .axis_style(WHITE)
-            .label_style(("sans-serif", 18).into_font().color(&WHITE))
-            .max_light_lines(0)
-            .bold_line_style(WHITE.mix(0.30))
+            .axis_style(foreground.stroke_width(scale))
+            .label_style(("sans-serif", 18 * scale).into_font().color(&foreground))
+            .x_labels(self.grid.x_lines.into())
+            .y_labels(self.grid.y_lines.into())
+            .max_light_lines(if self.grid.minor_lines { 4 } else { 0 })
+            .light_line_style(foreground.mix(0.10))
+            .bold_line_style(foreground.mix(0.30))
             .draw()
             .expect("drawn mesh");
 
-        let time = &self.time[start..end];
-        let output = time
+        if self.derived.frequency {
+            chart
+                .configure_secondary_axes()
+                .axis_style(foreground.stroke_width(scale))
+                .label_style(("sans-serif", 18 * scale).into_font().color(&foreground))
+                .y_desc("Hz")
+                .draw()
+                .expect("drawn secondary axis");
+        }
+
+        let input_window = self.input_window(start..end);
+        let time: Vec<_> = input_window.clone().map(|i| self.input_time(i)).collect();
+        let time = time.as_slice();
+        let markers = time.len() < crate::MARKER_THRESHOLD;
+        let (output_time, output_window) = self.aligned(total_samples, start..end);
+        let output_time: Vec<_> = output_time.map(|i| self.output_time(i)).collect();
+        let output_time = output_time.as_slice();
+
+        let (mut input_trend, mut output_trend) = self.trends;
+        if self.detrending.input == Detrend::None {
+            input_trend = Trend::default();
+        }
+        if self.detrending.output == Detrend::None {
+            output_trend = Trend::default();
+        }
+
+        let input_samples =
+            &input_trend.remove(&unfiltered[input_window.clone()], input_window.start);
+        let output_samples =
+            &output_trend.remove(&filtered[output_window.clone()], output_window.start);
+
+        let output: Vec<_> = output_time
             .iter()
-            .zip(&filtered[start..end])
-            .map(|(x, y)| (*x, *y));
-        let input = time
+            .zip(output_samples)
+            .map(|(x, y)| (*x, *y))
+            .collect();
+        let input: Vec<_> = time
             .iter()
-            .zip(&unfiltered[start..end])
-            .map(|(x, y)| (*x, *y));
+            .zip(input_samples)
+            .map(|(x, y)| (*x, *y))
+            .collect();
 
-        // Input
-        {
-            let color = CYAN;
+        let trace = Trace {
+            rendering: self.rendering,
+            markers,
+            scale,
+        };
+
+        let label = |name: &str| format!("{name} [{}]", self.unit);
+        trace.draw(
+            &mut chart,
+            &input,
+            self.palette.series(0, &colors),
+            &label("Input"),
+        );
+        if self.shown[0] {
+            trace.draw(
+                &mut chart,
+                &output,
+                self.palette.series(1, &colors),
+                &label(&channel_name(0)),
+            );
+        }
+
+        // Channels following the output, drawn at its times without detrending
+        let channels = crate::perf::lock(&self.channels);
+        for (i, samples) in channels.iter().enumerate() {
+            let channel = i + 1;
+            if !self.shown[channel] {
+                continue;
+            }
+
+            let end = output_window.end.min(samples.len());
+            let points: Vec<_> = output_time
+                .iter()
+                .zip(&samples[output_window.start.min(end)..end])
+                .map(|(x, y)| (*x, *y))
+                .collect();
+
+            trace.draw(
+                &mut chart,
+                &points,
+                self.palette.channel(channel, &colors),
+                &label(&channel_name(channel)),
+            );
+        }
+
+        // Reference filtered on the host, at the time of its inputs
+        if let Some(reference) = &self.reference {
+            let end = input_window.end.min(reference.len());
+            let points: Vec<_> = time
+                .iter()
+                .zip(&reference[input_window.start.min(end)..end])
+                .map(|(x, y)| (*x, *y))
+                .collect();
+
+            trace.draw(
+                &mut chart,
+                &points,
+                (self.palette.series(3, &colors).0, Dash::Dotted),
+                &label(&format!("Reference {}", self.design.family)),
+            );
+        }
+
+        if !pre_trigger.is_empty() {
+            let mut points: Vec<_> = pre_trigger
+                .iter()
+                .rev()
+                .zip(1..)
+                .map(|(y, before)| (-(before as f32) * output_interval, *y))
+                .collect();
+            points.reverse();
+
+            trace.draw(
+                &mut chart,
+                &points,
+                self.palette.series(2, &colors),
+                &label("Pre-trigger output"),
+            );
+
+            // Transmission start
             chart
-                .draw_series(LineSeries::new(input, color.stroke_width(2)))
-                .expect("drawn input")
-                .label("Input")
-                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+                .draw_series([PathElement::new(
+                    vec![(0.0, self.y_range.start), (0.0, self.y_range.end)],
+                    foreground.mix(0.4).stroke_width(scale),
+                )])
+                .expect("drawn trigger");
         }
 
-        // Output
-        {
-            let color = YELLOW;
+        // Boundaries between repetitions of the input
+        if self.cycle > 0 {
+            let boundaries = (input_window.start / self.cycle + 1..)
+                .map(|k| k * self.cycle)
+                .take_while(|&i| i < input_window.end)
+                .map(|i| {
+                    PathElement::new(
+                        vec![
+                            (self.input_time(i), self.y_range.start),
+                            (self.input_time(i), self.y_range.end),
+                        ],
+                        foreground.mix(0.25).stroke_width(scale),
+                    )
+                });
+
+            chart
+                .draw_series(boundaries)
+                .expect("drawn cycle boundaries");
+        }
+
+        // Derived series, dashed in the color of their source
+        let sources = [
+            ("Input", time, input_samples, sampling_interval, 0),
+            ("Output", output_time, output_samples, output_interval, 1),
+        ];
+
+        if self.derived.envelope {
+            let trace = Trace {
+                rendering: Rendering::Line,
+                markers: false,
+                scale,
+            };
+
+            for (name, time, samples, _, series) in sources {
+                let envelope: Vec<_> = time
+                    .iter()
+                    .copied()
+                    .zip(hilbert::envelope(samples))
+                    .collect();
+
+                trace.draw(
+                    &mut chart,
+                    &envelope,
+                    (self.palette.series(series, &colors).0, Dash::Dashed),
+                    &label(&format!("{name} envelope")),
+                );
+            }
+        }
+
+        if self.derived.frequency {
+            let (size, spacing) = Dash::Dotted.pattern().expect("dotted pattern");
+            let length = 20 * i32::try_from(scale).expect("small scale");
+
+            for (name, time, samples, interval, series) in sources {
+                let (color, _) = self.palette.series(series, &colors);
+                let style = color.stroke_width(2 * scale);
+                let frequency = hilbert::instantaneous_frequency(samples, interval);
+
+                chart
+                    .draw_secondary_series(DashedLineSeries::new(
+                        time.iter().skip(1).copied().zip(frequency),
+                        size * scale,
+                        spacing * scale,
+                        style,
+                    ))
+                    .expect("drawn instantaneous frequency")
+                    .label(format!("{name} frequency [Hz]"))
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + length, y)], style));
+            }
+        }
+
+        // Crosshair
+        let crosshair = self
+            .crosshair
+            .filter(|t| state.is_some() && (first..=last).contains(t));
+
+        if let Some(t) = crosshair {
+            chart
+                .draw_series([PathElement::new(
+                    vec![(t, self.y_range.start), (t, self.y_range.end)],
+                    foreground.mix(0.6).stroke_width(scale),
+                )])
+                .expect("drawn crosshair");
+        }
+
+        // Crosshair through the cursor, with the values of the nearest input and output
+        let hovered = state
+            .and_then(|state| state.cursor)
+            .filter(|_| !input.is_empty())
+            .and_then(|cursor| chart.as_coord_spec().reverse_translate(cursor));
+
+        if let Some((x, y)) = hovered {
+            let nearest_input = nearest(time, x);
+            let index = input_window.start + nearest_input;
+            let (t, value) = input[nearest_input];
+
+            let readout = |name: &str, value: f32| format!("{name}: {value:.4} {}", self.unit);
+            let output = output
+                .get(nearest(output_time, x))
+                .filter(|_| self.shown[0])
+                .map_or_else(String::new, |&(_, value)| readout("Output", value));
+            let style = ("sans-serif", 16).into_font().color(&foreground);
+
+            chart
+                .draw_series([PathElement::new(
+                    vec![(first, y), (last, y)],
+                    foreground.mix(0.6).stroke_width(scale),
+                )])
+                .expect("drawn crosshair");
             chart
-                .draw_series(LineSeries::new(output, color.stroke_width(2)))
-                .expect("drawn output")
-                .label("Output")
-                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+                .draw_series([
+                    EmptyElement::at((t, value))
+                        + Circle::new((0, 0), 4, foreground.filled())
+                        + Text::new(format!("#{index} (t = {t:.4})"), (8, -56), style.clone())
+                        + Text::new(readout("Input", value), (8, -38), style.clone())
+                        + Text::new(output, (8, -20), style),
+                ])
+                .expect("drawn tooltip");
         }
 
         // Legend
         {
             chart
                 .configure_series_labels()
-                .border_style(WHITE)
-                .label_font(("sans-serif", 18).into_font().color(&WHITE))
-                .background_style(BLACK)
+                .border_style(foreground)
+                .label_font(("sans-serif", 18 * scale).into_font().color(&foreground))
+                .background_style(background)
                 .position(SeriesLabelPosition::UpperRight)
                 .draw()
                 .expect("drawn legend");
         }
     }
+
+    /// Draws the magnitude spectra of the inputs and outputs shown in the time domain, or of
+    /// the latest [`crate::SPECTRUM_WINDOW_SIZE`] while streaming
+    fn plot_spectrum<DB: DrawingBackend>(
+        &self,
+        builder: ChartBuilder<'_, '_, DB>,
+        state: Option<&ChartState>,
+        scale: u32,
+    ) {
+        // Frequencies have no time to hover
+        if let Some(state) = state {
+            state.axis.set(None);
+        }
+
+        let colors = theme::colors();
+        let (foreground, background) = (colors.foreground, colors.background);
+        let filtered = &*self.snapshot;
+        let total_samples = filtered.len();
+
+        if total_samples < 2 {
+            return;
+        }
+
+        let window = match self.mode {
+            Mode::Streaming => {
+                total_samples - total_samples.min(crate::SPECTRUM_WINDOW_SIZE)..total_samples
+            }
+            Mode::Static { .. } => self.window(total_samples, None),
+        };
+        let input_window = self.input_window(window.clone());
+
+        let sampling_interval = self.sampling_interval();
+        let output_interval = self.output_interval.unwrap_or(sampling_interval);
+        let spectra = [
+            (
+                "Input",
+                spectrum::magnitude(&self.unfiltered_data[input_window], sampling_interval),
+                0,
+            ),
+            (
+                "Output",
+                spectrum::magnitude(&filtered[window], output_interval),
+                1,
+            ),
+        ];
+
+        let nyquist = 0.5 / sampling_interval.min(output_interval);
+        let peak = spectra
+            .iter()
+            .flat_map(|(_, magnitudes, _)| magnitudes)
+            .map(|&(_, magnitude)| magnitude)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let top = if peak.is_finite() {
+            (peak / 10.0).ceil() * 10.0
+        } else {
+            0.0
+        };
+
+        let mut chart = builder
+            .x_label_area_size(24 * scale)
+            .y_label_area_size(36 * scale)
+            .margin(10 * scale)
+            .build_cartesian_2d(0f32..nyquist, top - 100.0..top)
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(foreground.stroke_width(scale))
+            .label_style(("sans-serif", 18 * scale).into_font().color(&foreground))
+            .x_labels(self.grid.x_lines.into())
+            .y_labels(self.grid.y_lines.into())
+            .max_light_lines(if self.grid.minor_lines { 4 } else { 0 })
+            .light_line_style(foreground.mix(0.10))
+            .bold_line_style(foreground.mix(0.30))
+            .x_desc("Hz")
+            .y_desc("dB")
+            .draw()
+            .expect("drawn mesh");
+
+        let trace = Trace {
+            rendering: Rendering::Line,
+            markers: false,
+            scale,
+        };
+
+        for (name, magnitudes, series) in &spectra {
+            trace.draw(
+                &mut chart,
+                magnitudes,
+                self.palette.series(*series, &colors),
+                &format!("{name} [dB]"),
+            );
+        }
+
+        chart
+            .configure_series_labels()
+            .border_style(foreground)
+            .label_font(("sans-serif", 18 * scale).into_font().color(&foreground))
+            .background_style(background)
+            .position(SeriesLabelPosition::UpperRight)
+            .draw()
+            .expect("drawn legend");
+    }
+}
+
+impl Chart<Message> for Graph {
+    type State = ChartState;
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        state: &Self::State,
+        builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let start = Instant::now();
+        self.draw(builder, Some(state), 1);
+        crate::perf::record_frame(start.elapsed());
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (Status, Option<Message>) {
+        if let canvas::Event::Mouse(event) = event {
+            #[allow(clippy::cast_possible_truncation)]
+            let position = cursor
+                .position_in(&bounds)
+                .map(|point| (point.x as i32, point.y as i32));
+
+            state.cursor = position;
+
+            if let Some(message) = self.navigate(state, event) {
+                return (Status::Captured, Some(message));
+            }
+
+            let hovered = position
+                .zip(state.axis.get())
+                .and_then(|((x, _), axis)| axis.time_at(x));
+
+            if hovered != state.hovered {
+                state.hovered = hovered;
+                return (Status::Ignored, Some(Message::Hovered(hovered)));
+            }
+        }
+
+        (Status::Ignored, None)
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> mouse::Interaction {
+        if state.dragged.is_some() {
+            mouse::Interaction::Grabbing
+        } else if state.cursor.is_some() {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::Idle
+        }
+    }
+}
+
+/// Legend name of the numbered output channel
+fn channel_name(channel: usize) -> String {
+    match channel {
+        0 => "Output".to_owned(),
+        _ => format!("Channel {}", channel + 1),
+    }
+}
+
+/// How a series is drawn
+struct Trace {
+    rendering: Rendering,
+    /// Draw per-sample markers regardless of [`Self::rendering`]
+    markers: bool,
+    scale: u32,
+}
+
+impl Trace {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
+        points: &[(f32, f32)],
+        (color, dash): (RGBColor, Dash),
+        label: &str,
+    ) {
+        let scale = self.scale;
+        let style = color.stroke_width(2 * scale);
+        let pattern = dash.pattern();
+
+        let series = match (self.rendering, pattern) {
+            (Rendering::Line, None) => {
+                chart.draw_series(LineSeries::new(points.iter().copied(), style))
+            }
+            (Rendering::Line, Some((size, spacing))) => chart.draw_series(DashedLineSeries::new(
+                points.iter().copied(),
+                size * scale,
+                spacing * scale,
+                style,
+            )),
+            (Rendering::Steps, None) => {
+                chart.draw_series(LineSeries::new(steps(points.iter().copied()), style))
+            }
+            (Rendering::Steps, Some((size, spacing))) => {
+                chart.draw_series(DashedLineSeries::new(
+                    steps(points.iter().copied()),
+                    size * scale,
+                    spacing * scale,
+                    style,
+                ))
+            }
+            (Rendering::Markers, _) => chart.draw_series(self.sample_markers(points, style)),
+        };
+
+        let length = 20 * i32::try_from(self.scale).expect("small scale");
+        series
+            .expect("drawn series")
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + length, y)], style));
+
+        if self.markers && self.rendering != Rendering::Markers {
+            chart
+                .draw_series(self.sample_markers(points, style))
+                .expect("drawn markers");
+        }
+    }
+
+    fn sample_markers<'a>(
+        &self,
+        points: &'a [(f32, f32)],
+        style: ShapeStyle,
+    ) -> impl Iterator<Item = Circle<(f32, f32), u32>> + 'a {
+        let size = 2 * self.scale;
+
+        points
+            .iter()
+            .map(move |&point| Circle::new(point, size, style.filled()))
+    }
+}
+
+/// Index of the sample in `time` closest to `x`
+fn nearest(time: &[f32], x: f32) -> usize {
+    let i = time.partition_point(|&t| t < x);
+
+    if i == 0 {
+        0
+    } else if i == time.len() || x - time[i - 1] < time[i] - x {
+        i - 1
+    } else {
+        i
+    }
+}
+
+/// Holds each sample until the next one arrives
+fn steps(
+    points: impl Iterator<Item = (f32, f32)> + Clone,
+) -> impl Iterator<Item = (f32, f32)> + Clone {
+    let mut previous = None;
+
+    points.flat_map(move |(x, y)| {
+        let hold = previous.replace(y).map(|previous| (x, previous));
+        hold.into_iter().chain(iter::once((x, y)))
+    })
 }
 
 fn assign(out: &mut usize, value: f64) {
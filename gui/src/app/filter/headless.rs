@@ -0,0 +1,297 @@
+use std::{
+    io,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU16},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::{
+    super::{
+        config::Config,
+        profile::Profile,
+        signal::{self, Excursion, Parameters},
+    },
+    connect,
+    export::{self, Format},
+    link::Link,
+    storage::Samples,
+    workers::{self, Block, Supervisor, Throughput},
+    Announcement, Protocol, Target, Transmission,
+};
+
+const USAGE: &str = "\
+Usage: online-filtering --headless --port <PORT> --function <EXPRESSION> --stop-time <SECONDS>
+                        --output <FILE.json|FILE.csv> [--seed <SEED>] [--profile <NAME>]
+                        [--ascii]
+
+Filters the input the expression of t generates through the device on PORT, a serial port or
+a tcp://HOST:PORT address, then exports the run to FILE, in the format of its extension. The
+profile defaults to the one last selected. Devices exchanging samples as lines of text are
+reached with --ascii";
+
+/// Arguments of a headless run
+struct Options {
+    port: String,
+    function: String,
+    stop_time: f32,
+    output: PathBuf,
+    /// Random unless given
+    seed: Option<u32>,
+    /// Name of the device profile, the last selected one unless given
+    profile: Option<String>,
+    /// The device speaks the ASCII protocol
+    ascii: bool,
+}
+
+impl Options {
+    /// Parses `args`, or returns [`None`] if help was asked for
+    fn parse(mut args: impl Iterator<Item = String>) -> io::Result<Option<Self>> {
+        fn parse<T: FromStr>(flag: &str, value: &str) -> io::Result<T> {
+            value
+                .parse()
+                .map_err(|_| usage(&format!("invalid value {value:?} of {flag}")))
+        }
+
+        let (mut port, mut function, mut stop_time, mut output, mut seed, mut profile) =
+            (None, None, None, None, None, None);
+        let mut ascii = false;
+
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| usage(&format!("missing value of {flag}")))
+            };
+
+            match flag.as_str() {
+                "--port" => port = Some(value()?),
+                "--function" => function = Some(value()?),
+                "--stop-time" => stop_time = Some(parse(&flag, &value()?)?),
+                "--output" => output = Some(PathBuf::from(value()?)),
+                "--seed" => seed = Some(parse(&flag, &value()?)?),
+                "--profile" => profile = Some(value()?),
+                "--ascii" => ascii = true,
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(usage(&format!("unknown argument {flag}"))),
+            }
+        }
+
+        Ok(Some(Self {
+            port: port.ok_or_else(|| usage("missing --port"))?,
+            function: function.ok_or_else(|| usage("missing --function"))?,
+            stop_time: stop_time.ok_or_else(|| usage("missing --stop-time"))?,
+            output: output.ok_or_else(|| usage("missing --output"))?,
+            seed,
+            profile,
+            ascii,
+        }))
+    }
+}
+
+/// Filters the input described by `args` through a device and exports the run, without a
+/// window, for scripted hardware-in-the-loop tests
+///
+/// Runs the same connection, transmission and reception as the interface, with the default
+/// [`Transmission`], and exports like it too
+///
+/// # Errors
+/// Fails on invalid arguments, if the device can't be reached or stops responding, or if the
+/// run can't be exported
+pub fn run(args: impl Iterator<Item = String>) -> io::Result<()> {
+    let Some(options) = Options::parse(args)? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let format = Format::ALL
+        .into_iter()
+        .find(|format| {
+            options
+                .output
+                .extension()
+                .map_or(false, |extension| extension == format.extension())
+        })
+        .ok_or_else(|| usage("the output must be a .json or .csv file"))?;
+
+    let config = Config::load();
+    let mut profiles = Profile::load_all();
+    let profile = match options.profile {
+        Some(name) => profiles
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| usage(&format!("unknown profile {name:?}")))?,
+
+        None => {
+            let selected = config
+                .profile
+                .and_then(|name| profiles.iter().position(|profile| profile.name == name))
+                .unwrap_or(0);
+            profiles.swap_remove(selected)
+        }
+    };
+
+    let parameters = Parameters {
+        function: options.function,
+        preset: None,
+        stop_time: options.stop_time,
+        samples: None,
+        seed: options.seed.unwrap_or_else(signal::random_seed),
+        overrange: config.overrange,
+        normalization: None,
+        time_base: config.time_base,
+    };
+    let mut transmission = Transmission {
+        protocol: if options.ascii {
+            Protocol::Ascii
+        } else {
+            Protocol::Binary
+        },
+        ..Transmission::default()
+    };
+    let target = match options.port.strip_prefix("tcp://") {
+        Some(address) => Target::Tcp(address.to_owned()),
+        None => Target::Serial(options.port),
+    };
+    let device = target.to_string();
+
+    let (
+        Announcement {
+            input,
+            output,
+            block,
+            channels,
+        },
+        Link { tx, rx },
+    ) = connect(
+        target,
+        &profile,
+        parameters.seed,
+        transmission.handshakes,
+        false,
+        transmission.protocol,
+        &AtomicU16::new(0),
+    )?;
+
+    tracing::info!("Seed: {}", parameters.seed);
+    let sampling_interval = (input as f32).recip();
+    let output_interval = (output != input).then(|| (output as f32).recip());
+    let (_, mut data) = signal::evaluate(&parameters, sampling_interval)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let generated = Arc::new(Samples::from(data.clone()));
+    if let Some(Excursion {
+        count,
+        peak,
+        clamped,
+    }) = signal::limit(&mut data, profile.full_scale, parameters.overrange)
+    {
+        tracing::warn!(
+            "{count} samples exceed the full scale of {}, peaking at {peak}{}",
+            profile.full_scale,
+            if clamped { ", clamped" } else { "" }
+        );
+    }
+
+    let cycle = data.len();
+    transmission.block = block.map(|block| Block::new(block, input, output));
+    if let Some(block) = transmission.block {
+        signal::pad(&mut data, block.input);
+    }
+    let data = Arc::new(Samples::from(data));
+
+    let mut workers = Supervisor::default();
+    // Never paused, but shared all the same
+    let control = Arc::default();
+    let started = Arc::new(AtomicBool::new(false));
+    let throughput = Arc::new(Throughput::default());
+    let epoch = Instant::now();
+    let start = export::Provenance::timestamp(SystemTime::now());
+
+    let received = workers::spawn_receiver(
+        &mut workers,
+        rx,
+        data.len(),
+        usize::try_from(channels).expect("channel count fits in memory"),
+        transmission,
+        Arc::clone(&control),
+        Arc::clone(&started),
+        Arc::clone(&throughput),
+        epoch,
+    );
+    workers::spawn_transmitter(
+        &mut workers,
+        tx,
+        Arc::clone(&data),
+        transmission,
+        control,
+        started,
+        Arc::clone(&throughput),
+        epoch,
+    );
+
+    tracing::info!("Streaming {} samples to {device}", data.len());
+    while !workers.has_ended(workers::RECEIVER) {
+        thread::sleep(Duration::from_millis(100));
+    }
+    workers.shutdown();
+
+    let Some(ending) = *crate::perf::lock(&received.outcome) else {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the receiver panicked",
+        ));
+    };
+    tracing::info!("{ending}: received {} outputs", throughput.received());
+
+    if let Some(error) = ending.error() {
+        return Err(io::Error::new(error, ending.to_string()));
+    }
+
+    let metadata = export::Metadata {
+        seed: parameters.seed,
+        gain: None,
+        time_base: parameters.time_base,
+        cycle,
+        sampling_rate: sampling_interval.recip(),
+        output_rate: output_interval.map(f32::recip),
+        provenance: export::Provenance {
+            function: Some(parameters.function),
+            stop_time: Some(parameters.stop_time),
+            device: Some(device),
+            started: Some(start),
+            ended: Some(start + throughput.elapsed().as_secs_f64()),
+        },
+    };
+    let pre_trigger = crate::perf::lock(&received.pre_trigger).clone();
+    let log = crate::perf::lock(&received.log).clone();
+    let inputs = export::Inputs {
+        generated,
+        transmitted: data,
+        sent: throughput.sent(),
+        front_end: None,
+    };
+
+    let task = export::export(
+        options.output,
+        format,
+        metadata,
+        pre_trigger,
+        log,
+        inputs,
+        received.output,
+        Arc::default(),
+    );
+    let path = tokio::runtime::Runtime::new()?.block_on(task)?;
+
+    tracing::info!("Exported the run to {}", path.display());
+    Ok(())
+}
+
+/// Invalid arguments, explained by `reason` followed by the usage
+fn usage(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{reason}\n\n{USAGE}"))
+}
@@ -0,0 +1,50 @@
+use rustfft::{num_complex::Complex32, FftPlanner};
+use std::f32::consts::PI;
+
+/// Analytic signal of `samples`, through the Hilbert transform
+///
+/// Non-finite samples are treated as zeros
+fn analytic(samples: &[f32]) -> Vec<Complex32> {
+    let n = samples.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut planner = FftPlanner::new();
+
+    let mut spectrum: Vec<_> = samples
+        .iter()
+        .map(|&x| Complex32::new(if x.is_finite() { x } else { 0.0 }, 0.0))
+        .collect();
+    planner.plan_fft_forward(n).process(&mut spectrum);
+
+    // Negative frequencies are removed, and positive ones doubled to keep the real part
+    for (k, bin) in spectrum.iter_mut().enumerate() {
+        if k == 0 || 2 * k == n {
+            continue;
+        }
+
+        *bin *= if 2 * k < n { 2.0 } else { 0.0 };
+    }
+
+    planner.plan_fft_inverse(n).process(&mut spectrum);
+
+    let norm = (n as f32).recip();
+    spectrum.iter_mut().for_each(|z| *z *= norm);
+    spectrum
+}
+
+/// Instantaneous amplitude of `samples`
+pub fn envelope(samples: &[f32]) -> Vec<f32> {
+    analytic(samples).into_iter().map(Complex32::norm).collect()
+}
+
+/// Instantaneous frequency of `samples` in Hz, between each sample and the previous one
+///
+/// Taken from the phase difference of consecutive analytic samples, so no unwrapping is needed
+pub fn instantaneous_frequency(samples: &[f32], sampling_interval: f32) -> Vec<f32> {
+    analytic(samples)
+        .windows(2)
+        .map(|pair| (pair[1] * pair[0].conj()).arg() / (2.0 * PI * sampling_interval))
+        .collect()
+}
@@ -0,0 +1,472 @@
+use serde::Deserialize;
+use std::{
+    fmt, fs,
+    io::{self, BufRead},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use super::{
+    super::{config::Config, persist, schema::Schema, signal::TimeBase},
+    export::{Format, Metadata, Provenance, SCHEMA},
+    log::{Entry, Severity},
+};
+
+/// Run read back from an export, converted to the current [`SCHEMA`]
+#[derive(Debug)]
+pub struct Recording {
+    /// Schema the run was exported with
+    pub schema: Schema,
+    /// Version of the application that exported the run, if recorded
+    pub version: Option<String>,
+    pub metadata: Metadata,
+    pub log: Vec<Entry>,
+    /// Outputs received before transmission started
+    pub pre_trigger: Vec<f32>,
+    /// Values of the function over one cycle, if exported
+    pub generated: Vec<f32>,
+    /// Samples sent to the device
+    pub input: Vec<f32>,
+    pub output: Vec<f32>,
+}
+
+/// Asks for an exported file to replay, starting from the directory of the last one
+pub async fn pick_path() -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new().set_title("Replay a run");
+
+    for format in Format::ALL {
+        dialog = dialog.add_filter(&format.to_string(), &[format.extension()]);
+    }
+
+    if let Some(directory) = Config::load()
+        .recent_files
+        .first()
+        .and_then(|file| file.parent())
+    {
+        dialog = dialog.set_directory(directory);
+    }
+
+    dialog.pick_file().await.map(|file| file.path().to_owned())
+}
+
+/// Reads a run exported to `path` on the blocking thread pool, in the format of its extension
+///
+/// Files are checked against their embedded checksum, and rejected if they were corrupted or
+/// edited. Files of older schemas are converted by [`migrate`], those exported before sampling
+/// rates were recorded being assumed to be sampled at `sampling_rate`
+pub async fn load(path: PathBuf, sampling_rate: f32) -> io::Result<Recording> {
+    tokio::task::spawn_blocking(move || {
+        let format = Format::ALL
+            .into_iter()
+            .find(|format| {
+                path.extension()
+                    .map_or(false, |extension| extension == format.extension())
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} isn't a JSON or CSV export", path.display()),
+                )
+            })?;
+
+        let contents = fs::read(&path)?;
+        let marker = match format {
+            Format::Json => persist::CHECKSUM_MEMBER,
+            Format::Csv => persist::CHECKSUM_COMMENT,
+        };
+        if !persist::verify(&contents, marker)? {
+            tracing::warn!("{} has no checksum, it can't be verified", path.display());
+        }
+
+        let document = match format {
+            Format::Json => read_json(&contents)?,
+            Format::Csv => read_csv(&contents[..])?,
+        };
+
+        migrate(document, sampling_rate)
+    })
+    .await
+    .expect("import task ran")
+}
+
+/// Export as read, in any schema
+struct Document {
+    header: Header,
+    log: Vec<Entry>,
+    pre_trigger: Vec<f32>,
+    /// Empty before schema 1.1
+    generated: Vec<f32>,
+    input: Vec<f32>,
+    output: Vec<f32>,
+}
+
+/// Metadata of an export as read, in any schema
+#[derive(Default)]
+struct Header {
+    /// Declared by files exported since schemas were, inferred otherwise
+    schema: Option<Schema>,
+    /// Recorded since schema 1.1
+    version: Option<String>,
+    seed: u32,
+    gain: Option<f32>,
+    time_base: TimeBase,
+    cycle: usize,
+    /// Recorded since schema 1.1
+    sampling_rate: Option<f32>,
+    output_rate: Option<f32>,
+    /// Recorded since schema 1.2
+    provenance: Provenance,
+}
+
+/// Converters of headers from each schema to the next, in order, given the sampling rate
+/// assumed when it wasn't recorded
+const MIGRATIONS: [(Schema, Schema, fn(&mut Header, f32)); 2] = [
+    (Schema::new(1, 0), Schema::new(1, 1), from_1_0),
+    (Schema::new(1, 1), Schema::new(1, 2), from_1_1),
+];
+
+/// Converts `document` to the current [`SCHEMA`], one version at a time
+///
+/// Files of a newer minor version are read ignoring what was added since, those of a newer
+/// major version are rejected
+fn migrate(document: Document, sampling_rate: f32) -> io::Result<Recording> {
+    let Document {
+        mut header,
+        log,
+        pre_trigger,
+        generated,
+        input,
+        output,
+    } = document;
+
+    // Only files of schema 1.1 onwards record the version of the application
+    let exported = header.schema.unwrap_or(if header.version.is_some() {
+        Schema::new(1, 1)
+    } else {
+        Schema::new(1, 0)
+    });
+
+    if !exported.is_readable_by(SCHEMA) {
+        return Err(invalid(format!(
+            "exported with schema {exported}, which this version (schema {SCHEMA}) can't read"
+        )));
+    }
+
+    if exported > SCHEMA {
+        tracing::warn!("Exported with schema {exported}, parts newer than {SCHEMA} are ignored");
+    }
+
+    let mut schema = exported;
+    for (from, to, convert) in MIGRATIONS {
+        if schema == from {
+            convert(&mut header, sampling_rate);
+            schema = to;
+        }
+    }
+
+    Ok(Recording {
+        schema: exported,
+        version: header.version,
+        metadata: Metadata {
+            seed: header.seed,
+            gain: header.gain,
+            time_base: header.time_base,
+            cycle: header.cycle,
+            sampling_rate: header
+                .sampling_rate
+                .ok_or_else(|| invalid("missing sampling rate".to_owned()))?,
+            output_rate: header.output_rate,
+            provenance: header.provenance,
+        },
+        log,
+        pre_trigger,
+        generated,
+        input,
+        output,
+    })
+}
+
+/// Schema 1.0 didn't record the sampling rate
+fn from_1_0(header: &mut Header, sampling_rate: f32) {
+    header.sampling_rate.get_or_insert(sampling_rate);
+}
+
+/// Schema 1.1 didn't record the provenance of runs, which stays unknown
+fn from_1_1(_header: &mut Header, _sampling_rate: f32) {}
+
+/// Layout written by [`super::export`], non-finite samples being `null`
+#[derive(Deserialize)]
+struct Exported {
+    schema: Option<Schema>,
+    version: Option<String>,
+    seed: u32,
+    gain: Option<f32>,
+    #[serde(default)]
+    time_base: TimeBase,
+    cycle: usize,
+    sampling_rate: Option<f32>,
+    output_rate: Option<f32>,
+    function: Option<String>,
+    stop_time: Option<f32>,
+    device: Option<String>,
+    started: Option<f64>,
+    ended: Option<f64>,
+    #[serde(default)]
+    log: Vec<Logged>,
+    #[serde(default)]
+    pre_trigger: Vec<Option<f32>>,
+    #[serde(default)]
+    generated: Vec<Option<f32>>,
+    input: Vec<Option<f32>>,
+    output: Vec<Option<f32>>,
+}
+
+#[derive(Deserialize)]
+struct Logged {
+    index: usize,
+    severity: String,
+    message: String,
+}
+
+fn read_json(contents: &[u8]) -> io::Result<Document> {
+    fn samples(values: Vec<Option<f32>>) -> Vec<f32> {
+        values
+            .into_iter()
+            .map(|value| value.unwrap_or(f32::NAN))
+            .collect()
+    }
+
+    let exported: Exported = serde_json::from_slice(contents)?;
+
+    Ok(Document {
+        header: Header {
+            schema: exported.schema,
+            version: exported.version,
+            seed: exported.seed,
+            gain: exported.gain,
+            time_base: exported.time_base,
+            cycle: exported.cycle,
+            sampling_rate: exported.sampling_rate,
+            output_rate: exported.output_rate,
+            provenance: Provenance {
+                function: exported.function,
+                stop_time: exported.stop_time,
+                device: exported.device,
+                started: exported.started,
+                ended: exported.ended,
+            },
+        },
+        log: exported
+            .log
+            .into_iter()
+            .map(|logged| Entry {
+                index: logged.index,
+                severity: severity(&logged.severity),
+                message: logged.message,
+            })
+            .collect(),
+        pre_trigger: samples(exported.pre_trigger),
+        generated: samples(exported.generated),
+        input: samples(exported.input),
+        output: samples(exported.output),
+    })
+}
+
+/// Reads the `# key=value` metadata lines, then the rows under the header, whose columns may
+/// come in any order
+///
+/// Missing values are skipped, as only the ends of the shorter series are ever left empty
+fn read_csv(reader: impl BufRead) -> io::Result<Document> {
+    let mut document = Document {
+        header: Header::default(),
+        log: Vec::new(),
+        pre_trigger: Vec::new(),
+        generated: Vec::new(),
+        input: Vec::new(),
+        output: Vec::new(),
+    };
+    let mut header = None;
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = number + 1;
+
+        if let Some(comment) = line.strip_prefix("# ") {
+            let Some((key, value)) = comment.split_once('=') else {
+                continue;
+            };
+
+            let header = &mut document.header;
+            match key {
+                "schema" => header.schema = Some(parse(value, number)?),
+                "version" => header.version = Some(value.to_owned()),
+                "seed" => header.seed = parse(value, number)?,
+                "gain" => header.gain = Some(parse(value, number)?),
+                "endpoint" => header.time_base.endpoint = parse(value, number)?,
+                "centered" => header.time_base.centered = parse(value, number)?,
+                "cycle" => header.cycle = parse(value, number)?,
+                "sampling_rate" => header.sampling_rate = Some(parse(value, number)?),
+                "output_rate" => header.output_rate = Some(parse(value, number)?),
+                "function" => header.provenance.function = Some(unescape(value)),
+                "stop_time" => header.provenance.stop_time = Some(parse(value, number)?),
+                "device" => header.provenance.device = Some(unescape(value)),
+                "started" => header.provenance.started = Some(parse(value, number)?),
+                "ended" => header.provenance.ended = Some(parse(value, number)?),
+                "log" => document.log.push(entry(value, number)?),
+                _ => {}
+            }
+
+            continue;
+        }
+
+        let Some(columns) = &header else {
+            header = Some(Columns::new(&line, number)?);
+            continue;
+        };
+
+        let fields: Vec<_> = line.split(',').collect();
+        let value = |column: Option<usize>| -> io::Result<Option<f32>> {
+            match column.and_then(|column| fields.get(column)) {
+                Some(field) if !field.is_empty() => parse(field, number).map(Some),
+                _ => Ok(None),
+            }
+        };
+
+        if fields
+            .get(columns.index)
+            .map_or(false, |index| index.starts_with('-'))
+        {
+            let sample = value(Some(columns.output))?;
+            document.pre_trigger.push(sample.unwrap_or(f32::NAN));
+            continue;
+        }
+
+        if let Some(sample) = value(columns.generated)? {
+            document.generated.push(sample);
+        }
+
+        if let Some(sample) = value(Some(columns.input))? {
+            document.input.push(sample);
+        }
+
+        if let Some(sample) = value(Some(columns.output))? {
+            document.output.push(sample);
+        }
+    }
+
+    if header.is_none() {
+        return Err(invalid("missing header".to_owned()));
+    }
+
+    Ok(document)
+}
+
+/// Positions of the columns of a CSV export
+struct Columns {
+    index: usize,
+    /// Missing from files exported before it was
+    generated: Option<usize>,
+    input: usize,
+    output: usize,
+}
+
+impl Columns {
+    fn new(header: &str, number: usize) -> io::Result<Self> {
+        let names: Vec<_> = header.split(',').collect();
+        let position = |name| names.iter().position(|column| *column == name);
+        let required = |name| {
+            position(name)
+                .ok_or_else(|| invalid(format!("missing column {name:?} on line {number}")))
+        };
+
+        Ok(Self {
+            index: required("index")?,
+            generated: position("generated"),
+            input: required("input")?,
+            output: required("output")?,
+        })
+    }
+}
+
+/// Parses an `index,severity,message` log line, its message escaped by [`str::escape_debug`]
+fn entry(value: &str, number: usize) -> io::Result<Entry> {
+    let mut fields = value.splitn(3, ',');
+    let (Some(index), Some(severity_name), Some(message)) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid(format!("malformed log entry on line {number}")));
+    };
+
+    Ok(Entry {
+        index: parse(index, number)?,
+        severity: severity(severity_name),
+        message: unescape(message),
+    })
+}
+
+/// Severity by name, unknown ones being treated as debug output like unknown levels
+fn severity(name: &str) -> Severity {
+    Severity::ALL
+        .into_iter()
+        .find(|severity| severity.to_string() == name)
+        .unwrap_or(Severity::Debug)
+}
+
+/// Reverses [`str::escape_debug`], keeping unknown escapes as they are
+fn unescape(escaped: &str) -> String {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('0') => unescaped.push('\0'),
+            Some('u') => {
+                let rest = chars.as_str();
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .and_then(|(hex, _)| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32);
+
+                match code {
+                    Some(c) => {
+                        unescaped.push(c);
+                        chars = rest[rest.find('}').map_or(0, |end| end + 1)..].chars();
+                    }
+                    None => unescaped.push_str("\\u"),
+                }
+            }
+            Some(c @ ('\\' | '\'' | '"')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+fn parse<T>(value: &str, number: usize) -> io::Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| invalid(format!("invalid value {value:?} on line {number}: {e}")))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
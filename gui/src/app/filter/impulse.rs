@@ -0,0 +1,175 @@
+use iced::{
+    widget::{button, column, row, slider, text},
+    Element, Length,
+};
+use plotters::prelude::*;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget};
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use super::{
+    super::{persist, theme},
+    response::Spectra,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Message {
+    RegularizationUpdated(f32),
+    TapsUpdated(u16),
+    Export,
+}
+
+/// Impulse response plot, deconvolved from the run's spectra
+pub struct Impulse {
+    /// Base 10 logarithm of the regularization
+    regularization: f32,
+    /// Number of coefficients shown and exported
+    taps: u16,
+}
+
+impl Impulse {
+    pub const fn new() -> Self {
+        Self {
+            regularization: -4.0,
+            taps: 64,
+        }
+    }
+
+    pub fn update(&mut self, message: Message, spectra: Option<&Spectra>) {
+        match message {
+            Message::RegularizationUpdated(regularization) => self.regularization = regularization,
+            Message::TapsUpdated(taps) => self.taps = taps,
+            Message::Export => {
+                let Some(spectra) = spectra else {
+                    return;
+                };
+
+                let path = Path::new(crate::COEFFICIENTS_FILENAME);
+                match self.export(spectra, path) {
+                    Ok(()) => tracing::info!("Exported coefficients to {}", path.display()),
+                    Err(e) => tracing::error!("Unable to export coefficients: {e}"),
+                }
+            }
+        }
+    }
+
+    pub fn view(&self, spectra: Option<&Spectra>) -> Element<'_, Message> {
+        let Some(spectra) = spectra else {
+            return text("Not enough samples to estimate the impulse response")
+                .height(Length::Fill)
+                .into();
+        };
+
+        let chart = ChartWidget::new(Plot {
+            coefficients: self.coefficients(spectra),
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let controls = column![
+            row![
+                text(format!("Regularization: 1e{:.1}", self.regularization))
+                    .width(Length::FillPortion(1)),
+                slider(
+                    -8.0..=0.0,
+                    self.regularization,
+                    Message::RegularizationUpdated
+                )
+                .step(0.1),
+            ],
+            row![
+                text(format!("Coefficients: {}", self.taps)).width(Length::FillPortion(1)),
+                slider(8..=512, self.taps, Message::TapsUpdated),
+            ],
+            button("Export coefficients").on_press(Message::Export),
+        ]
+        .spacing(5);
+
+        column![chart, controls].spacing(10).into()
+    }
+
+    /// Impulse response with the current settings
+    pub fn coefficients(&self, spectra: &Spectra) -> Vec<f32> {
+        spectra.impulse_response(10f32.powf(self.regularization), self.taps.into())
+    }
+
+    /// Writes one coefficient per line, between a commented header and checksum
+    fn export(&self, spectra: &Spectra, path: &Path) -> io::Result<()> {
+        let coefficients = self.coefficients(spectra);
+
+        persist::write_checksummed(path, |writer| {
+            writeln!(writer, "# version={}", crate::VERSION)?;
+            writeln!(
+                writer,
+                "# Impulse response, {} coefficients",
+                coefficients.len()
+            )?;
+            writeln!(writer, "# regularization=1e{:.1}", self.regularization)?;
+
+            for coefficient in &coefficients {
+                writeln!(writer, "{coefficient}")?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+struct Plot {
+    coefficients: Vec<f32>,
+}
+
+impl Chart<Message> for Plot {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        _state: &Self::State,
+        mut builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let colors = theme::colors();
+        let extent = self
+            .coefficients
+            .iter()
+            .fold(f32::EPSILON, |extent, coefficient| {
+                extent.max(coefficient.abs())
+            })
+            * 1.1;
+
+        let mut chart = builder
+            .x_label_area_size(24)
+            .y_label_area_size(48)
+            .margin(10)
+            .build_cartesian_2d(0..self.coefficients.len(), -extent..extent)
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(colors.foreground)
+            .label_style(("sans-serif", 18).into_font().color(&colors.foreground))
+            .x_desc("n")
+            .y_desc("h[n]")
+            .light_line_style(colors.foreground.mix(0.10))
+            .bold_line_style(colors.foreground.mix(0.30))
+            .draw()
+            .expect("drawn mesh");
+
+        // Stems
+        chart
+            .draw_series(self.coefficients.iter().enumerate().map(|(n, &h)| {
+                PathElement::new(vec![(n, 0.0), (n, h)], colors.input.stroke_width(2))
+            }))
+            .expect("drawn stems");
+
+        chart
+            .draw_series(
+                self.coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(n, &h)| Circle::new((n, h), 3, colors.input.filled())),
+            )
+            .expect("drawn coefficients");
+    }
+}
@@ -0,0 +1,173 @@
+use iced::{
+    widget::{column, scrollable, text, Column},
+    Element, Length,
+};
+use parking_lot::Mutex;
+use std::{collections::VecDeque, fmt, mem, sync::Arc};
+
+/// Most payload bytes, or characters of a line, previewed per frame
+const PREVIEW: usize = 16;
+
+/// What a frame's payload starts with, as frames may hold samples and reports alike
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Samples,
+    Telemetry,
+    Log,
+    TimeSync,
+    /// The empty payload ending the transmission
+    Eot,
+}
+
+impl Kind {
+    fn of(payload: &[u8]) -> Self {
+        match payload.get(..mem::size_of::<u32>()) {
+            _ if payload.is_empty() => Self::Eot,
+            Some(word) if word == crate::TELEMETRY => Self::Telemetry,
+            Some(word) if word == crate::LOG => Self::Log,
+            Some(word) if word == crate::TIME_SYNC => Self::TimeSync,
+            _ => Self::Samples,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Samples => "Samples",
+            Self::Telemetry => "Telemetry",
+            Self::Log => "Log",
+            Self::TimeSync => "Time sync",
+            Self::Eot => "EOT",
+        })
+    }
+}
+
+/// Frame or line as decoded by the receiver
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Not numbered in the ASCII protocol
+    pub sequence: Option<u16>,
+    pub kind: Kind,
+    /// Of the payload, in bytes, as announced by corrupted frames
+    pub length: usize,
+    /// First bytes of the payload in hexadecimal, or the start of a line
+    pub preview: String,
+    /// Why the frame was dropped, if it was
+    pub error: Option<String>,
+}
+
+impl Frame {
+    /// Frame of `length` bytes, whose `payload` is only read as far as it's previewed
+    pub fn binary(sequence: u16, length: usize, payload: &[u8], error: Option<String>) -> Self {
+        let shown = &payload[..payload.len().min(PREVIEW)];
+        let mut preview = shown
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if length > shown.len() {
+            preview.push_str(" …");
+        }
+
+        Self {
+            sequence: Some(sequence),
+            kind: Kind::of(payload),
+            length,
+            preview,
+            error,
+        }
+    }
+
+    /// Line of the ASCII protocol, without its line ending
+    pub fn line(line: &str, error: Option<String>) -> Self {
+        let mut preview: String = line.chars().take(PREVIEW).collect();
+        if line.chars().count() > PREVIEW {
+            preview.push('…');
+        }
+
+        Self {
+            sequence: None,
+            kind: if line.is_empty() {
+                Kind::Eot
+            } else {
+                Kind::Samples
+            },
+            length: line.len(),
+            preview,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sequence {
+            Some(sequence) => write!(f, "#{sequence:<5}")?,
+            None => write!(f, "{:6}", "")?,
+        }
+
+        write!(
+            f,
+            "  {:<9}  {:>4} B  {}  {}",
+            self.kind.to_string(),
+            self.length,
+            match &self.error {
+                None if self.sequence.is_some() => "CRC ok",
+                None => "ok",
+                Some(error) => error,
+            },
+            self.preview
+        )
+    }
+}
+
+/// Last [`crate::INSPECTED_FRAMES`] frames decoded, oldest first
+#[derive(Debug, Default)]
+pub struct Inspector {
+    frames: VecDeque<Frame>,
+    /// Including those no longer kept
+    decoded: usize,
+}
+
+/// [`Inspector`] shared with the receiver
+pub type Shared = Arc<Mutex<Inspector>>;
+
+impl Inspector {
+    pub fn record(&mut self, frame: Frame) {
+        if self.frames.len() == crate::INSPECTED_FRAMES {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(frame);
+        self.decoded += 1;
+    }
+}
+
+/// Lists the last frames decoded, newest first, with their sequence number, kind, length and
+/// CRC status, and a preview of their payload
+pub fn view<'a, M: 'a>(inspector: &Shared) -> Element<'a, M> {
+    let inspector = crate::perf::lock(inspector);
+    if inspector.frames.is_empty() {
+        return text("No frames decoded yet").into();
+    }
+
+    let frames: Column<'a, M> = inspector
+        .frames
+        .iter()
+        .rev()
+        .fold(Column::new().spacing(2), |frames, frame| {
+            frames.push(text(frame.to_string()).size(14))
+        });
+
+    column![
+        text(format!(
+            "Last {} of {} frames decoded",
+            inspector.frames.len(),
+            inspector.decoded
+        )),
+        scrollable(frames).height(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,133 @@
+use iced::widget::pane_grid::{self, Configuration, Node};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Rearrangeable panel of the workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Panel {
+    /// Selected [`super::View`]
+    Chart,
+    /// Metrics and transmission status
+    Stats,
+    Log,
+    /// Frequency response
+    Spectrum,
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Chart => "Chart",
+            Self::Stats => "Statistics",
+            Self::Log => "Device log",
+            Self::Spectrum => "Spectrum",
+        })
+    }
+}
+
+/// Direction of a split, as [`pane_grid::Axis`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    /// One region above the other
+    Horizontal,
+    /// Regions side by side
+    Vertical,
+}
+
+impl From<Axis> for pane_grid::Axis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<pane_grid::Axis> for Axis {
+    fn from(axis: pane_grid::Axis) -> Self {
+        match axis {
+            pane_grid::Axis::Horizontal => Self::Horizontal,
+            pane_grid::Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+/// Arrangement of [`Panel`]s, persisted per profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Layout {
+    Panel(Panel),
+    Split {
+        axis: Axis,
+        /// Share of the first region, in `0.0..1.0`
+        ratio: f32,
+        a: Box<Layout>,
+        b: Box<Layout>,
+    },
+}
+
+impl Default for Layout {
+    /// Chart above the statistics, with the spectrum above the log to their right
+    fn default() -> Self {
+        let split = |axis, ratio, a, b| Self::Split {
+            axis,
+            ratio,
+            a: Box::new(a),
+            b: Box::new(b),
+        };
+
+        split(
+            Axis::Vertical,
+            0.7,
+            split(
+                Axis::Horizontal,
+                0.75,
+                Self::Panel(Panel::Chart),
+                Self::Panel(Panel::Stats),
+            ),
+            split(
+                Axis::Horizontal,
+                0.5,
+                Self::Panel(Panel::Spectrum),
+                Self::Panel(Panel::Log),
+            ),
+        )
+    }
+}
+
+impl Layout {
+    /// Panes arranged as described
+    pub fn panes(&self) -> pane_grid::State<Panel> {
+        pane_grid::State::with_configuration(self.configuration())
+    }
+
+    /// Describes the current arrangement of `panes`
+    pub fn of(panes: &pane_grid::State<Panel>) -> Self {
+        Self::of_node(panes.layout(), panes)
+    }
+
+    fn configuration(&self) -> Configuration<Panel> {
+        match self {
+            Self::Panel(panel) => Configuration::Pane(*panel),
+            Self::Split { axis, ratio, a, b } => Configuration::Split {
+                axis: (*axis).into(),
+                ratio: *ratio,
+                a: Box::new(a.configuration()),
+                b: Box::new(b.configuration()),
+            },
+        }
+    }
+
+    fn of_node(node: &Node, panes: &pane_grid::State<Panel>) -> Self {
+        match node {
+            Node::Pane(pane) => Self::Panel(*panes.get(pane).expect("pane in layout")),
+            Node::Split {
+                axis, ratio, a, b, ..
+            } => Self::Split {
+                axis: (*axis).into(),
+                ratio: *ratio,
+                a: Box::new(Self::of_node(a, panes)),
+                b: Box::new(Self::of_node(b, panes)),
+            },
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use parking_lot::Mutex;
+use serialport::SerialPort;
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    mem,
+    net::TcpStream,
+    sync::Arc,
+};
+
+use super::{lock::PortLock, Serial};
+
+/// Ends of the link kept open by the last run, for the next run on the same device
+static IDLE: Mutex<Idle> = parking_lot::const_mutex(Idle::EMPTY);
+
+/// Receiving end of a [`Link`]
+pub trait Source: Read + Send {
+    /// Number of bytes waiting to be read, if known
+    fn backlog(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl Source for Serial {
+    fn backlog(&self) -> Option<usize> {
+        self.bytes_to_read().ok().map(|bytes| bytes as usize)
+    }
+}
+
+/// End of a link keeping its port locked, until both ends are dropped
+struct Locked<T> {
+    inner: T,
+    _lock: Arc<PortLock>,
+}
+
+impl<T: Read> Read for Locked<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<T: Write> Write for Locked<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Source> Source for Locked<T> {
+    fn backlog(&self) -> Option<usize> {
+        self.inner.backlog()
+    }
+}
+
+/// Ends of a link returned by a finished run
+#[derive(Default)]
+struct Idle {
+    /// Display name of the device they reach
+    device: Option<String>,
+    tx: Option<Box<dyn Write + Send>>,
+    rx: Option<Box<dyn Source>>,
+}
+
+impl Idle {
+    const EMPTY: Self = Self {
+        device: None,
+        tx: None,
+        rx: None,
+    };
+}
+
+/// End of a link, returned to [`IDLE`] when dropped
+struct Kept<T: Keep> {
+    inner: Option<T>,
+    /// Display name of the device it reaches
+    device: String,
+}
+
+trait Keep {
+    fn keep(self, idle: &mut Idle);
+}
+
+impl Keep for Box<dyn Write + Send> {
+    fn keep(self, idle: &mut Idle) {
+        idle.tx = Some(self);
+    }
+}
+
+impl Keep for Box<dyn Source> {
+    fn keep(self, idle: &mut Idle) {
+        idle.rx = Some(self);
+    }
+}
+
+impl<T: Keep> Kept<T> {
+    fn inner(&mut self) -> &mut T {
+        self.inner.as_mut().expect("end kept until dropped")
+    }
+}
+
+impl<T: Keep> Drop for Kept<T> {
+    fn drop(&mut self) {
+        let inner = self.inner.take().expect("end kept until dropped");
+        let mut idle = crate::perf::lock(&IDLE);
+
+        // Ends of another device are closed
+        if idle.device.as_ref() != Some(&self.device) {
+            *idle = Idle {
+                device: Some(self.device.clone()),
+                ..Idle::EMPTY
+            };
+        }
+
+        inner.keep(&mut idle);
+    }
+}
+
+impl Read for Kept<Box<dyn Source>> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner().read(buf)
+    }
+}
+
+impl Source for Kept<Box<dyn Source>> {
+    fn backlog(&self) -> Option<usize> {
+        self.inner.as_ref()?.backlog()
+    }
+}
+
+impl Write for Kept<Box<dyn Write + Send>> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner().flush()
+    }
+}
+
+/// Connection to a networked device
+struct Tcp(TcpStream);
+
+impl Read for Tcp {
+    /// Reports read timeouts as [`io::ErrorKind::TimedOut`] on every platform, as serial ports do
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::WouldBlock => io::ErrorKind::TimedOut.into(),
+            _ => e,
+        })
+    }
+}
+
+impl Write for Tcp {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Source for Tcp {}
+
+/// Connection to a serial, networked or emulated device
+pub struct Link {
+    pub tx: Box<dyn Write + Send>,
+    pub rx: Box<dyn Source>,
+}
+
+impl Link {
+    /// Link over `serial`, whose port stays locked by `lock` while either end is in use
+    pub fn serial(serial: Serial, lock: PortLock) -> io::Result<Self> {
+        let tx = serial.try_clone_native()?;
+        let lock = Arc::new(lock);
+
+        Ok(Self {
+            tx: Box::new(Locked {
+                inner: tx,
+                _lock: lock.clone(),
+            }),
+            rx: Box::new(Locked {
+                inner: serial,
+                _lock: lock,
+            }),
+        })
+    }
+
+    /// Link over `stream`, whose reads should time out
+    pub fn tcp(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            tx: Box::new(Tcp(stream.try_clone()?)),
+            rx: Box::new(Tcp(stream)),
+        })
+    }
+
+    /// Same link, kept open once both its ends are dropped for the next run on `device`, its
+    /// display name, to [`Self::reopen`]
+    pub fn keep_open(self, device: String) -> Self {
+        Self {
+            tx: Box::new(Kept {
+                inner: Some(self.tx),
+                device: device.clone(),
+            }),
+            rx: Box::new(Kept {
+                inner: Some(self.rx),
+                device,
+            }),
+        }
+    }
+
+    /// Link kept open by the last run on `device`, once both its ends were dropped
+    pub fn reopen(device: &str) -> Option<Self> {
+        let mut idle = crate::perf::lock(&IDLE);
+        if idle.device.as_deref() != Some(device) || idle.tx.is_none() || idle.rx.is_none() {
+            return None;
+        }
+
+        let Idle { tx, rx, .. } = mem::take(&mut *idle);
+        Some(Self { tx: tx?, rx: rx? })
+    }
+
+    /// Closes the link kept open by the last run, if any, releasing its port
+    pub fn close_idle() {
+        let idle = mem::take(&mut *crate::perf::lock(&IDLE));
+        drop(idle);
+    }
+}
+
+impl Read for Link {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.rx.read(buf)
+    }
+}
+
+impl Write for Link {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.tx.flush()
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link").finish_non_exhaustive()
+    }
+}
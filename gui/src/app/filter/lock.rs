@@ -0,0 +1,103 @@
+use fs2::FileExt;
+use parking_lot::Mutex;
+use std::{
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Ports held by this instance
+static HELD: Mutex<BTreeSet<String>> = parking_lot::const_mutex(BTreeSet::new());
+
+/// Exclusive claim on a serial port, across runs of this instance and other instances,
+/// released when dropped
+///
+/// Other instances are kept out by an advisory lock on a lock file, which the OS releases once
+/// the holder's process is gone, even if it crashed. The file is left in place, as another
+/// instance may be about to lock it
+#[derive(Debug)]
+pub struct PortLock {
+    port: String,
+    /// Locked file, if one could be opened
+    file: Option<File>,
+}
+
+impl PortLock {
+    /// Claims `port`
+    ///
+    /// # Errors
+    /// Fails if `port` is in use by another run or another instance
+    pub fn acquire(port: &str) -> io::Result<Self> {
+        if !crate::perf::lock(&HELD).insert(port.to_owned()) {
+            return Err(in_use(format!("{port} is in use by another run")));
+        }
+
+        // Released from the registry if the lock file is held elsewhere
+        let mut lock = Self {
+            port: port.to_owned(),
+            file: None,
+        };
+
+        let path = path(port);
+        match claim(&path) {
+            Ok(file) => lock.file = Some(file),
+
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                // Unreadable where locks are mandatory, as on Windows
+                let holder = fs::read_to_string(&path).unwrap_or_default();
+                let holder = match holder.trim() {
+                    "" => String::new(),
+                    pid => format!(" (process {pid})"),
+                };
+
+                return Err(in_use(format!(
+                    "{port} is in use by another instance{holder}"
+                )));
+            }
+
+            // Locking is best effort where no lock file can be written
+            Err(e) => tracing::warn!("Unable to lock {port} against other instances: {e}"),
+        }
+
+        Ok(lock)
+    }
+}
+
+impl Drop for PortLock {
+    fn drop(&mut self) {
+        // Unlocked before other runs of this instance may claim the port again
+        drop(self.file.take());
+        crate::perf::lock(&HELD).remove(&self.port);
+    }
+}
+
+/// Opens and locks the lock file at `path`, recording this process
+fn claim(path: &Path) -> io::Result<File> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.try_lock_exclusive()?;
+
+    file.set_len(0)?;
+    write!(file, "{}", process::id())?;
+    Ok(file)
+}
+
+/// Lock file of `port`, shared by all instances
+fn path(port: &str) -> PathBuf {
+    let name: String = port
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    std::env::temp_dir().join(format!("online-filtering-{name}.lock"))
+}
+
+fn in_use(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::AddrInUse, message)
+}
@@ -0,0 +1,125 @@
+use iced::{
+    widget::{column, pick_list, row, scrollable, text, text_input, Column},
+    Alignment, Element, Length,
+};
+use parking_lot::Mutex;
+use std::{fmt, sync::Arc};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SeveritySelected(Severity),
+    QueryUpdated(String),
+}
+
+/// Importance of a device log message, encoded as its discriminant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error = 0,
+    Warning = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl Severity {
+    pub const ALL: [Self; 4] = [Self::Error, Self::Warning, Self::Info, Self::Debug];
+
+    /// Decodes the severity word of a log frame, unknown levels being treated as debug output
+    pub const fn from_word(word: u32) -> Self {
+        match word {
+            0 => Self::Error,
+            1 => Self::Warning,
+            2 => Self::Info,
+            _ => Self::Debug,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "Error",
+            Self::Warning => "Warning",
+            Self::Info => "Info",
+            Self::Debug => "Debug",
+        })
+    }
+}
+
+/// Message logged by the device
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Number of outputs received before the message
+    pub index: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Messages logged by the device during a run
+///
+/// Logged as [`crate::LOG`], then the severity and the length in bytes as `u32`s, then the
+/// UTF-8 message padded to a multiple of 4 bytes, all in place of samples
+pub type Log = Arc<Mutex<Vec<Entry>>>;
+
+/// Filterable view of a [`Log`]
+pub struct Panel {
+    /// Least important severity shown
+    severity: Severity,
+    /// Only messages containing this are shown, ignoring case
+    query: String,
+}
+
+impl Panel {
+    pub const fn new() -> Self {
+        Self {
+            severity: Severity::Info,
+            query: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SeveritySelected(severity) => self.severity = severity,
+            Message::QueryUpdated(query) => self.query = query,
+        }
+    }
+
+    pub fn view<'a>(&'a self, log: &Log, sampling_interval: f32) -> Element<'a, Message> {
+        let filters = row![
+            text("Show up to"),
+            pick_list(
+                &Severity::ALL[..],
+                Some(self.severity),
+                Message::SeveritySelected
+            ),
+            text_input("Filter...", &self.query).on_input(Message::QueryUpdated),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let query = self.query.to_lowercase();
+        let log = crate::perf::lock(log);
+        let entries: Column<'a, Message> = log
+            .iter()
+            .filter(|entry| entry.severity <= self.severity)
+            .filter(|entry| query.is_empty() || entry.message.to_lowercase().contains(&query))
+            .fold(Column::new().spacing(2), |entries, entry| {
+                entries.push(text(format!(
+                    "{:>10.3} s  {:<7}  {}",
+                    entry.index as f32 * sampling_interval,
+                    entry.severity.to_string(),
+                    entry.message
+                )))
+            });
+
+        let content: Element<'a, Message> = if log.is_empty() {
+            text("No messages logged by the device").into()
+        } else {
+            scrollable(entries).height(Length::Fill).into()
+        };
+
+        column![filters, content]
+            .spacing(10)
+            .height(Length::Fill)
+            .into()
+    }
+}
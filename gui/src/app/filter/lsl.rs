@@ -0,0 +1,86 @@
+use ::lsl::{ChannelFormat, ExPushable, StreamInfo, StreamOutlet};
+use parking_lot::Mutex;
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    storage::Samples,
+    workers::{Shutdown, Supervisor},
+};
+
+/// Spawns a Lab Streaming Layer outlet streaming outputs as they are received, until shut down
+///
+/// The stream is identified by `source`, the name of the device, and its single channel
+/// carries outputs in `unit`
+pub fn spawn(
+    supervisor: &mut Supervisor,
+    source: String,
+    unit: String,
+    output_interval: f32,
+    output: Arc<Mutex<Samples>>,
+) {
+    supervisor.spawn("LSL outlet", move |shutdown| {
+        if let Err(e) = stream(&source, &unit, output_interval, &output, &shutdown) {
+            tracing::error!("LSL outlet failed: {e:?}");
+        }
+
+        tracing::info!("LSL streaming ended");
+    });
+}
+
+/// Outputs are timestamped on the LSL clock when read, spaced by `output_interval`, so that
+/// recorders can align them with other streams
+fn stream(
+    source: &str,
+    unit: &str,
+    output_interval: f32,
+    output: &Mutex<Samples>,
+    shutdown: &Shutdown,
+) -> Result<(), ::lsl::Error> {
+    let rate = f64::from(output_interval.recip());
+    let mut info = StreamInfo::new(
+        "Online filtering",
+        "Filtered",
+        1,
+        rate,
+        ChannelFormat::Float32,
+        source,
+    )?;
+
+    let mut description = info.desc();
+    description.append_child_value("manufacturer", "online-filtering");
+    description
+        .append_child("channels")
+        .append_child("channel")
+        .append_child_value("label", "Output")
+        .append_child_value("unit", unit)
+        .append_child_value("type", "Filtered");
+
+    let outlet = StreamOutlet::new(&info, 0, 360)?;
+    tracing::info!("Streaming outputs over LSL at {rate} Hz as {source}");
+
+    // Index of the next output to stream
+    let mut next = 0;
+
+    loop {
+        // Outputs received since the last frame are still streamed once shut down
+        let ordered = shutdown.is_ordered();
+
+        let samples = crate::perf::lock(output)[next..].to_vec();
+        let now = ::lsl::local_clock();
+
+        for (i, sample) in samples.iter().enumerate() {
+            let before = (samples.len() - 1 - i) as f64;
+            let timestamp = before.mul_add(-f64::from(output_interval), now);
+            outlet.push_sample_ex(&vec![*sample], timestamp, i + 1 == samples.len())?;
+        }
+
+        next += samples.len();
+        if ordered {
+            break;
+        }
+
+        shutdown.sleep(Duration::from_millis(1000 / crate::FPS));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use parking_lot::Mutex;
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Arc,
+    time::Duration,
+};
+
+use super::{storage::Samples, workers::Supervisor};
+
+/// OSC address of published outputs, whose arguments are the output's index as an `int32` and
+/// its value as a `float32`
+const ADDRESS: &str = "/online-filtering/output";
+
+/// Publishing of live outputs over Open Sound Control
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// `host:port` of the UDP listener
+    pub target: String,
+    /// Only every `decimation`th output is published
+    pub decimation: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            target: crate::DEFAULT_OSC_TARGET.to_owned(),
+            decimation: 10,
+        }
+    }
+}
+
+/// Encodes an OSC message to [`ADDRESS`]
+///
+/// Strings are null-terminated and padded to 4 bytes, numbers are big-endian
+fn message(index: usize, value: f32) -> Vec<u8> {
+    fn string(packet: &mut Vec<u8>, string: &str) {
+        packet.extend_from_slice(string.as_bytes());
+        packet.push(0);
+        packet.resize(packet.len() + (4 - packet.len() % 4) % 4, 0);
+    }
+
+    let mut packet = Vec::with_capacity(40);
+    string(&mut packet, ADDRESS);
+    string(&mut packet, ",if");
+    packet.extend_from_slice(&i32::try_from(index).unwrap_or(i32::MAX).to_be_bytes());
+    packet.extend_from_slice(&value.to_be_bytes());
+    packet
+}
+
+/// Spawns a publisher sending outputs as they are received, until shut down
+///
+/// # Errors
+/// Fails if the target can't be resolved, or no socket can be bound
+pub fn spawn(
+    supervisor: &mut Supervisor,
+    settings: &Settings,
+    output: Arc<Mutex<Samples>>,
+) -> io::Result<()> {
+    let target = settings.target.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} resolves to no address", settings.target),
+        )
+    })?;
+    let local: SocketAddr = if target.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+
+    let socket = UdpSocket::bind(local)?;
+    let decimation = usize::from(settings.decimation.max(1));
+    tracing::info!("Publishing every {decimation} outputs over OSC to {target}");
+
+    supervisor.spawn("OSC publisher", move |shutdown| {
+        // Index of the next output to publish
+        let mut next = 0;
+
+        loop {
+            // Outputs received since the last frame are still published once shut down
+            let ordered = shutdown.is_ordered();
+
+            let packets: Vec<_> = {
+                let output = crate::perf::lock(&output);
+                (next..output.len())
+                    .step_by(decimation)
+                    .map(|i| message(i, output[i]))
+                    .collect()
+            };
+            next += packets.len() * decimation;
+
+            for packet in packets {
+                if let Err(e) = socket.send_to(&packet, target) {
+                    tracing::debug!("Unable to publish over OSC: {e}");
+                }
+            }
+
+            if ordered {
+                break;
+            }
+
+            shutdown.sleep(Duration::from_millis(1000 / crate::FPS));
+        }
+
+        tracing::info!("OSC publishing ended");
+    });
+
+    Ok(())
+}
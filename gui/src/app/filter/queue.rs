@@ -0,0 +1,171 @@
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    thread::{self, Thread},
+    time::Duration,
+};
+
+/// Bytes of a read, timestamped on arrival
+pub struct Chunk {
+    buffer: Box<[u8]>,
+    len: usize,
+    /// [`super::clock::timestamp`] of the read
+    pub received: u32,
+}
+
+impl Chunk {
+    pub fn bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+}
+
+/// Ring of chunks, each only ever accessed by one end at a time
+struct Shared {
+    chunks: Box<[UnsafeCell<Chunk>]>,
+    /// Chunks pushed so far, only written by the [`Producer`]
+    head: AtomicUsize,
+    /// Chunks popped so far, only written by the [`Consumer`]
+    tail: AtomicUsize,
+    /// Set once either end is dropped
+    closed: AtomicBool,
+    /// Thread of the consumer, once it waited
+    consumer: OnceLock<Thread>,
+}
+
+// Chunks between `tail` and `head` belong to the consumer, the others to the producer
+unsafe impl Sync for Shared {}
+
+impl Shared {
+    fn chunk(&self, index: usize) -> *mut Chunk {
+        self.chunks[index % self.chunks.len()].get()
+    }
+}
+
+/// Creates a lock-free queue of `capacity` chunks of up to `size` bytes, allocated up front,
+/// between a single producer and a single consumer
+pub fn channel(capacity: usize, size: usize) -> (Producer, Consumer) {
+    let chunks = (0..capacity.max(1))
+        .map(|_| {
+            UnsafeCell::new(Chunk {
+                buffer: vec![0; size].into_boxed_slice(),
+                len: 0,
+                received: 0,
+            })
+        })
+        .collect();
+
+    let shared = Arc::new(Shared {
+        chunks,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        closed: AtomicBool::new(false),
+        consumer: OnceLock::new(),
+    });
+
+    (
+        Producer {
+            shared: Arc::clone(&shared),
+        },
+        Consumer { shared },
+    )
+}
+
+/// Filling end of a queue, closing it when dropped
+pub struct Producer {
+    shared: Arc<Shared>,
+}
+
+impl Producer {
+    /// Buffer of the next chunk to fill, or [`None`] while the queue is full
+    pub fn slot(&mut self) -> Option<&mut [u8]> {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+
+        // SAFETY: the consumer is done with chunks it popped, and doesn't touch the others
+        (head.wrapping_sub(tail) < self.shared.chunks.len())
+            .then(|| unsafe { &mut (&mut *self.shared.chunk(head)).buffer[..] })
+    }
+
+    /// Hands the chunk of the last [`Self::slot`] to the consumer, with the first `len` bytes
+    /// of its buffer filled at `received`
+    pub fn push(&mut self, len: usize, received: u32) {
+        let head = self.shared.head.load(Ordering::Relaxed);
+
+        // SAFETY: as in `slot`
+        let chunk = unsafe { &mut *self.shared.chunk(head) };
+        chunk.len = len.min(chunk.buffer.len());
+        chunk.received = received;
+
+        self.shared
+            .head
+            .store(head.wrapping_add(1), Ordering::Release);
+        if let Some(consumer) = self.shared.consumer.get() {
+            consumer.unpark();
+        }
+    }
+
+    /// Is the consumer gone?
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for Producer {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        if let Some(consumer) = self.shared.consumer.get() {
+            consumer.unpark();
+        }
+    }
+}
+
+/// Draining end of a queue, closing it when dropped
+pub struct Consumer {
+    shared: Arc<Shared>,
+}
+
+impl Consumer {
+    /// Oldest chunk not yet popped, if any
+    pub fn front(&self) -> Option<&Chunk> {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        let head = self.shared.head.load(Ordering::Acquire);
+
+        // SAFETY: the producer is done with chunks it pushed, and doesn't touch them until
+        // they're popped
+        (tail != head).then(|| unsafe { &*self.shared.chunk(tail) })
+    }
+
+    /// Hands the chunk of [`Self::front`] back to the producer
+    pub fn pop(&mut self) {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        if tail != self.shared.head.load(Ordering::Acquire) {
+            self.shared
+                .tail
+                .store(tail.wrapping_add(1), Ordering::Release);
+        }
+    }
+
+    /// Is the producer gone, with every chunk it pushed popped?
+    pub fn is_drained(&self) -> bool {
+        // Checked first, as chunks pushed before closing must be seen
+        self.shared.closed.load(Ordering::Acquire) && self.front().is_none()
+    }
+
+    /// Waits for a chunk to be pushed or the producer to go away, for up to `timeout`
+    pub fn wait(&self, timeout: Duration) {
+        self.shared.consumer.get_or_init(thread::current);
+
+        if self.front().is_none() && !self.shared.closed.load(Ordering::Acquire) {
+            thread::park_timeout(timeout);
+        }
+    }
+}
+
+impl Drop for Consumer {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+    }
+}
@@ -0,0 +1,265 @@
+use rustfft::{num_complex::Complex32, FftPlanner};
+use std::f32::consts::PI;
+
+/// Segment length of the spectral estimates
+const SEGMENT: usize = 1024;
+/// Bins with less input power than this, relative to the strongest one, aren't excited enough
+/// to be measured
+const MIN_RELATIVE_POWER: f32 = 1e-6;
+
+/// Averaged spectra of paired input and output samples
+#[derive(Debug, Clone)]
+pub struct Spectra {
+    /// Input to output cross spectrum, for bins `0..=SEGMENT / 2`
+    cross: Vec<Complex32>,
+    /// Input power spectrum
+    power: Vec<f32>,
+    /// Bin spacing, in Hz
+    resolution: f32,
+}
+
+/// Measured frequency response of the device
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// In Hz, ascending
+    pub frequency: Vec<f32>,
+    /// In dB
+    pub magnitude: Vec<f32>,
+}
+
+impl Spectra {
+    /// Estimates the spectra of `input` and `output`, if there are enough samples
+    ///
+    /// Averages Hann-windowed segments overlapping by half (Welch's method).
+    /// Segments containing non-finite samples are skipped
+    pub fn estimate(input: &[f32], output: &[f32], sampling_interval: f32) -> Option<Self> {
+        let len = input.len().min(output.len());
+        if len < SEGMENT {
+            return None;
+        }
+
+        let fft = FftPlanner::new().plan_fft_forward(SEGMENT);
+        let window: Vec<f32> = (0..SEGMENT)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / SEGMENT as f32).cos())
+            .collect();
+
+        let bins = SEGMENT / 2 + 1;
+        let mut cross = vec![Complex32::default(); bins];
+        let mut power = vec![0f32; bins];
+
+        let mut x = vec![Complex32::default(); SEGMENT];
+        let mut y = vec![Complex32::default(); SEGMENT];
+
+        for start in (0..=len - SEGMENT).step_by(SEGMENT / 2) {
+            let input = &input[start..start + SEGMENT];
+            let output = &output[start..start + SEGMENT];
+
+            if !input.iter().chain(output).all(|sample| sample.is_finite()) {
+                continue;
+            }
+
+            for (i, w) in window.iter().enumerate() {
+                x[i] = Complex32::new(input[i] * w, 0.0);
+                y[i] = Complex32::new(output[i] * w, 0.0);
+            }
+
+            fft.process(&mut x);
+            fft.process(&mut y);
+
+            for (((cross, power), x), y) in cross.iter_mut().zip(&mut power).zip(&x).zip(&y) {
+                *cross += x.conj() * y;
+                *power += x.norm_sqr();
+            }
+        }
+
+        if power.iter().all(|&power| power <= 0.0) {
+            return None;
+        }
+
+        Some(Self {
+            cross,
+            power,
+            resolution: (SEGMENT as f32 * sampling_interval).recip(),
+        })
+    }
+
+    /// Magnitude response at sufficiently excited frequencies, through the H1 estimator
+    /// (cross spectrum over input power spectrum)
+    pub fn response(&self) -> Response {
+        let threshold = self.peak_power() * MIN_RELATIVE_POWER;
+
+        // DC is skipped, as it can't be shown on a logarithmic axis
+        let (frequency, magnitude) = (1..self.power.len())
+            .filter(|&k| self.power[k] > threshold)
+            .map(|k| {
+                (
+                    k as f32 * self.resolution,
+                    20.0 * (self.cross[k].norm() / self.power[k]).log10(),
+                )
+            })
+            .unzip();
+
+        Response {
+            frequency,
+            magnitude,
+        }
+    }
+
+    /// First `taps` coefficients of the impulse response, through regularized deconvolution
+    ///
+    /// Inverse transform of the cross spectrum over the input power spectrum plus
+    /// `regularization` times its peak, which keeps weakly excited frequencies from
+    /// amplifying noise at the cost of resolution
+    pub fn impulse_response(&self, regularization: f32, taps: usize) -> Vec<f32> {
+        let floor = self.peak_power() * regularization;
+
+        let spectrum = self
+            .cross
+            .iter()
+            .zip(&self.power)
+            .map(|(cross, power)| *cross / (power + floor))
+            .collect();
+
+        let mut impulse_response = inverse(spectrum);
+        impulse_response.truncate(taps);
+        impulse_response
+    }
+
+    /// Delay of the output behind the input in samples, at the cross-correlation peak
+    ///
+    /// The peak is searched by magnitude, so inverting devices are handled too. Only lags
+    /// within half a segment can be found
+    pub fn lag(&self) -> isize {
+        let correlation = inverse(self.cross.clone());
+
+        let peak = correlation
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+            .map_or(0, |(k, _)| k);
+
+        let peak = isize::try_from(peak).expect("small segment");
+        if peak < isize::try_from(SEGMENT / 2).expect("small segment") {
+            peak
+        } else {
+            peak - isize::try_from(SEGMENT).expect("small segment")
+        }
+    }
+
+    fn peak_power(&self) -> f32 {
+        self.power.iter().copied().fold(0f32, f32::max)
+    }
+}
+
+/// Real inverse transform of the bins `0..=SEGMENT / 2` of a real signal's spectrum
+fn inverse(mut spectrum: Vec<Complex32>) -> Vec<f32> {
+    // Real signals have Hermitian spectra
+    let mirrored: Vec<_> = spectrum[1..SEGMENT / 2]
+        .iter()
+        .rev()
+        .map(Complex32::conj)
+        .collect();
+    spectrum.extend(mirrored);
+
+    FftPlanner::new()
+        .plan_fft_inverse(SEGMENT)
+        .process(&mut spectrum);
+
+    spectrum
+        .iter()
+        .map(|coefficient| coefficient.re / SEGMENT as f32)
+        .collect()
+}
+
+/// Limits on a frequency response
+///
+/// Describes a low-pass if the passband edge lies below the stopband edge, and a high-pass
+/// otherwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mask {
+    /// In Hz
+    pub passband_edge: f32,
+    /// Allowed deviation from 0 dB in the passband, in dB
+    pub ripple: f32,
+    /// In Hz
+    pub stopband_edge: f32,
+    /// Minimum attenuation in the stopband, in dB
+    pub attenuation: f32,
+}
+
+/// Worst excursion of a [`Response`] outside of a [`Mask`]
+#[derive(Debug, Clone, Copy)]
+pub struct Violation {
+    pub band: Band,
+    /// In Hz
+    pub frequency: f32,
+    /// By how much the limit is exceeded, in dB
+    pub excess: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Band {
+    Passband,
+    Stopband,
+}
+
+impl Mask {
+    /// A low-pass mask for a device sampling at twice `nyquist`
+    pub fn new(nyquist: f32) -> Self {
+        Self {
+            passband_edge: 0.1 * nyquist,
+            ripple: 1.0,
+            stopband_edge: 0.3 * nyquist,
+            attenuation: 20.0,
+        }
+    }
+
+    pub fn is_low_pass(&self) -> bool {
+        self.passband_edge < self.stopband_edge
+    }
+
+    pub fn band(&self, frequency: f32) -> Option<Band> {
+        let (passband, stopband) = if self.is_low_pass() {
+            (
+                frequency <= self.passband_edge,
+                frequency >= self.stopband_edge,
+            )
+        } else {
+            (
+                frequency >= self.passband_edge,
+                frequency <= self.stopband_edge,
+            )
+        };
+
+        if passband {
+            Some(Band::Passband)
+        } else if stopband {
+            Some(Band::Stopband)
+        } else {
+            None
+        }
+    }
+
+    /// Worst violation of the mask by `response`, if any
+    pub fn check(&self, response: &Response) -> Option<Violation> {
+        response
+            .frequency
+            .iter()
+            .zip(&response.magnitude)
+            .filter_map(|(&frequency, &magnitude)| {
+                let band = self.band(frequency)?;
+                let excess = match band {
+                    Band::Passband => magnitude.abs() - self.ripple,
+                    Band::Stopband => magnitude + self.attenuation,
+                };
+
+                Some(Violation {
+                    band,
+                    frequency,
+                    excess,
+                })
+            })
+            .filter(|violation| violation.excess > 0.0)
+            .max_by(|a, b| a.excess.total_cmp(&b.excess))
+    }
+}
@@ -0,0 +1,220 @@
+use iced::{widget::text, Element, Length};
+use parking_lot::Mutex;
+use plotters::prelude::*;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget};
+use rustfft::{num_complex::Complex32, FftPlanner};
+use std::{f32::consts::PI, sync::Arc};
+
+use super::{super::theme, storage::Samples};
+
+/// Longer signals are decimated down to this many samples
+const MAX_SAMPLES: usize = 1 << 16;
+/// Number of analyzed frequencies
+const ROWS: usize = 48;
+/// Number of time slots the transform is reduced to
+const COLUMNS: usize = 256;
+/// Center frequency of the Morlet wavelet, in radians per unit scale
+const OMEGA0: f32 = 6.0;
+
+/// Magnitude of the continuous wavelet transform of a signal
+#[derive(Debug)]
+pub struct Scalogram {
+    /// Analyzed frequencies in Hz, ascending and logarithmically spaced
+    frequencies: Vec<f32>,
+    /// One row of [`COLUMNS`] magnitudes per frequency, in signal units
+    magnitudes: Vec<Vec<f32>>,
+    /// In seconds
+    duration: f32,
+}
+
+/// Progress of a scalogram computation, started on demand
+#[derive(Debug)]
+pub enum Status {
+    Idle,
+    Pending,
+    /// [`None`] if the signal is too short
+    Done(Option<Scalogram>),
+}
+
+/// Computes the scalogram of `output` on the blocking thread pool
+///
+/// Uses Morlet wavelets, convolved through the FFT. The wavelets are normalized so a
+/// sinusoid's magnitude equals its amplitude at its frequency
+pub async fn compute(output: Arc<Mutex<Samples>>, sampling_interval: f32) -> Option<Scalogram> {
+    tokio::task::spawn_blocking(move || {
+        let (signal, factor) = {
+            let output = crate::perf::lock(&output).extent();
+            let factor = ((output.len() + MAX_SAMPLES - 1) / MAX_SAMPLES).max(1);
+
+            // Averaging doubles as a crude anti-aliasing filter
+            let signal: Vec<_> = output
+                .chunks(factor)
+                .map(|chunk| {
+                    chunk.iter().copied().filter(|x| x.is_finite()).sum::<f32>()
+                        / chunk.len() as f32
+                })
+                .collect();
+
+            (signal, factor)
+        };
+
+        let dt = sampling_interval * factor as f32;
+        let n = signal.len();
+        let duration = n as f32 * dt;
+
+        let lowest = 8.0 / duration;
+        let highest = 0.4 / dt;
+        if n < COLUMNS || lowest >= highest {
+            return None;
+        }
+
+        let frequencies: Vec<_> = (0..ROWS)
+            .map(|row| lowest * (highest / lowest).powf(row as f32 / (ROWS - 1) as f32))
+            .collect();
+
+        // Padded against circular wrap-around
+        let len = (2 * n).next_power_of_two();
+        let mut planner = FftPlanner::new();
+        let forward = planner.plan_fft_forward(len);
+        let inverse = planner.plan_fft_inverse(len);
+
+        let mut spectrum: Vec<_> = signal
+            .iter()
+            .map(|&x| Complex32::new(x, 0.0))
+            .chain(std::iter::repeat(Complex32::default()))
+            .take(len)
+            .collect();
+        forward.process(&mut spectrum);
+
+        let angular_resolution = 2.0 * PI / (len as f32 * dt);
+        let mut buffer = vec![Complex32::default(); len];
+
+        let magnitudes = frequencies
+            .iter()
+            .map(|frequency| {
+                let scale = OMEGA0 / (2.0 * PI * frequency);
+
+                for (j, (wavelet, x)) in buffer.iter_mut().zip(&spectrum).enumerate() {
+                    // Analytic wavelet, so negative frequencies are dropped
+                    *wavelet = if j <= len / 2 {
+                        let deviation = scale * j as f32 * angular_resolution - OMEGA0;
+                        *x * 2.0 * (-0.5 * deviation * deviation).exp()
+                    } else {
+                        Complex32::default()
+                    };
+                }
+
+                inverse.process(&mut buffer);
+
+                buffer[..n]
+                    .chunks((n + COLUMNS - 1) / COLUMNS)
+                    .map(|slot| {
+                        slot.iter()
+                            .map(|coefficient| coefficient.norm() / len as f32)
+                            .fold(0f32, f32::max)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(Scalogram {
+            frequencies,
+            magnitudes,
+            duration,
+        })
+    })
+    .await
+    .expect("scalogram task ran")
+}
+
+impl Status {
+    pub fn view<'a, M: 'a>(&'a self) -> Element<'a, M> {
+        let message = match self {
+            Self::Done(Some(scalogram)) => {
+                return ChartWidget::new(Plot { scalogram })
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into();
+            }
+
+            Self::Idle | Self::Pending => "Computing scalogram...",
+            Self::Done(None) => "Not enough samples to compute the scalogram",
+        };
+
+        text(message).height(Length::Fill).into()
+    }
+}
+
+struct Plot<'a> {
+    scalogram: &'a Scalogram,
+}
+
+impl<M> Chart<M> for Plot<'_> {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        _state: &Self::State,
+        mut builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let colors = theme::colors();
+        let scalogram = self.scalogram;
+        let (Some(&lowest), Some(&highest)) =
+            (scalogram.frequencies.first(), scalogram.frequencies.last())
+        else {
+            return;
+        };
+
+        // Half a row, as a frequency ratio
+        let ratio = (highest / lowest).powf(0.5 / (ROWS - 1) as f32);
+
+        let mut chart = builder
+            .x_label_area_size(24)
+            .y_label_area_size(48)
+            .margin(10)
+            .build_cartesian_2d(
+                0f32..scalogram.duration,
+                (lowest / ratio..highest * ratio).log_scale(),
+            )
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(colors.foreground)
+            .label_style(("sans-serif", 18).into_font().color(&colors.foreground))
+            .x_desc("s")
+            .y_desc("Hz")
+            .disable_mesh()
+            .draw()
+            .expect("drawn mesh");
+
+        let peak = scalogram
+            .magnitudes
+            .iter()
+            .flatten()
+            .fold(f32::EPSILON, |peak, &magnitude| peak.max(magnitude));
+
+        let cells = scalogram
+            .frequencies
+            .iter()
+            .zip(&scalogram.magnitudes)
+            .flat_map(|(&frequency, row)| {
+                let width = scalogram.duration / row.len() as f32;
+
+                row.iter().enumerate().map(move |(column, &magnitude)| {
+                    let start = column as f32 * width;
+                    let intensity = f64::from(magnitude / peak);
+
+                    Rectangle::new(
+                        [
+                            (start, frequency / ratio),
+                            (start + width, frequency * ratio),
+                        ],
+                        HSLColor(0.7 * (1.0 - intensity), 1.0, 0.5 * intensity).filled(),
+                    )
+                })
+            });
+
+        chart.draw_series(cells).expect("drawn scalogram");
+    }
+}
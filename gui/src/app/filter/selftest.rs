@@ -0,0 +1,252 @@
+use serialport::{ClearBuffer, SerialPort};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    mem,
+    sync::atomic::AtomicU16,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    super::profile::Profile,
+    connect,
+    framing::{Framer, Protocol},
+    link::Link,
+    lock::PortLock,
+    workers, Announcement, Target,
+};
+
+/// Outcome of a round-trip self-test
+///
+/// The device is expected to echo a known pseudo-random pattern exactly, as it does in
+/// pass-through mode, verifying the cabling, baud rate and framing
+#[derive(Debug, Clone, Copy)]
+pub struct Report {
+    /// Number of pattern words sent
+    pub sent: usize,
+    /// Number of words echoed back, sideband frames excluded
+    pub received: usize,
+    /// Echoed words differing from the pattern
+    pub corrupted: usize,
+    /// Differing bits among the echoed words
+    pub bit_errors: u64,
+}
+
+impl Report {
+    /// Were all words echoed back exactly?
+    pub const fn passed(&self) -> bool {
+        self.received == self.sent && self.corrupted == 0
+    }
+
+    /// Fraction of the echoed bits that were flipped
+    pub fn bit_error_rate(&self) -> f64 {
+        let bits = self.received.min(self.sent) * u32::BITS as usize;
+        if bits == 0 {
+            return 0.0;
+        }
+
+        self.bit_errors as f64 / bits as f64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed() {
+            return write!(f, "passed: {} words echoed exactly", self.sent);
+        }
+
+        write!(
+            f,
+            "failed: {} of {} words echoed, {} corrupted (BER {:.2e})",
+            self.received,
+            self.sent,
+            self.corrupted,
+            self.bit_error_rate()
+        )
+    }
+}
+
+/// Pseudo-random words from `seed`, through xorshift32
+///
+/// NaNs and infinities are avoided, as they would be taken for markers or rejected by the
+/// device, and so is negative zero, which arithmetic on the device may not preserve
+fn pattern(seed: u32) -> impl Iterator<Item = u32> {
+    const EXPONENT: u32 = 0x7F80_0000;
+    const NEGATIVE_ZERO: u32 = 0x8000_0000;
+
+    let mut state = seed.max(1);
+
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+
+        match state {
+            word if word & EXPONENT == EXPONENT => word ^ 0x0080_0000,
+            NEGATIVE_ZERO => 0,
+            word => word,
+        }
+    })
+}
+
+/// Sends [`crate::SELF_TEST_LENGTH`] pattern words from `seed` to `target` and compares them
+/// to its echo, connecting in up to `handshakes` attempts
+///
+/// Words are compared in order, so a dropped word, or frame of words failing its CRC, corrupts
+/// every following one
+///
+/// # Errors
+/// Fails if the device can't be reached, or doesn't echo at its input rate
+pub fn run(target: Target, profile: &Profile, seed: u32, handshakes: u16) -> io::Result<Report> {
+    let (
+        Announcement {
+            input,
+            output,
+            block,
+            channels,
+        },
+        mut link,
+    ) = connect(
+        target,
+        profile,
+        seed,
+        handshakes,
+        false,
+        Protocol::Binary,
+        &AtomicU16::new(0),
+    )?;
+
+    if output != input || block.is_some() || channels != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "self-test requires a device in pass-through mode",
+        ));
+    }
+
+    let (report, _) = echo(&mut link, seed)?;
+    tracing::info!("Self-test {report}");
+
+    Ok(report)
+}
+
+/// Outcome of a loopback test, with the port's TX jumpered to its RX
+#[derive(Debug, Clone, Copy)]
+pub struct Loopback {
+    pub report: Report,
+    /// Bytes echoed per second while streaming the pattern
+    pub throughput: f32,
+    /// Median round trip of a single word
+    pub latency: Duration,
+}
+
+impl fmt::Display for Loopback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 8N1 framing: a start and a stop bit per byte
+        let capacity = crate::BAUD_RATE as f32 / 10.0;
+
+        write!(
+            f,
+            "Loopback test {}, {:.0} bytes/s ({:.0}% of {} baud), {:.2} ms round trip",
+            self.report,
+            self.throughput,
+            100.0 * self.throughput / capacity,
+            crate::BAUD_RATE,
+            self.latency.as_secs_f32() * 1000.0
+        )
+    }
+}
+
+/// Validates the host's serial path to `port_name` without any device firmware, by sending the
+/// pattern from `seed` through a loopback plug
+///
+/// Latency is measured first over [`crate::LOOPBACK_PINGS`] single words, then throughput and
+/// integrity as in [`run`]
+///
+/// # Errors
+/// Fails if the port can't be opened, or nothing is echoed back
+pub fn loopback(port_name: &str, seed: u32) -> io::Result<Loopback> {
+    // Which may be the port kept open by the last run
+    Link::close_idle();
+    let lock = PortLock::acquire(port_name)?;
+    let serial = serialport::new(port_name, crate::BAUD_RATE)
+        .timeout(Duration::from_millis(crate::SELF_TEST_TIMEOUT))
+        .open_native()?;
+    serial.clear(ClearBuffer::All)?;
+
+    let mut link = Link::serial(serial, lock)?;
+
+    let mut latencies = pattern(seed)
+        .take(crate::LOOPBACK_PINGS)
+        .map(|word| {
+            let start = Instant::now();
+            link.write_all(&word.to_le_bytes())?;
+            link.flush()?;
+
+            let mut echoed = [0u8; mem::size_of::<u32>()];
+            link.read_exact(&mut echoed).map_err(|e| {
+                io::Error::new(e.kind(), format!("no echo, is TX jumpered to RX? ({e})"))
+            })?;
+
+            Ok(start.elapsed())
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    latencies.sort_unstable();
+
+    let (report, elapsed) = echo(&mut link, seed)?;
+    let bytes = (report.received * mem::size_of::<u32>()) as f32;
+
+    let loopback = Loopback {
+        report,
+        throughput: bytes / elapsed.as_secs_f32(),
+        latency: latencies[latencies.len() / 2],
+    };
+    tracing::info!("{loopback}");
+
+    Ok(loopback)
+}
+
+/// Sends [`crate::SELF_TEST_LENGTH`] pattern words from `seed` then the EOT over `link`, in
+/// frames of [`crate::FRAME_SAMPLES`] words, comparing them to what comes back, and timing the
+/// exchange
+fn echo(link: &mut Link, seed: u32) -> io::Result<(Report, Duration)> {
+    let Link { tx, rx } = link;
+
+    let expected: Vec<u32> = pattern(seed).take(crate::SELF_TEST_LENGTH).collect();
+    let mut frames = Framer::new(Vec::new());
+    for words in expected.chunks(crate::FRAME_SAMPLES) {
+        let payload: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        frames.send(&payload)?;
+    }
+    frames.end()?;
+    let bytes = frames.into_inner();
+
+    // Sent while reading, so that the echo can't fill up the device's transmit buffer
+    let start = Instant::now();
+    let echoed = thread::scope(|scope| {
+        let transmitter = scope.spawn(move || tx.write_all(&bytes).and_then(|()| tx.flush()));
+        let echoed = workers::collect(rx, Duration::from_millis(crate::SELF_TEST_TIMEOUT));
+
+        transmitter.join().expect("transmitter joined")?;
+        echoed
+    })?;
+    let elapsed = start.elapsed();
+
+    let (corrupted, bit_errors) = expected
+        .iter()
+        .zip(&echoed)
+        .map(|(expected, echoed)| expected ^ echoed.to_bits())
+        .filter(|&flipped| flipped != 0)
+        .fold((0, 0), |(corrupted, bit_errors), flipped| {
+            (corrupted + 1, bit_errors + u64::from(flipped.count_ones()))
+        });
+
+    let report = Report {
+        sent: expected.len(),
+        received: echoed.len(),
+        corrupted,
+        bit_errors,
+    };
+
+    Ok((report, elapsed))
+}
@@ -0,0 +1,146 @@
+use std::{
+    cell::UnsafeCell,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::storage::{Extent, Samples};
+
+/// Set on [`Shared::latest`] once published and not yet taken
+const FRESH: u8 = 0b100;
+
+/// Triple buffer of extents of the same samples, each buffer only ever accessed by one end at
+/// a time
+struct Shared {
+    buffers: [UnsafeCell<Extent>; 3],
+    /// Index of the buffer last published, along with [`FRESH`]
+    latest: AtomicU8,
+    /// Publications replaced before they were taken
+    dropped: AtomicUsize,
+}
+
+// The publisher only touches its back buffer, and snapshots their front one
+unsafe impl Sync for Shared {}
+
+impl Shared {
+    fn buffer(&self, index: u8) -> *mut Extent {
+        self.buffers[usize::from(index & !FRESH)].get()
+    }
+}
+
+/// Creates a triple buffer, from which snapshots of samples published as they're received are
+/// taken without locking
+///
+/// Only extents are buffered, which share the samples rather than copying them
+pub fn channel() -> (Publisher, Snapshot) {
+    let shared = Arc::new(Shared {
+        buffers: [(); 3].map(|()| UnsafeCell::new(Extent::default())),
+        latest: AtomicU8::new(1),
+        dropped: AtomicUsize::new(0),
+    });
+
+    (
+        Publisher {
+            shared: Arc::clone(&shared),
+            back: 2,
+            published: None,
+        },
+        Snapshot { shared, front: 0 },
+    )
+}
+
+/// Publishing end of a triple buffer
+///
+/// Publishes at most once per frame drawn, as more would only be dropped. Publications the
+/// snapshot doesn't take in time are dropped and counted, as they only ever hold samples kept
+/// elsewhere, rather than holding back the publisher
+pub struct Publisher {
+    shared: Arc<Shared>,
+    back: u8,
+    /// When samples were last published, if they were
+    published: Option<Instant>,
+}
+
+impl Publisher {
+    /// Publishes `samples`, unless they were published less than a frame ago
+    pub fn publish(&mut self, samples: &Samples) {
+        const FRAME: Duration = Duration::from_micros(1_000_000 / crate::FPS);
+
+        if self
+            .published
+            .map_or(true, |published| published.elapsed() >= FRAME)
+        {
+            self.force(samples.extent());
+        }
+    }
+
+    /// Publishes `samples` for the last time
+    pub fn finish(mut self, samples: &Samples) {
+        self.force(samples.extent());
+    }
+
+    fn force(&mut self, extent: Extent) {
+        // SAFETY: the back buffer is only ever accessed here
+        unsafe { *self.shared.buffer(self.back) = extent };
+
+        let previous = self.shared.latest.swap(self.back | FRESH, Ordering::AcqRel);
+        if previous & FRESH != 0 {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.back = previous & !FRESH;
+        self.published = Some(Instant::now());
+    }
+}
+
+/// Samples as last published, once taken by [`Self::refresh`]
+pub struct Snapshot {
+    shared: Arc<Shared>,
+    front: u8,
+}
+
+impl Snapshot {
+    /// Snapshot of samples that won't grow anymore
+    pub fn complete(samples: &Samples) -> Self {
+        Self::of(samples.extent())
+    }
+
+    /// Snapshot of the samples last taken, which isn't refreshed anymore
+    pub fn frozen(&self) -> Self {
+        // SAFETY: the front buffer is only ever accessed by this end
+        Self::of(unsafe { (*self.shared.buffer(self.front)).clone() })
+    }
+
+    fn of(extent: Extent) -> Self {
+        let (mut publisher, mut snapshot) = channel();
+        publisher.force(extent);
+        snapshot.refresh();
+
+        snapshot
+    }
+
+    /// Takes the samples last published, if they weren't already
+    pub fn refresh(&mut self) {
+        if self.shared.latest.load(Ordering::Relaxed) & FRESH != 0 {
+            self.front = self.shared.latest.swap(self.front, Ordering::AcqRel) & !FRESH;
+        }
+    }
+
+    /// Number of publications dropped, as they weren't taken before the next one
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Deref for Snapshot {
+    type Target = [f32];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the front buffer is only ever accessed here, and only changes on refresh
+        unsafe { &*self.shared.buffer(self.front) }
+    }
+}
@@ -0,0 +1,440 @@
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    super::profile::Profile,
+    connect,
+    framing::{Deframer, Framer, Protocol},
+    link::{Link, Source},
+    workers, Announcement, Target, Transmission,
+};
+
+/// Departure from the reference period during a soak test
+#[derive(Debug, Clone, Copy)]
+pub struct Deviation {
+    /// Since the start of the test
+    pub at: Duration,
+    pub fault: Fault,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Fault {
+    /// A period of outputs differed from the reference
+    Mismatch {
+        /// Index of the period, from the reference
+        period: usize,
+        /// Largest absolute difference
+        error: f32,
+    },
+    /// Frames went missing, after which outputs are realigned with the reference
+    Lost { frames: usize },
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", clock(self.at))?;
+
+        match self.fault {
+            Fault::Mismatch { period, error } => write!(f, "period {period} off by {error:.3e}"),
+            Fault::Lost { frames } => write!(f, "{frames} frames lost"),
+        }
+    }
+}
+
+/// Soak test in progress, shared with the interface
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// Set to end the test early
+    stopped: AtomicBool,
+    /// Number of periods compared to the reference
+    verified: AtomicUsize,
+    /// Oldest first
+    deviations: Mutex<Vec<Deviation>>,
+}
+
+impl Progress {
+    /// Ends the test once the device has processed what was sent
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Number of periods compared to the reference
+    pub fn verified(&self) -> usize {
+        self.verified.load(Ordering::Relaxed)
+    }
+
+    /// Number of deviations, and the latest one
+    pub fn deviations(&self) -> (usize, Option<Deviation>) {
+        let deviations = crate::perf::lock(&self.deviations);
+        (deviations.len(), deviations.last().copied())
+    }
+
+    fn record(&self, deviation: Deviation) {
+        tracing::warn!("Soak test deviation at {deviation}");
+        crate::perf::lock(&self.deviations).push(deviation);
+    }
+}
+
+/// Outcome of a soak test
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub elapsed: Duration,
+    /// Number of periods compared to the reference
+    pub verified: usize,
+    pub deviations: usize,
+    /// Largest absolute difference of any period from the reference
+    pub worst: f32,
+}
+
+impl Summary {
+    pub const fn passed(&self) -> bool {
+        self.verified > 0 && self.deviations == 0
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            elapsed,
+            verified,
+            deviations,
+            worst,
+        } = self;
+
+        if self.passed() {
+            write!(f, "passed: ")?;
+        } else {
+            write!(f, "failed: {deviations} deviations, ")?;
+        }
+
+        write!(
+            f,
+            "{verified} periods verified over {}, largest error {worst:.1e}",
+            clock(*elapsed)
+        )
+    }
+}
+
+/// Streams a pattern from `seed` to `target` at its sampling frequency for `duration`, or until
+/// stopped through `progress`, verifying each period of outputs against a reference
+///
+/// The pattern repeats every [`crate::SOAK_PERIOD`] samples, so that once the filter has
+/// settled, over the first period, its outputs repeat too. The second period is the reference,
+/// which later ones must match within [`crate::SOAK_TOLERANCE`] of the full scale, so devices
+/// adding noise deviate. After frames are lost, outputs are realigned with the reference
+///
+/// # Errors
+/// Fails if the device can't be reached, doesn't output a sample per input, or stops
+/// responding for longer than the `transmission`'s stall limit
+pub fn run(
+    target: Target,
+    profile: &Profile,
+    seed: u32,
+    transmission: Transmission,
+    duration: Duration,
+    progress: &Progress,
+) -> io::Result<Summary> {
+    let (
+        Announcement {
+            input,
+            output,
+            block,
+            channels,
+        },
+        link,
+    ) = connect(
+        target,
+        profile,
+        seed,
+        transmission.handshakes,
+        false,
+        Protocol::Binary,
+        &AtomicU16::new(0),
+    )?;
+
+    if output != input || block.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "soak tests require a device outputting a sample per input",
+        ));
+    }
+
+    let Link { tx, rx } = link;
+    let pattern = pattern(seed, profile.full_scale / 2.0);
+    let verifier = Verifier {
+        tolerance: crate::SOAK_TOLERANCE * profile.full_scale,
+        channels: channels as usize,
+        stall: transmission.stall,
+        start: Instant::now(),
+        progress,
+    };
+    let deadline = verifier.start + duration;
+
+    tracing::info!("Soak test started for {}", clock(duration));
+
+    let summary = thread::scope(|scope| {
+        let transmitter = scope.spawn(move || transmit(tx, &pattern, input, deadline, progress));
+        let summary = verifier.run(rx);
+
+        // Stopped early by a failure
+        progress.stop();
+        transmitter.join().expect("transmitter joined")?;
+        summary
+    })?;
+
+    tracing::info!("Soak test {summary}");
+    Ok(summary)
+}
+
+/// [`crate::SOAK_PERIOD`] samples from `seed`, uniformly distributed within `amplitude`
+fn pattern(seed: u32, amplitude: f32) -> Vec<f32> {
+    let mut state = seed.max(1);
+
+    std::iter::repeat_with(|| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+
+        amplitude * ((state >> 8) as f32 / (1u32 << 23) as f32 - 1.0)
+    })
+    .take(crate::SOAK_PERIOD)
+    .collect()
+}
+
+/// Sends `pattern` over and over at `rate` samples per second until `deadline`, or until
+/// stopped, then the EOT
+fn transmit(
+    tx: Box<dyn Write + Send>,
+    pattern: &[f32],
+    rate: u32,
+    deadline: Instant,
+    progress: &Progress,
+) -> io::Result<()> {
+    let mut frames = Framer::new(tx);
+    let interval = Duration::from_secs_f64(crate::FRAME_SAMPLES as f64 / f64::from(rate));
+    let mut next = Instant::now();
+
+    for samples in pattern.chunks(crate::FRAME_SAMPLES).cycle() {
+        if progress.is_stopped() || Instant::now() >= deadline {
+            break;
+        }
+
+        let payload: Vec<u8> = samples.iter().flat_map(|x| x.to_le_bytes()).collect();
+        frames.send(&payload)?;
+
+        next += interval;
+        thread::sleep(next.saturating_duration_since(Instant::now()));
+    }
+
+    frames.end()
+}
+
+/// Stage of the comparison of outputs to the reference period
+enum Stage {
+    /// Discarding outputs until the filter has settled
+    Settling,
+    Verifying {
+        reference: Vec<f32>,
+        /// Index of the next period
+        period: usize,
+    },
+    /// Searching for the reference's start, after frames were lost
+    Realigning { reference: Vec<f32>, period: usize },
+}
+
+struct Verifier<'a> {
+    /// Largest accepted absolute difference from the reference
+    tolerance: f32,
+    /// Number of interleaved output channels, the first of which is verified
+    channels: usize,
+    /// Longest time without data before the device is deemed dead
+    stall: Duration,
+    start: Instant,
+    progress: &'a Progress,
+}
+
+impl Verifier<'_> {
+    /// Verifies the outputs read from `rx` until the EOT
+    fn run(&self, mut rx: Box<dyn Source>) -> io::Result<Summary> {
+        let mut buffer = [0u8; crate::RX_BUFFER_SIZE];
+        let mut deframer = Deframer::default();
+        let mut lost = 0;
+        // Outputs of the first channel not yet verified
+        let mut outputs = Vec::new();
+        let mut stage = Stage::Settling;
+        let mut worst = 0.0f32;
+        let mut last_read = Instant::now();
+        let mut last_status = Instant::now();
+
+        loop {
+            let read = match rx.read(&mut buffer) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "port closed during the soak test",
+                    ))
+                }
+
+                Ok(read) => read,
+
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                    let silence = last_read.elapsed();
+                    if silence >= self.stall {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!(
+                                "device stopped responding at {}",
+                                clock(self.start.elapsed())
+                            ),
+                        ));
+                    }
+
+                    continue;
+                }
+
+                Err(e) => return Err(e),
+            };
+
+            last_read = Instant::now();
+            deframer.push(&buffer[..read]);
+
+            while let Some(payload) = deframer.next_payload() {
+                if payload.is_empty() {
+                    return Ok(Summary {
+                        elapsed: self.start.elapsed(),
+                        verified: self.progress.verified(),
+                        deviations: self.progress.deviations().0,
+                        worst,
+                    });
+                }
+
+                let samples = workers::samples(payload);
+
+                // The outputs before the loss no longer line up with the ones after it
+                if deframer.lost() > lost {
+                    self.progress.record(Deviation {
+                        at: self.start.elapsed(),
+                        fault: Fault::Lost {
+                            frames: deframer.lost() - lost,
+                        },
+                    });
+                    lost = deframer.lost();
+                    outputs.clear();
+
+                    stage = match stage {
+                        Stage::Settling => Stage::Settling,
+                        Stage::Verifying { reference, period }
+                        | Stage::Realigning { reference, period } => {
+                            Stage::Realigning { reference, period }
+                        }
+                    };
+                }
+
+                outputs.extend(samples.iter().step_by(self.channels.max(1)));
+            }
+
+            stage = self.advance(stage, &mut outputs, &mut worst);
+
+            if last_status.elapsed() >= Duration::from_secs(crate::SOAK_STATUS_PERIOD) {
+                last_status = Instant::now();
+                tracing::info!(
+                    "Soak test at {}: {} periods verified, {} deviations",
+                    clock(self.start.elapsed()),
+                    self.progress.verified(),
+                    self.progress.deviations().0
+                );
+            }
+        }
+    }
+
+    /// Consumes the complete periods of `outputs`
+    fn advance(&self, mut stage: Stage, outputs: &mut Vec<f32>, worst: &mut f32) -> Stage {
+        const PERIOD: usize = crate::SOAK_PERIOD;
+
+        loop {
+            stage = match stage {
+                Stage::Settling if outputs.len() >= 2 * PERIOD => {
+                    let reference = outputs[PERIOD..2 * PERIOD].to_vec();
+                    outputs.drain(..2 * PERIOD);
+
+                    tracing::info!("Soak test settled, verifying");
+                    Stage::Verifying {
+                        reference,
+                        period: 1,
+                    }
+                }
+
+                Stage::Verifying { reference, period } if outputs.len() >= PERIOD => {
+                    let error = error(&outputs[..PERIOD], &reference);
+                    outputs.drain(..PERIOD);
+
+                    *worst = worst.max(error);
+                    self.progress.verified.fetch_add(1, Ordering::Relaxed);
+
+                    if error > self.tolerance {
+                        self.progress.record(Deviation {
+                            at: self.start.elapsed(),
+                            fault: Fault::Mismatch { period, error },
+                        });
+                    }
+
+                    Stage::Verifying {
+                        reference,
+                        period: period + 1,
+                    }
+                }
+
+                Stage::Realigning { reference, period } if outputs.len() >= 2 * PERIOD => {
+                    let offset = (0..PERIOD).find(|&offset| {
+                        error(&outputs[offset..offset + PERIOD], &reference) <= self.tolerance
+                    });
+
+                    if let Some(offset) = offset {
+                        tracing::info!("Soak test realigned, skipping {offset} outputs");
+                        outputs.drain(..offset);
+                        Stage::Verifying { reference, period }
+                    } else {
+                        tracing::warn!("Soak test unable to realign, retrying");
+                        outputs.drain(..PERIOD);
+                        Stage::Realigning { reference, period }
+                    }
+                }
+
+                stage => return stage,
+            };
+        }
+    }
+}
+
+/// Largest absolute difference between `outputs` and `reference`, infinite for NaNs
+fn error(outputs: &[f32], reference: &[f32]) -> f32 {
+    outputs
+        .iter()
+        .zip(reference)
+        .map(|(output, reference)| match (output - reference).abs() {
+            error if error.is_nan() => f32::INFINITY,
+            error => error,
+        })
+        .fold(0.0, f32::max)
+}
+
+/// `duration` as hours, minutes and seconds
+pub fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
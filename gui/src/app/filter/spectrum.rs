@@ -0,0 +1,44 @@
+use rustfft::{num_complex::Complex32, FftPlanner};
+use std::f32::consts::PI;
+
+/// Magnitudes below this, in dB, are clamped to it
+const FLOOR: f32 = -120.0;
+
+/// Single-sided magnitude spectrum of `samples` through a Hann window, as `(frequency in Hz,
+/// magnitude in dB)` from DC to the Nyquist frequency
+///
+/// Scaled so that a sinusoid of amplitude 1 peaks at 0 dB. Non-finite samples are treated as
+/// zeros
+pub fn magnitude(samples: &[f32], sampling_interval: f32) -> Vec<(f32, f32)> {
+    let n = samples.len();
+    if n < 2 {
+        return Vec::new();
+    }
+
+    let window: Vec<_> = (0..n)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos())
+        .collect();
+    let gain: f32 = window.iter().sum();
+
+    let mut spectrum: Vec<_> = samples
+        .iter()
+        .zip(&window)
+        .map(|(&x, w)| Complex32::new(if x.is_finite() { x * w } else { 0.0 }, 0.0))
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+
+    let resolution = (n as f32 * sampling_interval).recip();
+    let floor = 10f32.powf(FLOOR / 20.0);
+
+    spectrum[..=n / 2]
+        .iter()
+        .enumerate()
+        .map(|(k, bin)| {
+            // Negative frequencies are folded onto positive ones, except at DC and Nyquist
+            let folded = if k == 0 || 2 * k == n { 1.0 } else { 2.0 };
+            let amplitude = folded * bin.norm() / gain;
+
+            (k as f32 * resolution, 20.0 * amplitude.max(floor).log10())
+        })
+        .collect()
+}
@@ -0,0 +1,185 @@
+use memmap2::MmapMut;
+use std::{fs::File, io, mem, ops::Deref, slice, sync::Arc};
+
+const SAMPLE_SIZE: usize = mem::size_of::<f32>();
+
+/// Samples of a run, only ever appended to
+///
+/// Captures larger than [`crate::MMAP_THRESHOLD`] are appended to a memory-mapped
+/// temporary file instead of the heap, so their size is bounded by disk space rather than RAM.
+/// Samples are stored in a buffer that's replaced rather than moved once full, so that
+/// [`Extent`]s of it are read without locking
+pub struct Samples {
+    buffer: Arc<Buffer>,
+    /// Number of samples written
+    len: usize,
+}
+
+impl Samples {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Arc::new(Buffer::new(capacity.max(1))),
+            len: 0,
+        }
+    }
+
+    /// Appends `sample`, unless the storage couldn't grow to hold it
+    pub fn push(&mut self, sample: f32) -> io::Result<()> {
+        if self.len == self.buffer.capacity {
+            self.buffer = Arc::new(self.buffer.grow(self.len)?);
+        }
+
+        // SAFETY: samples past `len` aren't part of any extent, and are only written here
+        unsafe { self.buffer.ptr.add(self.len).write(sample) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Appends `samples` up to the first that couldn't be stored, if any
+    pub fn append(&mut self, samples: impl IntoIterator<Item = f32>) -> io::Result<()> {
+        samples.into_iter().try_for_each(|sample| self.push(sample))
+    }
+
+    /// Samples written so far, which are never written again
+    pub fn extent(&self) -> Extent {
+        Extent {
+            buffer: Some(Arc::clone(&self.buffer)),
+            len: self.len,
+        }
+    }
+}
+
+/// Moved into a mapped file if larger than [`crate::MMAP_THRESHOLD`], like captures
+impl From<Vec<f32>> for Samples {
+    fn from(samples: Vec<f32>) -> Self {
+        let len = samples.len();
+        if len.saturating_mul(SAMPLE_SIZE) > crate::MMAP_THRESHOLD {
+            let mut mapped = Self::with_capacity(len);
+            mapped
+                .append(samples)
+                .expect("samples stored within capacity");
+            return mapped;
+        }
+
+        let mut samples = samples;
+        samples.resize(len.max(1), 0.0);
+
+        Self {
+            buffer: Arc::new(Buffer::memory(samples)),
+            len,
+        }
+    }
+}
+
+impl Deref for Samples {
+    type Target = [f32];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the first `len` samples were written, and are only read from now on
+        unsafe { slice::from_raw_parts(self.buffer.ptr, self.len) }
+    }
+}
+
+/// Leading samples of [`Samples`], as they were when taken, read without locking
+#[derive(Clone, Default)]
+pub struct Extent {
+    buffer: Option<Arc<Buffer>>,
+    len: usize,
+}
+
+impl Deref for Extent {
+    type Target = [f32];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.buffer {
+            // SAFETY: as for `Samples`, and the buffer is kept alive by the extent
+            Some(buffer) => unsafe { slice::from_raw_parts(buffer.ptr, self.len) },
+            None => &[],
+        }
+    }
+}
+
+/// Fixed capacity storage, which never moves
+struct Buffer {
+    /// First sample of [`Self::storage`], through which samples are accessed
+    ptr: *mut f32,
+    capacity: usize,
+    storage: Storage,
+}
+
+// Samples are only written by the owner of the `Samples`, past those shared as extents
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+
+/// Owner of a [`Buffer`]'s samples
+enum Storage {
+    Memory {
+        _samples: Vec<f32>,
+    },
+    Mapped {
+        /// Anonymous temporary file, shared by the buffers it grew through
+        file: Arc<File>,
+        _map: MmapMut,
+    },
+}
+
+impl Buffer {
+    /// Buffer of `capacity` samples, mapped over a new temporary file if that large, or in
+    /// memory if mapping fails
+    fn new(capacity: usize) -> Self {
+        if capacity.saturating_mul(SAMPLE_SIZE) > crate::MMAP_THRESHOLD {
+            let mapped =
+                tempfile::tempfile().and_then(|file| Self::mapped(Arc::new(file), capacity));
+
+            match mapped {
+                Ok(mapped) => return mapped,
+                Err(e) => tracing::warn!("Unable to map capture file, using memory: {e}"),
+            }
+        }
+
+        Self::memory(vec![0.0; capacity])
+    }
+
+    fn memory(mut samples: Vec<f32>) -> Self {
+        Self {
+            ptr: samples.as_mut_ptr(),
+            capacity: samples.len(),
+            storage: Storage::Memory { _samples: samples },
+        }
+    }
+
+    fn mapped(file: Arc<File>, capacity: usize) -> io::Result<Self> {
+        file.set_len((capacity * SAMPLE_SIZE) as u64)?;
+
+        // SAFETY: the file is private to this process and only ever accessed through its maps,
+        // which are only written past the samples they share
+        let mut map = unsafe { MmapMut::map_mut(&file) }?;
+
+        Ok(Self {
+            ptr: map.as_mut_ptr().cast(),
+            capacity,
+            storage: Storage::Mapped { file, _map: map },
+        })
+    }
+
+    /// Buffer of twice the capacity, holding the first `len` samples
+    ///
+    /// Mapped buffers are remapped over the same file, which already holds them, failing if
+    /// the file can't grow, as when the disk is full
+    fn grow(&self, len: usize) -> io::Result<Self> {
+        let capacity = self.capacity * 2;
+
+        match &self.storage {
+            Storage::Mapped { file, .. } => Self::mapped(Arc::clone(file), capacity),
+
+            Storage::Memory { .. } => {
+                let buffer = Self::new(capacity);
+
+                // SAFETY: the new buffer isn't shared yet
+                unsafe { buffer.ptr.copy_from_nonoverlapping(self.ptr, len) };
+                Ok(buffer)
+            }
+        }
+    }
+}
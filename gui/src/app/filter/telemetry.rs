@@ -0,0 +1,163 @@
+use iced::{
+    widget::{column, text},
+    Element, Length,
+};
+use parking_lot::Mutex;
+use plotters::prelude::*;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::super::theme;
+
+/// Internal state reported by the device alongside its outputs
+///
+/// Reports are framed as [`crate::TELEMETRY`], then the channel as a `u32`, then the value as
+/// an `f32`, all little-endian and in place of a sample
+#[derive(Debug, Default)]
+pub struct Telemetry {
+    /// Per channel, values with the number of outputs received before them
+    channels: BTreeMap<u32, Vec<(usize, f32)>>,
+}
+
+/// [`Telemetry`] shared with the receiver
+pub type Shared = Arc<Mutex<Telemetry>>;
+
+impl Telemetry {
+    pub fn record(&mut self, channel: u32, index: usize, value: f32) {
+        self.channels
+            .entry(channel)
+            .or_default()
+            .push((index, value));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+/// Plots each telemetry channel against time, if any was reported, below the number of
+/// display updates `dropped` by the chart, if any were
+///
+/// The time hovered on the chart is marked by a `crosshair`
+pub fn view<'a, M: 'a>(
+    telemetry: &Shared,
+    dropped: usize,
+    sampling_interval: f32,
+    crosshair: Option<f32>,
+) -> Option<Element<'a, M>> {
+    let reported = !crate::perf::lock(telemetry).is_empty();
+    if !reported && dropped == 0 {
+        return None;
+    }
+
+    let mut panel = column![].spacing(5);
+    if dropped > 0 {
+        panel = panel.push(
+            text(format!(
+                "{dropped} display updates dropped while drawing, no outputs lost"
+            ))
+            .size(14),
+        );
+    }
+
+    if reported {
+        panel = panel.push(
+            ChartWidget::new(Plot {
+                telemetry: Arc::clone(telemetry),
+                sampling_interval,
+                crosshair,
+            })
+            .width(Length::Fill)
+            .height(Length::Fill),
+        );
+    }
+
+    Some(panel.into())
+}
+
+struct Plot {
+    telemetry: Shared,
+    sampling_interval: f32,
+    /// Time hovered on the chart
+    crosshair: Option<f32>,
+}
+
+impl<M> Chart<M> for Plot {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        _state: &Self::State,
+        mut builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let colors = theme::colors();
+        let telemetry = crate::perf::lock(&self.telemetry);
+        let points = telemetry.channels.values().flatten();
+
+        let duration = points
+            .clone()
+            .map(|&(index, _)| index as f32 * self.sampling_interval)
+            .fold(self.sampling_interval, f32::max);
+        let (low, high) = points.filter(|(_, value)| value.is_finite()).fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(low, high), &(_, value)| (low.min(value), high.max(value)),
+        );
+
+        if low > high {
+            return;
+        }
+
+        let margin = ((high - low) * 0.1).max(f32::EPSILON);
+        let (low, high) = (low - margin, high + margin);
+
+        let mut chart = builder
+            .x_label_area_size(24)
+            .y_label_area_size(48)
+            .margin(10)
+            .build_cartesian_2d(0f32..duration, low..high)
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(colors.foreground)
+            .label_style(("sans-serif", 18).into_font().color(&colors.foreground))
+            .x_desc("s")
+            .light_line_style(colors.foreground.mix(0.10))
+            .bold_line_style(colors.foreground.mix(0.30))
+            .draw()
+            .expect("drawn mesh");
+
+        for (i, (channel, values)) in telemetry.channels.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            let points = values
+                .iter()
+                .map(|&(index, value)| (index as f32 * self.sampling_interval, value));
+
+            chart
+                .draw_series(LineSeries::new(points, color.stroke_width(2)))
+                .expect("drawn telemetry")
+                .label(format!("Channel {channel}"))
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+
+        if let Some(t) = self.crosshair.filter(|t| (0.0..=duration).contains(t)) {
+            chart
+                .draw_series([PathElement::new(
+                    vec![(t, low), (t, high)],
+                    colors.foreground.mix(0.6),
+                )])
+                .expect("drawn crosshair");
+        }
+
+        chart
+            .configure_series_labels()
+            .border_style(colors.foreground)
+            .label_font(("sans-serif", 18).into_font().color(&colors.foreground))
+            .background_style(colors.background)
+            .position(SeriesLabelPosition::UpperRight)
+            .draw()
+            .expect("drawn legend");
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    super::signal::{self, native, Overrange, Parameters, TimeBase},
+    framing::{Deframer, Framer, Protocol},
+    workers::Supervisor,
+};
+
+/// Largest relative deviation of generated inputs, as numpy's transcendental functions may
+/// differ by a few ULPs between platforms
+const TOLERANCE: f32 = 1e-6;
+
+/// Expected time and input vectors of a function
+///
+/// Follows numpy's `arange` semantics: `ceil(stop_time / interval)` samples at `i * interval`,
+/// evaluated in double precision and stored as `f32`
+#[derive(Deserialize)]
+struct Golden {
+    function: String,
+    stop_time: f32,
+    sampling_frequency: u32,
+    time: Vec<f32>,
+    input: Vec<f32>,
+}
+
+fn check(json: &str) {
+    pyo3::prepare_freethreaded_python();
+
+    let golden: Golden = serde_json::from_str(json).expect("valid golden vectors");
+    let parameters = Parameters {
+        function: golden.function.clone(),
+        preset: None,
+        stop_time: golden.stop_time,
+        samples: None,
+        seed: 0,
+        overrange: Overrange::Warn,
+        normalization: None,
+        time_base: TimeBase::default(),
+    };
+    let sampling_interval = (golden.sampling_frequency as f32).recip();
+
+    // The built-in evaluator must agree with numpy
+    for (evaluator, tensors) in [
+        ("numpy", signal::evaluate(&parameters, sampling_interval)),
+        ("built-in", native::evaluate(&parameters, sampling_interval)),
+    ] {
+        let (time, input) = tensors.expect("vectors");
+
+        // The time base must match exactly
+        assert_eq!(
+            time, golden.time,
+            "{evaluator} time vector of {}",
+            golden.function
+        );
+        assert_eq!(input.len(), golden.input.len());
+
+        for (i, (actual, expected)) in input.iter().zip(&golden.input).enumerate() {
+            assert!(
+                (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.0),
+                "{evaluator} sample {i} of {} is {actual}, expected {expected}",
+                golden.function
+            );
+        }
+    }
+}
+
+macro_rules! golden {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check(include_str!(concat!("golden/", stringify!($name), ".json")));
+            }
+        )*
+    };
+}
+
+golden!(
+    sine_1000hz,
+    polynomial_250hz,
+    sine_44100hz,
+    fractional_stop_time,
+    stop_time_below_interval,
+    sine_7hz_stop_not_multiple,
+);
+
+#[test]
+fn shutdown_wakes_sleeping_workers() {
+    let mut supervisor = Supervisor::default();
+    for name in ["transmitter", "publisher"] {
+        supervisor.spawn(
+            name,
+            |shutdown| while shutdown.sleep(Duration::from_secs(60)) {},
+        );
+    }
+
+    let start = Instant::now();
+    supervisor.shutdown();
+
+    assert!(!supervisor.is_running());
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn shutdown_survives_panicking_workers() {
+    let mut supervisor = Supervisor::default();
+    supervisor.spawn("panicking", |_| panic!("worker failed"));
+    supervisor.spawn("receiver", |_| {});
+
+    while !supervisor.has_ended("receiver") {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    supervisor.shutdown();
+    assert!(!supervisor.is_running());
+}
+
+#[test]
+fn deframer_skips_corrupted_frames() {
+    let mut framer = Framer::new(Vec::new());
+    for i in 0..4 {
+        framer.send(&[i; 8]).expect("frame sent");
+    }
+    framer.end().expect("EOT sent");
+
+    // Garbage ahead of the first frame, and a flipped bit in the second one's payload
+    let mut bytes = vec![0xA5, 0x00, 0x5A];
+    bytes.extend(framer.into_inner());
+    bytes[3 + 16 + 8] ^= 0x10;
+
+    let mut deframer = Deframer::default();
+    deframer.push(&bytes);
+    let payloads: Vec<_> =
+        std::iter::from_fn(|| deframer.next_payload().map(<[u8]>::to_vec)).collect();
+
+    assert_eq!(payloads, [vec![0; 8], vec![2; 8], vec![3; 8], vec![]]);
+    assert_eq!(deframer.lost(), 1);
+}
+
+#[test]
+fn ascii_lines_round_trip() {
+    fn bytes(samples: &[f32]) -> Vec<u8> {
+        samples.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    let mut framer = Framer::with_protocol(Vec::new(), Protocol::Ascii);
+    framer.send(&bytes(&[0.5, -1.25])).expect("lines sent");
+    framer.end().expect("EOT sent");
+    assert_eq!(framer.into_inner(), b"0.5\n-1.25\n\n");
+
+    // A banner, line endings of both kinds, and several samples on one line
+    let mut deframer = Deframer::with_protocol(Protocol::Ascii);
+    deframer.push(b"Ready!\r\n0.5\r\n1, 2 3\n\r\n4\n");
+    let payloads: Vec<_> =
+        std::iter::from_fn(|| deframer.next_payload().map(<[u8]>::to_vec)).collect();
+
+    assert_eq!(
+        payloads,
+        [
+            bytes(&[0.5]),
+            bytes(&[1.0, 2.0, 3.0]),
+            vec![],
+            bytes(&[4.0])
+        ]
+    );
+    assert_eq!(deframer.lost(), 1);
+}
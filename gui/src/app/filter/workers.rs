@@ -1,70 +1,1103 @@
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::{
-    io::{Read, Write},
+    fmt,
+    io::{self, Read, Write},
+    mem,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError, TryRecvError},
         Arc,
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-use super::Serial;
+use super::{
+    clock::{self, Exchange},
+    framing::{Deframer, Framer, Protocol},
+    inspector,
+    link::Source,
+    log::{self, Entry, Severity},
+    queue::{self, Consumer, Producer},
+    snapshot::{self, Publisher, Snapshot},
+    storage::Samples,
+    telemetry,
+};
 
-pub fn spawn_transmitter(
-    serial: Serial,
-    data: Arc<Vec<f32>>,
-    token: Arc<AtomicBool>,
-) -> JoinHandle<()> {
-    thread::spawn(move || transmitter(serial, data.as_ref(), token.as_ref()))
-}
-
-pub fn spawn_receiver(serial: Serial, capacity: usize) -> (Arc<Mutex<Vec<f32>>>, JoinHandle<()>) {
-    let output = Arc::new(Mutex::new(Vec::with_capacity(capacity)));
-    let handle = {
-        let output = Arc::clone(&output);
-        thread::spawn(move || {
-            receiver(serial, output.as_ref());
+/// How the input is transmitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transmission {
+    /// How long to receive for before the first sample is sent
+    pub pre_trigger: Duration,
+    /// Pause after each [`Self::chunk`], for devices that can't keep up with back-to-back
+    /// samples. Independent of flow control
+    pub gap: Duration,
+    /// Number of samples sent between gaps
+    pub chunk: u16,
+    /// Number of times the input is sent back to back
+    pub repeat: u16,
+    /// Exchange time synchronization frames with the device, which must support them
+    pub sync: bool,
+    /// Number of timed out reads retried over the run, for devices slow to produce some
+    /// outputs, before the device is deemed dead
+    pub retries: u16,
+    /// Longest time without data before the device is deemed dead, however many retries are
+    /// left
+    pub stall: Duration,
+    /// How samples are carried, as the device expects
+    pub protocol: Protocol,
+    /// Number of times [`crate::SYN`] is sent to a device that doesn't answer, such as one still
+    /// booting, before the connection fails
+    pub handshakes: u16,
+    /// Keep the connection open once the run ends, for the next run on the same device to
+    /// skip reopening its port, which resets some boards
+    pub keep_open: bool,
+    /// Set from the handshake for block-based devices, each block being sent once the previous
+    /// one was processed
+    #[serde(skip)]
+    pub block: Option<Block>,
+}
+
+impl Default for Transmission {
+    fn default() -> Self {
+        Self {
+            pre_trigger: Duration::ZERO,
+            gap: Duration::ZERO,
+            chunk: 1,
+            repeat: 1,
+            sync: false,
+            retries: 50,
+            stall: Duration::from_secs(2),
+            protocol: Protocol::Binary,
+            handshakes: 5,
+            keep_open: false,
+            block: None,
+        }
+    }
+}
+
+/// How reception ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    /// The device ended its outputs with an EOT, an empty frame
+    Completed {
+        /// Reads that timed out and were retried, from a slow device
+        timeouts: u32,
+        /// Frames corrupted or lost along the way
+        lost: usize,
+    },
+    /// Nothing was received for longer than [`Transmission::stall`]
+    Stalled {
+        /// Outputs received before
+        received: usize,
+    },
+    /// More than [`Transmission::retries`] reads timed out
+    Exhausted {
+        /// Outputs received before
+        received: usize,
+    },
+    /// The port was closed, or failed
+    Disconnected,
+    /// Shut down before the device's EOT, which wasn't received within
+    /// [`crate::EOT_DRAIN_TIMEOUT`]
+    Interrupted {
+        /// Outputs received before
+        received: usize,
+    },
+    /// Outputs couldn't be stored, as when the disk holding the capture is full
+    Unstored {
+        /// Outputs stored before
+        received: usize,
+        error: io::ErrorKind,
+    },
+}
+
+impl Ending {
+    /// Kind of error cutting the run short, if the device died or its outputs couldn't be stored
+    pub const fn error(self) -> Option<io::ErrorKind> {
+        match self {
+            Self::Stalled { .. } | Self::Exhausted { .. } => Some(io::ErrorKind::TimedOut),
+            Self::Unstored { error, .. } => Some(error),
+            Self::Completed { .. } | Self::Disconnected | Self::Interrupted { .. } => None,
+        }
+    }
+
+    /// Same ending, after `received` outputs
+    const fn counting(self, received: usize) -> Self {
+        match self {
+            Self::Stalled { .. } => Self::Stalled { received },
+            Self::Exhausted { .. } => Self::Exhausted { received },
+            ending => ending,
+        }
+    }
+}
+
+impl fmt::Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Completed { timeouts, lost } => {
+                f.write_str("Completed")?;
+
+                if *timeouts > 0 {
+                    write!(f, " by a slow device, after {timeouts} read timeouts")?;
+                }
+
+                if *lost > 0 {
+                    write!(f, ", {lost} corrupted or lost frames missing")?;
+                }
+
+                Ok(())
+            }
+            Self::Stalled { received } => write!(
+                f,
+                "Device stopped responding after {received} samples: silent for too long"
+            ),
+            Self::Exhausted { received } => write!(
+                f,
+                "Device stopped responding after {received} samples: too many read timeouts"
+            ),
+            Self::Disconnected => f.write_str("Disconnected"),
+            Self::Interrupted { received } => write!(
+                f,
+                "Interrupted after {received} samples, before the device's EOT"
+            ),
+            Self::Unstored { received, error } => write!(
+                f,
+                "Unable to store outputs after {received} samples: {error}"
+            ),
+        }
+    }
+}
+
+/// Block size of a block-based device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Inputs sent at once
+    pub input: usize,
+    /// Outputs received for each block of inputs
+    pub output: usize,
+}
+
+impl Block {
+    /// Block of `size` inputs, for a device with the given input and output sampling frequencies
+    pub fn new(size: u32, input_rate: u32, output_rate: u32) -> Self {
+        let output = u64::from(size) * u64::from(output_rate) / u64::from(input_rate);
+
+        Self {
+            input: usize::try_from(size).expect("block fits in memory"),
+            output: usize::try_from(output).expect("block fits in memory"),
+        }
+    }
+}
+
+/// Pause state of a run, shared with the transmitter
+#[derive(Debug, Default)]
+pub struct Control(AtomicBool);
+
+impl Control {
+    /// Suspends transmission before the next sample
+    pub fn pause(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes transmission from the sample it was suspended at
+    pub fn resume(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Blocks while paused, returning for how long, or [`None`] once shutdown is ordered
+    fn hold(&self, shutdown: &Shutdown) -> Option<Duration> {
+        const POLL: Duration = Duration::from_millis(10);
+
+        let start = Instant::now();
+        while self.is_paused() {
+            if !shutdown.sleep(POLL) {
+                return None;
+            }
+        }
+
+        (!shutdown.is_ordered()).then(|| start.elapsed())
+    }
+}
+
+/// Worker's end of its shutdown channel, over which nothing is sent: shutdown is ordered by
+/// its [`Supervisor`] dropping the other end
+pub struct Shutdown(mpsc::Receiver<()>);
+
+impl Shutdown {
+    pub fn is_ordered(&self) -> bool {
+        matches!(self.0.try_recv(), Err(TryRecvError::Disconnected))
+    }
+
+    /// Sleeps for `duration`, returning early with `false` once shutdown is ordered
+    pub fn sleep(&self, duration: Duration) -> bool {
+        matches!(
+            self.0.recv_timeout(duration),
+            Err(RecvTimeoutError::Timeout)
+        )
+    }
+}
+
+/// Worker threads of a run, all torn down by [`Self::shutdown`], which also runs when dropped
+#[derive(Default)]
+pub struct Supervisor {
+    workers: Vec<Worker>,
+}
+
+struct Worker {
+    name: &'static str,
+    /// Dropped to order shutdown
+    shutdown: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl Supervisor {
+    /// Spawns `worker` on a thread named `name`, handing it its end of a shutdown channel
+    pub fn spawn(&mut self, name: &'static str, worker: impl FnOnce(Shutdown) + Send + 'static) {
+        let (shutdown, receiver) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || worker(Shutdown(receiver)))
+            .expect("spawned worker thread");
+
+        self.workers.push(Worker {
+            name,
+            shutdown,
+            handle,
+        });
+    }
+
+    /// Are workers left to shut down?
+    pub fn is_running(&self) -> bool {
+        !self.workers.is_empty()
+    }
+
+    /// Has the worker named `name` ended by itself?
+    pub fn has_ended(&self, name: &str) -> bool {
+        self.workers
+            .iter()
+            .any(|worker| worker.name == name && worker.handle.is_finished())
+    }
+
+    /// Orders every worker to shut down at once, then waits for all of them to end
+    ///
+    /// Workers depending on each other, like the receiver awaiting the transmitter's EOT, thus
+    /// end in any order. Panics of workers are logged rather than propagated
+    pub fn shutdown(&mut self) {
+        let handles: Vec<_> = self
+            .workers
+            .drain(..)
+            .map(|Worker { name, handle, .. }| (name, handle))
+            .collect();
+
+        for (name, handle) in handles {
+            if handle.join().is_err() {
+                tracing::error!("The {name} thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Transmission progress, shared with the transmitter and receiver
+#[derive(Debug, Default)]
+pub struct Throughput {
+    /// Number of samples sent
+    sent: AtomicUsize,
+    /// Since the first sample was sent, in microseconds
+    elapsed: AtomicU64,
+    /// Number of outputs received since transmission started
+    received: AtomicUsize,
+    /// Number of blocks processed, in block mode
+    blocks: AtomicUsize,
+}
+
+impl Throughput {
+    /// Throughput of a replayed run, which sent and received everything but has no rate
+    pub fn replayed(sent: usize, received: usize) -> Self {
+        Self {
+            sent: AtomicUsize::new(sent),
+            received: AtomicUsize::new(received),
+            ..Self::default()
+        }
+    }
+
+    /// Effective transmission rate, in samples per second
+    pub fn rate(&self) -> Option<f32> {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let elapsed = self.elapsed.load(Ordering::Relaxed);
+
+        (elapsed > 0).then(|| sent as f32 / elapsed as f32 * 1e6)
+    }
+
+    /// Number of samples sent
+    pub fn sent(&self) -> usize {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Number of outputs received since transmission started
+    pub fn received(&self) -> usize {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    /// Number of blocks processed, in block mode
+    pub fn blocks(&self) -> usize {
+        self.blocks.load(Ordering::Relaxed)
+    }
+
+    /// Since the first sample was sent, until the last one was
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed.load(Ordering::Relaxed))
+    }
+
+    /// Estimated time until `expected` outputs are received, at the rate they were so far
+    pub fn remaining(&self, expected: usize) -> Option<Duration> {
+        let received = self.received();
+        let elapsed = self.elapsed.load(Ordering::Relaxed);
+
+        (received > 0 && elapsed > 0).then(|| {
+            Duration::from_micros(elapsed)
+                .mul_f64(expected.saturating_sub(received) as f64 / received as f64)
         })
+    }
+
+    /// Waits until `expected` outputs were received, failing on shutdown or timeout
+    fn await_outputs(&self, expected: usize, shutdown: &Shutdown) -> bool {
+        const POLL: Duration = Duration::from_millis(1);
+
+        let start = Instant::now();
+        while self.received.load(Ordering::Acquire) < expected {
+            if start.elapsed() >= Duration::from_millis(crate::BLOCK_TIMEOUT) {
+                tracing::error!("Timed out awaiting a processed block");
+                return false;
+            }
+
+            if !shutdown.sleep(POLL) {
+                tracing::info!("Shut down while awaiting a processed block");
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn record(&self, sent: usize, elapsed: Duration) {
+        self.sent.fetch_add(sent, Ordering::Relaxed);
+        self.elapsed.store(
+            u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// Outputs received before transmission started, oldest first
+pub type PreTrigger = Arc<Mutex<Vec<f32>>>;
+
+/// Outputs of the channels following the first, by channel, received since transmission started
+pub type Channels = Arc<Mutex<Vec<Vec<f32>>>>;
+
+/// How reception ended, once it has
+pub type Outcome = Arc<Mutex<Option<Ending>>>;
+
+pub fn spawn_transmitter(
+    supervisor: &mut Supervisor,
+    serial: Box<dyn Write + Send>,
+    data: Arc<Samples>,
+    transmission: Transmission,
+    control: Arc<Control>,
+    started: Arc<AtomicBool>,
+    throughput: Arc<Throughput>,
+    epoch: Instant,
+) {
+    supervisor.spawn("transmitter", move |shutdown| {
+        // The device is still sent an EOT when shut down early
+        let samples = if wait(transmission.pre_trigger, &shutdown) {
+            &data[..]
+        } else {
+            &[]
+        };
+
+        started.store(true, Ordering::Release);
+        transmitter(
+            serial,
+            samples,
+            transmission,
+            &control,
+            &shutdown,
+            &throughput,
+            epoch,
+        );
+    });
+}
+
+/// Outputs of a run, filled by the receiver
+pub struct Received {
+    pub output: Arc<Mutex<Samples>>,
+    /// [`Self::output`] as of the last read, for drawing without waiting on the receiver
+    pub snapshot: Snapshot,
+    pub channels: Channels,
+    pub pre_trigger: PreTrigger,
+    pub telemetry: telemetry::Shared,
+    pub log: log::Log,
+    pub clock: clock::Shared,
+    /// Frames decoded last
+    pub inspector: inspector::Shared,
+    pub outcome: Outcome,
+}
+
+/// Spawns the [`RECEIVER`], filing samples under [`PreTrigger`] until transmission has
+/// `started`, and the [`READER`] feeding it
+///
+/// Each output is a frame of `channels` interleaved samples, the first of which goes to
+/// [`Received::output`] and the others to [`Received::channels`]. The receiver ends once the
+/// device stops sending, after the transmitter's EOT, or is deemed dead as set by
+/// `transmission`, silence being expected while the transmitter's `control` is paused. On
+/// shutdown, it only keeps draining outputs in flight for [`crate::EOT_DRAIN_TIMEOUT`]. The
+/// reader then ends with it, closing the link
+pub fn spawn_receiver(
+    supervisor: &mut Supervisor,
+    serial: Box<dyn Source>,
+    capacity: usize,
+    channels: usize,
+    transmission: Transmission,
+    control: Arc<Control>,
+    started: Arc<AtomicBool>,
+    throughput: Arc<Throughput>,
+    epoch: Instant,
+) -> Received {
+    let (publisher, snapshot) = snapshot::channel();
+    let received = Received {
+        output: Arc::new(Mutex::new(Samples::with_capacity(capacity))),
+        snapshot,
+        channels: Arc::new(Mutex::new(vec![Vec::new(); channels.max(1) - 1])),
+        pre_trigger: PreTrigger::default(),
+        telemetry: telemetry::Shared::default(),
+        log: log::Log::default(),
+        clock: clock::Shared::default(),
+        inspector: inspector::Shared::default(),
+        outcome: Outcome::default(),
     };
+    {
+        let output = Arc::clone(&received.output);
+        let channels = Arc::clone(&received.channels);
+        let pre_trigger = Arc::clone(&received.pre_trigger);
+        let sideband = Sideband {
+            telemetry: Arc::clone(&received.telemetry),
+            log: Arc::clone(&received.log),
+            clock: Arc::clone(&received.clock),
+            inspector: Arc::clone(&received.inspector),
+        };
+        let outcome = Arc::clone(&received.outcome);
+        let (producer, consumer) = queue::channel(crate::RX_QUEUE_LENGTH, crate::RX_BUFFER_SIZE);
+        let reading = Arc::new(Reading::default());
+
+        {
+            let reading = Arc::clone(&reading);
+            let started = Arc::clone(&started);
+            supervisor.spawn(READER, move |_| {
+                reader(
+                    serial,
+                    producer,
+                    &reading,
+                    transmission,
+                    &control,
+                    &started,
+                    epoch,
+                );
+            });
+        }
+
+        supervisor.spawn(RECEIVER, move |shutdown| {
+            let ending = receiver(
+                consumer,
+                &reading,
+                &output,
+                publisher,
+                &channels,
+                &pre_trigger,
+                &sideband,
+                &started,
+                &throughput,
+                transmission.protocol,
+                &shutdown,
+            );
 
-    (output, handle)
+            *crate::perf::lock(&outcome) = Some(ending);
+        });
+    }
+
+    received
+}
+
+/// Name of the receiver's thread, whose end marks the end of the run
+pub const RECEIVER: &str = "receiver";
+
+/// Name of the thread reading from the device for the [`RECEIVER`]
+pub const READER: &str = "reader";
+
+/// Sleeps for `duration`, returning early with `false` if shutdown is ordered
+fn wait(duration: Duration, shutdown: &Shutdown) -> bool {
+    let elapsed = shutdown.sleep(duration);
+    if !elapsed {
+        tracing::info!("Shut down before transmission");
+    }
+
+    elapsed
 }
 
-fn transmitter(mut serial: Serial, samples: &[f32], token: &AtomicBool) {
-    for sample in samples.iter().copied().map(f32::to_le_bytes) {
-        if token.load(Ordering::Relaxed) {
-            tracing::info!("Ending transmission: cancellation ordered");
-            break;
+fn transmitter(
+    mut serial: Box<dyn Write + Send>,
+    samples: &[f32],
+    transmission: Transmission,
+    control: &Control,
+    shutdown: &Shutdown,
+    throughput: &Throughput,
+    epoch: Instant,
+) {
+    /// Sends the samples of `pending` in one frame, if any
+    fn send(serial: &mut Framer<Box<dyn Write + Send>>, pending: &mut Vec<u8>) -> io::Result<()> {
+        if pending.is_empty() {
+            return Ok(());
         }
 
-        if let Err(e) = serial.write_all(&sample) {
-            tracing::error!("Failed to transmit `{sample:?}`: {e}");
-            break;
+        let sent = serial.send(pending);
+        pending.clear();
+        sent
+    }
+
+    let mut serial = Framer::with_protocol(serial, transmission.protocol);
+    let mut start = Instant::now();
+    let mut next_sync = start;
+    let chunk = transmission
+        .block
+        .map_or(transmission.chunk.max(1).into(), |block| block.input);
+    // Samples are batched into frames, unless sent in chunks
+    let batched = transmission.block.is_none() && transmission.gap.is_zero();
+    let frame_size = crate::FRAME_SAMPLES * mem::size_of::<f32>();
+    let mut pending = Vec::with_capacity(frame_size);
+
+    'transmission: for (i, chunk) in samples.chunks(chunk).enumerate() {
+        // Time synchronization frames can't be written as lines
+        if transmission.sync
+            && transmission.protocol == Protocol::Binary
+            && Instant::now() >= next_sync
+        {
+            next_sync += Duration::from_millis(crate::TIME_SYNC_PERIOD);
+
+            let timestamp = clock::timestamp(epoch).to_le_bytes();
+            if let Err(e) = send(&mut serial, &mut pending)
+                .and_then(|()| serial.send(&[crate::TIME_SYNC, &timestamp].concat()))
+            {
+                tracing::error!("Failed to transmit time synchronization: {e}");
+                break;
+            }
+        }
+
+        for sample in chunk.iter().copied().map(f32::to_le_bytes) {
+            // The device isn't kept waiting for the rest of a frame while paused
+            if control.is_paused() {
+                if let Err(e) = send(&mut serial, &mut pending) {
+                    tracing::error!("Failed to transmit samples: {e}");
+                    break 'transmission;
+                }
+            }
+
+            match control.hold(shutdown) {
+                // Pauses are left out of the throughput and synchronization schedule
+                Some(paused) => {
+                    start += paused;
+                    next_sync += paused;
+                }
+                None => {
+                    tracing::info!("Ending transmission: shutdown ordered");
+                    break 'transmission;
+                }
+            }
+
+            pending.extend_from_slice(&sample);
+            if pending.len() >= frame_size {
+                if let Err(e) = send(&mut serial, &mut pending) {
+                    tracing::error!("Failed to transmit samples: {e}");
+                    break 'transmission;
+                }
+            }
+        }
+
+        if !batched {
+            if let Err(e) = send(&mut serial, &mut pending) {
+                tracing::error!("Failed to transmit samples: {e}");
+                break;
+            }
+        }
+
+        throughput.record(chunk.len(), start.elapsed());
+
+        if let Some(block) = transmission.block {
+            if !throughput.await_outputs((i + 1) * block.output, shutdown) {
+                break;
+            }
+
+            throughput.blocks.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if !transmission.gap.is_zero() {
+            thread::sleep(transmission.gap);
         }
     }
 
-    match serial.write_all(crate::EOT) {
+    match send(&mut serial, &mut pending).and_then(|()| serial.end()) {
         Ok(()) => tracing::info!("Transmission ended"),
         Err(e) => tracing::error!("Failed to complete transmission: {e}"),
     }
 }
 
-fn receiver(mut serial: Serial, output: &Mutex<Vec<f32>>) {
-    let mut buffer = [0u8; std::mem::size_of::<f32>()];
+/// Longest accepted device log message, in bytes. Longer ones are assumed to be corrupted
+const MAX_LOG_LENGTH: u32 = 1024;
 
-    loop {
-        if let Err(e) = serial.read_exact(&mut buffer) {
-            tracing::error!("Failed to read sample: {e}");
-            break;
+/// Data received alongside outputs
+struct Sideband {
+    telemetry: telemetry::Shared,
+    log: log::Log,
+    clock: clock::Shared,
+    inspector: inspector::Shared,
+}
+
+/// Position within a sideband report, or between reports, of a frame's payload
+enum Frame {
+    Sample,
+    /// After [`crate::TELEMETRY`]
+    Channel,
+    Value(u32),
+    /// After [`crate::LOG`]
+    Severity,
+    Length(Severity),
+    Text {
+        severity: Severity,
+        /// Bytes left to read, excluding padding
+        remaining: usize,
+        bytes: Vec<u8>,
+    },
+    /// After [`crate::TIME_SYNC`]
+    Echo,
+    Index(u32),
+}
+
+/// Sideband frame, with the number of samples of its block preceding it
+enum Report {
+    Telemetry {
+        channel: u32,
+        value: f32,
+    },
+    Log {
+        severity: Severity,
+        message: String,
+    },
+    /// Time synchronization reply, of a request sent at `sent`
+    Sync {
+        sent: u32,
+        index: u32,
+    },
+}
+
+impl Frame {
+    /// Frame following `word`, collecting completed samples and reports
+    fn advance(
+        self,
+        word: [u8; 4],
+        samples: &mut Vec<f32>,
+        reports: &mut Vec<(usize, Report)>,
+    ) -> Self {
+        let value = f32::from_le_bytes(word);
+        let bits = u32::from_le_bytes(word);
+
+        match self {
+            Self::Sample if word == crate::TELEMETRY => Self::Channel,
+            Self::Sample if word == crate::LOG => Self::Severity,
+            Self::Sample if word == crate::TIME_SYNC => Self::Echo,
+            Self::Sample => {
+                samples.push(value);
+                Self::Sample
+            }
+
+            Self::Channel => Self::Value(bits),
+            Self::Value(channel) => {
+                reports.push((samples.len(), Report::Telemetry { channel, value }));
+                Self::Sample
+            }
+
+            Self::Echo => Self::Index(bits),
+            Self::Index(sent) => {
+                reports.push((samples.len(), Report::Sync { sent, index: bits }));
+                Self::Sample
+            }
+
+            Self::Severity => Self::Length(Severity::from_word(bits)),
+            Self::Length(_) if bits > MAX_LOG_LENGTH => {
+                tracing::warn!("Dropping device log message of {bits} bytes");
+                Self::Sample
+            }
+            Self::Length(severity) => Self::Text {
+                severity,
+                remaining: bits as usize,
+                bytes: Vec::with_capacity(bits as usize),
+            }
+            .advance_text(samples, reports),
+            Self::Text {
+                severity,
+                remaining,
+                mut bytes,
+            } => {
+                let taken = remaining.min(word.len());
+                bytes.extend_from_slice(&word[..taken]);
+
+                Self::Text {
+                    severity,
+                    remaining: remaining - taken,
+                    bytes,
+                }
+                .advance_text(samples, reports)
+            }
         }
+    }
 
-        if buffer == crate::EOT {
-            tracing::info!("Ending reception: EOT");
-            break;
+    /// Completes a log message once all of its bytes were read
+    fn advance_text(self, samples: &[f32], reports: &mut Vec<(usize, Report)>) -> Self {
+        match self {
+            Self::Text {
+                severity,
+                remaining: 0,
+                bytes,
+            } => {
+                let message = String::from_utf8_lossy(&bytes).into_owned();
+                reports.push((samples.len(), Report::Log { severity, message }));
+                Self::Sample
+            }
+
+            frame => frame,
         }
+    }
+}
+
+/// Collects the samples and sideband reports of a frame's `payload`
+fn parse(payload: &[u8], samples: &mut Vec<f32>, reports: &mut Vec<(usize, Report)>) {
+    let words = payload.chunks_exact(mem::size_of::<f32>());
+    let complete = words.remainder().is_empty();
+
+    let frame = words.fold(Frame::Sample, |frame, word| {
+        frame.advance(word.try_into().expect("word sized chunk"), samples, reports)
+    });
 
-        output.lock().push(f32::from_le_bytes(buffer));
+    if !complete || !matches!(frame, Frame::Sample) {
+        tracing::warn!("Dropping a sideband report cut short by the end of its frame");
     }
+}
+
+/// Samples of a frame's `payload`, discarding its sideband reports
+pub fn samples(payload: &[u8]) -> Vec<f32> {
+    let mut samples = Vec::new();
+    parse(payload, &mut samples, &mut Vec::new());
+    samples
+}
+
+/// How the [`READER`] is faring, shared with the receiver
+#[derive(Default)]
+struct Reading {
+    /// Reads that timed out and were retried
+    timeouts: AtomicU32,
+    /// Why the reader gave up, if it did, set before it ends. Outputs are left uncounted
+    failure: Mutex<Option<Ending>>,
+}
+
+/// Reads from `serial` into `queue`, timestamping reads, until the device fails or stops
+/// responding, or the receiver is gone
+///
+/// Only ever waits on the receiver for room in `queue`, so that the device keeps being read
+/// while the receiver waits on locks
+fn reader(
+    mut serial: Box<dyn Source>,
+    mut queue: Producer,
+    reading: &Reading,
+    transmission: Transmission,
+    control: &Control,
+    started: &AtomicBool,
+    epoch: Instant,
+) {
+    const POLL: Duration = Duration::from_micros(100);
+
+    let mut last_read = Instant::now();
+    let mut full = false;
+
+    let failure = loop {
+        if queue.is_closed() {
+            return;
+        }
+
+        let Some(buffer) = queue.slot() else {
+            if !full {
+                tracing::warn!("Receive queue full, waiting for the receiver");
+                full = true;
+            }
 
-    tracing::info!("Reception ended");
+            thread::sleep(POLL);
+            continue;
+        };
+        full = false;
+
+        let read = match serial.read(buffer) {
+            Ok(0) => {
+                tracing::error!("Failed to read samples: port closed");
+                break Ending::Disconnected;
+            }
+
+            Ok(read) => read,
+
+            // The device has nothing to send before transmission starts, or while it's paused,
+            // so silence is only counted from when it resumes
+            Err(e)
+                if e.kind() == io::ErrorKind::TimedOut
+                    && (!started.load(Ordering::Acquire) || control.is_paused()) =>
+            {
+                last_read = Instant::now();
+                continue;
+            }
+
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                let timeouts = reading.timeouts.fetch_add(1, Ordering::Relaxed) + 1;
+
+                let silence = last_read.elapsed();
+                if silence >= transmission.stall {
+                    tracing::error!("Nothing received for {silence:?}, giving up");
+                    break Ending::Stalled { received: 0 };
+                }
+
+                if timeouts > u32::from(transmission.retries) {
+                    tracing::error!("Read timed out {timeouts} times, giving up");
+                    break Ending::Exhausted { received: 0 };
+                }
+
+                tracing::debug!("Read timed out, retrying ({timeouts} timeouts so far)");
+                continue;
+            }
+
+            Err(e) => {
+                tracing::error!("Failed to read samples: {e}");
+                break Ending::Disconnected;
+            }
+        };
+
+        last_read = Instant::now();
+        queue.push(read, clock::timestamp(epoch));
+
+        if let Some(bytes) = serial.backlog() {
+            crate::perf::record_backlog(bytes);
+        }
+    };
+
+    *crate::perf::lock(&reading.failure) = Some(failure);
+}
+
+/// Decodes the reads of `queue` into blocks of samples, locking `output` once per read
+///
+/// A frame split across two reads is kept until it is complete, as are the samples of an
+/// output whose channels weren't all read. Blocks read once transmission has `started` go to
+/// `output` and `channels`, so the pre-trigger boundary is only exact up to a block. Only the
+/// first channel is kept before it. The `snapshot` of `output` is published as reads come in,
+/// and once reception ends. Once `shutdown` is ordered, the EOT is only awaited for
+/// [`crate::EOT_DRAIN_TIMEOUT`]
+fn receiver(
+    mut queue: Consumer,
+    reading: &Reading,
+    output: &Mutex<Samples>,
+    mut snapshot: Publisher,
+    channels: &Mutex<Vec<Vec<f32>>>,
+    pre_trigger: &Mutex<Vec<f32>>,
+    sideband: &Sideband,
+    started: &AtomicBool,
+    throughput: &Throughput,
+    protocol: Protocol,
+    shutdown: &Shutdown,
+) -> Ending {
+    const POLL: Duration = Duration::from_millis(10);
+
+    let mut deframer = Deframer::with_protocol(protocol);
+    deframer.inspect(Arc::clone(&sideband.inspector));
+    // Samples of the current block, and reports with their position in it
+    let mut samples = Vec::with_capacity(crate::RX_BUFFER_SIZE / mem::size_of::<f32>());
+    let mut reports = Vec::new();
+    let width = crate::perf::lock(channels).len() + 1;
+    // Until when the EOT is awaited, once shutdown is ordered
+    let mut deadline = None;
+
+    let ending = loop {
+        if deadline.is_none() && shutdown.is_ordered() {
+            tracing::info!("Draining outputs until the device's EOT");
+            deadline = Some(Instant::now() + Duration::from_millis(crate::EOT_DRAIN_TIMEOUT));
+        }
+
+        if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            tracing::warn!("Ending reception: no EOT after draining outputs");
+            break Ending::Interrupted {
+                received: crate::perf::lock(output).len(),
+            };
+        }
+
+        let Some(chunk) = queue.front() else {
+            if queue.is_drained() {
+                // Left unset if the reader panicked
+                let failure = crate::perf::lock(&reading.failure).take();
+                break failure
+                    .unwrap_or(Ending::Disconnected)
+                    .counting(crate::perf::lock(output).len());
+            }
+
+            queue.wait(POLL);
+            continue;
+        };
+
+        let received = chunk.received;
+        deframer.push(chunk.bytes());
+        queue.pop();
+
+        let mut eot = false;
+
+        while let Some(payload) = deframer.next_payload() {
+            if payload.is_empty() {
+                eot = true;
+                break;
+            }
+
+            parse(payload, &mut samples, &mut reports);
+        }
+
+        // Samples of complete outputs, whose channels were all read
+        let framed = samples.len() - samples.len() % width;
+        let frames = &samples[..framed];
+
+        // Reports preceding transmission are filed at its start
+        let offset = if started.load(Ordering::Acquire) {
+            for (i, channel) in crate::perf::lock(channels).iter_mut().enumerate() {
+                channel.extend(frames.iter().skip(i + 1).step_by(width));
+            }
+
+            let mut output = crate::perf::lock(output);
+            let offset = output.len();
+            let stored = output.append(frames.iter().copied().step_by(width));
+            snapshot.publish(&output);
+
+            if let Err(e) = stored {
+                tracing::error!("Failed to store outputs: {e}");
+                break Ending::Unstored {
+                    received: output.len(),
+                    error: e.kind(),
+                };
+            }
+
+            throughput
+                .received
+                .fetch_add(framed / width, Ordering::Release);
+            Some(offset)
+        } else {
+            crate::perf::lock(pre_trigger).extend(frames.iter().step_by(width));
+            None
+        };
+
+        samples.drain(..framed);
+
+        for (index, report) in reports.drain(..) {
+            let index = offset.map_or(0, |offset| offset + index / width);
+
+            match report {
+                Report::Telemetry { channel, value } => {
+                    crate::perf::lock(&sideband.telemetry).record(channel, index, value);
+                }
+
+                Report::Log { severity, message } => {
+                    tracing::debug!("Device {severity}: {message}");
+                    crate::perf::lock(&sideband.log).push(Entry {
+                        index,
+                        severity,
+                        message,
+                    });
+                }
+
+                Report::Sync { sent, index } => {
+                    crate::perf::lock(&sideband.clock).record(Exchange {
+                        sent,
+                        received,
+                        index,
+                    });
+                }
+            }
+        }
+
+        if eot {
+            tracing::info!("Ending reception: EOT");
+            break Ending::Completed {
+                timeouts: reading.timeouts.load(Ordering::Relaxed),
+                lost: deframer.lost(),
+            };
+        }
+    };
+
+    snapshot.finish(&crate::perf::lock(output));
+
+    tracing::info!("Reception ended: {ending}");
+    ending
+}
+
+/// Reads samples until the end of the transmission, discarding sideband reports
+///
+/// Unlike the receiver, gives up once nothing arrived for `timeout`, returning the samples read
+/// so far
+pub fn collect(source: &mut impl Read, timeout: Duration) -> io::Result<Vec<f32>> {
+    let mut buffer = [0u8; crate::RX_BUFFER_SIZE];
+    let mut deframer = Deframer::default();
+    let mut samples = Vec::new();
+    let mut reports = Vec::new();
+    let mut last_read = Instant::now();
+
+    loop {
+        let read = match source.read(&mut buffer) {
+            Ok(0) => {
+                tracing::warn!("Port closed before EOT");
+                return Ok(samples);
+            }
+
+            Ok(read) => read,
+
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                if last_read.elapsed() < timeout {
+                    continue;
+                }
+
+                tracing::warn!("Nothing received for {timeout:?}, giving up before EOT");
+                return Ok(samples);
+            }
+
+            Err(e) => return Err(e),
+        };
+
+        last_read = Instant::now();
+        deframer.push(&buffer[..read]);
+
+        while let Some(payload) = deframer.next_payload() {
+            if payload.is_empty() {
+                return Ok(samples);
+            }
+
+            parse(payload, &mut samples, &mut reports);
+            reports.clear();
+        }
+    }
 }
@@ -0,0 +1,278 @@
+use iced::{
+    widget::{column, pick_list, row, text},
+    Element, Length,
+};
+use plotters::prelude::*;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget};
+use rustfft::num_complex::Complex64;
+use std::{f64::consts::PI, fmt};
+
+use super::super::theme;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Message {
+    SourceSelected(Source),
+}
+
+/// Transfer function coefficients, in ascending powers of `z^-1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coefficients {
+    /// Numerator
+    pub b: Vec<f64>,
+    /// Denominator
+    pub a: Vec<f64>,
+}
+
+impl Coefficients {
+    /// Finite impulse response with the given taps
+    pub fn fir(taps: &[f32]) -> Self {
+        Self {
+            b: taps.iter().copied().map(f64::from).collect(),
+            a: vec![1.0],
+        }
+    }
+
+    /// Zeros and poles, including those at the origin
+    fn roots(&self) -> (Vec<Complex64>, Vec<Complex64>) {
+        let mut zeros = roots(&self.b);
+        let mut poles = roots(&self.a);
+
+        let (zero_order, pole_order) = (order(&self.b), order(&self.a));
+        zeros.resize(
+            zeros.len() + pole_order.saturating_sub(zero_order),
+            Complex64::default(),
+        );
+        poles.resize(
+            poles.len() + zero_order.saturating_sub(pole_order),
+            Complex64::default(),
+        );
+
+        (zeros, poles)
+    }
+}
+
+/// Where plotted coefficients come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Those the device was designed with, if known
+    Designed,
+    /// Those of the impulse response estimated from the run
+    Estimated,
+}
+
+impl Source {
+    const ALL: [Self; 2] = [Self::Designed, Self::Estimated];
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Designed => "Designed",
+            Self::Estimated => "Estimated",
+        })
+    }
+}
+
+/// Pole-zero plot in the z-plane
+pub struct ZPlane {
+    source: Source,
+}
+
+impl ZPlane {
+    pub const fn new(designed: bool) -> Self {
+        Self {
+            source: if designed {
+                Source::Designed
+            } else {
+                Source::Estimated
+            },
+        }
+    }
+
+    pub const fn source(&self) -> Source {
+        self.source
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SourceSelected(source) => self.source = source,
+        }
+    }
+
+    pub fn view(&self, coefficients: Option<&Coefficients>) -> Element<'_, Message> {
+        let source = row![
+            text("Coefficients"),
+            pick_list(&Source::ALL[..], Some(self.source), Message::SourceSelected),
+        ]
+        .spacing(10);
+
+        let Some(coefficients) = coefficients else {
+            let message = match self.source {
+                Source::Designed => "The device's design is unknown",
+                Source::Estimated => "Not enough samples to estimate the impulse response",
+            };
+
+            return column![text(message).height(Length::Fill), source].into();
+        };
+
+        let (zeros, poles) = coefficients.roots();
+        let radius = poles.iter().map(|pole| pole.norm()).fold(0.0, f64::max);
+
+        // Poles on the unit circle are marginally stable at best
+        let stability = if radius < 1.0 - 1e-9 {
+            format!("Stable: largest pole radius {radius:.4}")
+        } else {
+            format!("Unstable: pole at radius {radius:.4}")
+        };
+
+        let chart = ChartWidget::new(Plot { zeros, poles })
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        column![chart, row![source, text(stability)].spacing(20)]
+            .spacing(10)
+            .into()
+    }
+}
+
+/// Order of the polynomial in `z^-1` with the given coefficients
+fn order(coefficients: &[f64]) -> usize {
+    coefficients
+        .iter()
+        .rposition(|&coefficient| coefficient != 0.0)
+        .unwrap_or(0)
+}
+
+/// Nonzero roots in `z` of the polynomial in `z^-1` with the given coefficients
+///
+/// Found with the Durand-Kerner method. Leading zero coefficients are pure delays, whose
+/// poles at the origin are accounted for by [`Coefficients::roots`]
+fn roots(coefficients: &[f64]) -> Vec<Complex64> {
+    const ITERATIONS: usize = 1000;
+    const TOLERANCE: f64 = 1e-12;
+
+    let Some(first) = coefficients.iter().position(|&c| c != 0.0) else {
+        return Vec::new();
+    };
+    let last = order(coefficients);
+
+    // Monic, in descending powers of `z`
+    let monic: Vec<_> = coefficients[first..=last]
+        .iter()
+        .map(|c| c / coefficients[first])
+        .collect();
+    let degree = monic.len() - 1;
+
+    let evaluate = |z: Complex64| {
+        monic
+            .iter()
+            .fold(Complex64::default(), |value, &c| value * z + c)
+    };
+
+    let seed = Complex64::new(0.4, 0.9);
+    #[allow(clippy::cast_possible_truncation)]
+    let mut roots: Vec<_> = (0..degree).map(|i| seed.powu(i as u32)).collect();
+
+    for _ in 0..ITERATIONS {
+        let mut change = 0f64;
+
+        for i in 0..degree {
+            let denominator = (0..degree)
+                .filter(|&j| j != i)
+                .fold(Complex64::new(1.0, 0.0), |product, j| {
+                    product * (roots[i] - roots[j])
+                });
+
+            let delta = evaluate(roots[i]) / denominator;
+            roots[i] -= delta;
+            change = change.max(delta.norm());
+        }
+
+        if change < TOLERANCE {
+            break;
+        }
+    }
+
+    roots
+}
+
+struct Plot {
+    zeros: Vec<Complex64>,
+    poles: Vec<Complex64>,
+}
+
+impl Chart<Message> for Plot {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(
+        &self,
+        _state: &Self::State,
+        mut builder: ChartBuilder<'_, '_, DB>,
+    ) {
+        let colors = theme::colors();
+        let extent = self
+            .zeros
+            .iter()
+            .chain(&self.poles)
+            .map(|root| root.re.abs().max(root.im.abs()))
+            .fold(1.0, f64::max)
+            * 1.2;
+
+        let mut chart = builder
+            .x_label_area_size(24)
+            .y_label_area_size(36)
+            .margin(10)
+            .build_cartesian_2d(-extent..extent, -extent..extent)
+            .expect("built chart");
+
+        chart
+            .configure_mesh()
+            .axis_style(colors.foreground)
+            .label_style(("sans-serif", 18).into_font().color(&colors.foreground))
+            .x_desc("Re")
+            .y_desc("Im")
+            .light_line_style(colors.foreground.mix(0.10))
+            .bold_line_style(colors.foreground.mix(0.30))
+            .draw()
+            .expect("drawn mesh");
+
+        chart
+            .draw_series(LineSeries::new(
+                (0..=256).map(|i| {
+                    let angle = 2.0 * PI * f64::from(i) / 256.0;
+                    (angle.cos(), angle.sin())
+                }),
+                colors.foreground.mix(0.6),
+            ))
+            .expect("drawn unit circle");
+
+        chart
+            .draw_series(
+                self.zeros
+                    .iter()
+                    .map(|zero| Circle::new((zero.re, zero.im), 6, colors.input.stroke_width(2))),
+            )
+            .expect("drawn zeros")
+            .label("Zeros")
+            .legend(move |(x, y)| Circle::new((x + 10, y), 6, colors.input.stroke_width(2)));
+
+        chart
+            .draw_series(
+                self.poles
+                    .iter()
+                    .map(|pole| Cross::new((pole.re, pole.im), 6, RED.stroke_width(2))),
+            )
+            .expect("drawn poles")
+            .label("Poles")
+            .legend(|(x, y)| Cross::new((x + 10, y), 6, RED.stroke_width(2)));
+
+        chart
+            .configure_series_labels()
+            .border_style(colors.foreground)
+            .label_font(("sans-serif", 18).into_font().color(&colors.foreground))
+            .background_style(colors.background)
+            .position(SeriesLabelPosition::UpperRight)
+            .draw()
+            .expect("drawn legend");
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, IntoInnerError, Read, Write},
+    path::Path,
+};
+
+/// Precedes the checksum of line-based files, on their last line
+pub const CHECKSUM_COMMENT: &str = "# crc32=";
+/// Precedes the checksum of JSON files, as the last member of their object
+pub const CHECKSUM_MEMBER: &str = r#","crc32":""#;
+
+/// Writes `path` atomically
+///
+/// Contents are written to a temporary file next to `path`, synced to disk and read back to
+/// verify their checksum, and only then renamed over `path`. A crash or failure at any point
+/// leaves the previous contents of `path` intact.
+///
+/// # Errors
+/// Fails if `contents` fails, on I/O errors, or if the contents read back don't match
+pub fn write_atomic(
+    path: &Path,
+    contents: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let file = tempfile::NamedTempFile::new_in(directory)?;
+    let mut writer = BufWriter::new(Checksummed::new(file));
+
+    contents(&mut writer)?;
+
+    let Checksummed { inner: file, hasher } =
+        writer.into_inner().map_err(IntoInnerError::into_error)?;
+    file.as_file().sync_all()?;
+
+    let expected = hasher.finalize();
+    let actual = checksum(file.reopen()?)?;
+
+    if actual != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("checksum mismatch: wrote {expected:08x}, read back {actual:08x}"),
+        ));
+    }
+
+    file.persist(path).map_err(|e| e.error)?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    File::open(directory)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Writes `path` with [`write_atomic`], ending with a [`CHECKSUM_COMMENT`] line for [`verify`]
+///
+/// # Errors
+/// Fails like [`write_atomic`]
+pub fn write_checksummed(
+    path: &Path,
+    contents: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    write_atomic(path, |writer| {
+        let mut writer = Checksummed::new(writer);
+        contents(&mut writer)?;
+
+        let checksum = writer.checksum();
+        writeln!(writer, "{CHECKSUM_COMMENT}{checksum:08x}")
+    })
+}
+
+/// CRC32 of everything readable from `reader`
+fn checksum(reader: impl Read) -> io::Result<u32> {
+    let mut reader = BufReader::new(reader);
+    let mut hasher = crc32fast::Hasher::new();
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break Ok(hasher.finalize());
+        }
+
+        hasher.update(buffer);
+        let consumed = buffer.len();
+        reader.consume(consumed);
+    }
+}
+
+/// Checks the CRC32 in hexadecimal following the last `marker` of `contents`, against
+/// everything before it
+///
+/// Returns whether `contents` carried a checksum, which files written before they were
+/// embedded don't
+///
+/// # Errors
+/// Fails if the checksum doesn't match, the contents having been corrupted or edited
+pub fn verify(contents: &[u8], marker: &str) -> io::Result<bool> {
+    let Some(position) = contents
+        .windows(marker.len())
+        .rposition(|window| window == marker.as_bytes())
+    else {
+        return Ok(false);
+    };
+
+    let expected = contents
+        .get(position + marker.len()..position + marker.len() + 8)
+        .and_then(|hex| std::str::from_utf8(hex).ok())
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed checksum"))?;
+    let actual = crc32fast::hash(&contents[..position]);
+
+    if actual == expected {
+        Ok(true)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "checksum mismatch: recorded {expected:08x}, computed {actual:08x}, the file \
+                 was corrupted or edited"
+            ),
+        ))
+    }
+}
+
+/// Computes the checksum of everything written through it
+pub struct Checksummed<W> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W> Checksummed<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    /// CRC32 of everything written so far
+    pub fn checksum(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -1,117 +1,1304 @@
 use iced::{
     alignment::Horizontal,
     widget::{
-        button, column, horizontal_space, radio, row, scrollable, slider, text, text_input,
-        vertical_space,
+        button, checkbox, column, horizontal_space, pick_list, radio, row, scrollable, slider,
+        text, text_input, vertical_space, Slider,
     },
-    Command, Element, Length, Subscription,
+    Alignment, Color, Command, Element, Length, Subscription,
+};
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
 };
-use pyo3::{types::IntoPyDict, PyResult, Python};
-use serialport::SerialPortInfo;
 
-use super::{filter::Filter, Message::Ports as App};
+use super::{
+    config::{Config, Session},
+    diagnostics::pick_interpreter,
+    filter::{
+        emulator::{Artifacts, Device},
+        import, osc, selftest, soak, Filter, Outlets, Protocol, Target, Transmission, View,
+    },
+    profile::Profile,
+    signal::{self, Overrange, Parameters, Preset, Summary, TimeBase, Waveform},
+    template::Template,
+    theme,
+    Message::Ports as App,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     RefreshPorts,
-    PortSelected(usize),
+    PortSelected(String),
+    ProfileSelected(String),
+    ClampToggled(bool),
+    DeviceSelected(Device),
+    NetworkToggled(bool),
+    NetworkAddressUpdated(String),
+    ResolutionUpdated(u8),
+    OffsetUpdated(f32),
+    NoiseUpdated(f32),
     StopTimeUpdated(f32),
+    SampleCountToggled(bool),
+    SampleCountUpdated(String),
+    EndpointToggled(bool),
+    CenteredToggled(bool),
+    PreTriggerUpdated(u16),
+    GapUpdated(u16),
+    ChunkUpdated(u16),
+    RepeatUpdated(u16),
+    SyncToggled(bool),
+    RetriesUpdated(u16),
+    HandshakesUpdated(u16),
+    KeepOpenToggled(bool),
+    ProtocolSelected(Protocol),
+    /// Stall limit, in milliseconds
+    StallUpdated(u16),
+    OscToggled(bool),
+    OscTargetUpdated(String),
+    OscDecimationUpdated(u16),
+    LslToggled(bool),
+    ChooseInterpreter,
+    /// Picked interpreter, if any
+    InterpreterPicked(Option<PathBuf>),
+    /// Use the embedded interpreter's own packages
+    EmbeddedInterpreter,
+    NormalizationToggled(bool),
+    NormalizationUpdated(f32),
+    /// Switches between presets and functions of `t`
+    AdvancedToggled(bool),
+    WaveformSelected(Waveform),
+    AmplitudeUpdated(f32),
+    FrequencyUpdated(f32),
+    EndFrequencyUpdated(f32),
+    DelayUpdated(f32),
     FunctionUpdated(String),
+    /// Function picked from [`Ports::function_history`]
+    FunctionRecalled(String),
+    SeedUpdated(String),
+    RandomizeSeed,
+    TemplateSelected(usize),
+    RecallSession(usize),
+    CompareToggled(usize),
+    OpenFile,
+    /// Exported file to replay, if any
+    FilePicked(Option<PathBuf>),
     EvaluateFunction,
+    /// Outcome of the numbered validation, with a preview of the generated input
+    Validated(u64, Result<Summary, String>),
+    CancelValidation,
+    /// Advances the validation spinner, and the progress of soak tests
+    Spin,
+    SelfTest,
+    SelfTestCompleted(Result<selftest::Report, String>),
+    LoopbackTest,
+    LoopbackCompleted(Result<selftest::Loopback, String>),
+    SoakHoursUpdated(u8),
+    SoakTest,
+    StopSoakTest,
+    SoakCompleted(Result<soak::Summary, String>),
+    ToggleSettings,
+    Theme(theme::Message),
     Filter,
 }
 
+/// Outcome of [`Ports::update`]
+pub enum Transition {
+    /// Keep configuring, running the given command
+    Stay(Command<super::Message>),
+    /// Start filtering
+    Filter(Filter, Command<super::Message>),
+}
+
+/// Round-trip test of the selected port or emulated device
+enum SelfTest {
+    Running,
+    Finished(selftest::Report),
+    /// Loopback test of the selected port
+    LoopedBack(selftest::Loopback),
+    /// Soak test in progress, since the given time
+    Soaking(Arc<soak::Progress>, Instant),
+    Soaked(soak::Summary),
+    /// Unable to connect or to exchange the pattern
+    Failed(String),
+}
+
+/// Source of [`Message::RefreshPorts`]
+enum Hotplug {
+    Starting,
+    /// Woken up by USB devices coming and going
+    Watching(nusb::hotplug::HotplugWatch),
+    /// Periodic enumeration, where no watcher is available
+    Polling,
+}
+
 pub struct Ports {
+    /// Built-in signal, generated unless [`Self::advanced`]
+    preset: Preset,
+    /// Is the input given by [`Self::function`] rather than [`Self::preset`]?
+    advanced: bool,
     /// Function to be evaluated
     ///
     /// Evaluated at uniform intervals between \[0, [`Self::stop_time`]\]
     function: String,
-    /// Is [`Self::function`] syntactically correct?
+    /// Is the input, from [`Self::function`] or [`Self::preset`], valid?
     validated: bool,
+    /// Start of the validation in flight, if any
+    validating: Option<Instant>,
+    /// Interruptible task of the latest validation, identifying it
+    validation: u64,
+    /// Statistics of the input generated by the last validation, until its settings change
+    preview: Option<Summary>,
     /// How long to simulate [`Self::function`] for
     stop_time: f32,
-    /// Index of desired port in [`Self::available_ports`]
-    selected_port: Option<usize>,
+    /// Run length in samples, overriding [`Self::stop_time`]
+    samples: Option<u32>,
+    /// [`Self::samples`] as typed, which may not parse into a positive count
+    samples_text: String,
+    /// Convention of the time vector
+    time_base: TimeBase,
+    /// Seed of the random number generators available to [`Self::function`]
+    seed: u32,
+    /// [`Self::seed`] as typed, which may not parse into it
+    seed_text: String,
+    /// Percentage of the full scale to normalize the input's peak to, if any
+    normalization: Option<f32>,
+    /// How the input is transmitted
+    transmission: Transmission,
+    /// Plot shown once connected, as set by templates
+    view: View,
+    /// Live streams of the outputs to other applications
+    outlets: Outlets,
+    /// Interpreter whose environment's packages are used, if not the embedded one
+    python: Option<PathBuf>,
+    /// Was [`Self::python`] changed since startup, only taking effect on restart?
+    python_changed: bool,
+    /// Identity of the desired port in [`Self::available_ports`], as given by [`identity`]
+    selected_port: Option<String>,
+    /// Emulated device, used instead of a port
+    emulated_device: Option<Device>,
+    /// Is the device at [`Self::network_address`] used instead of a port?
+    networked: bool,
+    /// `host:port` address of a networked device, kept while unused
+    network_address: String,
+    /// Imperfections of the emulated device
+    artifacts: Artifacts,
+    /// Known device profiles, starting with the default
+    profiles: Vec<Profile>,
+    /// Index of the device's profile in [`Self::profiles`]
+    selected_profile: usize,
+    /// Handling of generated samples outside of the profile's full scale
+    overrange: Overrange,
     /// Scanned ports
     available_ports: Vec<SerialPortInfo>,
+    /// Previous runs, most recent first
+    recent_sessions: Vec<Session>,
+    /// Previously exported files, most recent first
+    recent_files: Vec<PathBuf>,
+    /// Previously validated functions, most recent first
+    function_history: Vec<String>,
+    /// Indices of the sessions being compared in [`Self::recent_sessions`], at most two
+    compared: Vec<usize>,
+    /// Last self-test, loopback or soak test started, if any
+    self_test: Option<SelfTest>,
+    /// Duration of soak tests, in hours
+    soak_hours: u8,
+    /// Appearance editor, while open
+    settings: Option<theme::Panel>,
 }
 
 impl Ports {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
+        let Config {
+            recent_sessions,
+            recent_files,
+            function_history,
+            profile,
+            overrange,
+            time_base,
+            python,
+            network_address,
+            ..
+        } = Config::load();
+
+        let profiles = Profile::load_all();
+        let selected_profile = profile
+            .and_then(|name| profiles.iter().position(|profile| profile.name == name))
+            .unwrap_or(0);
+
+        let seed = signal::random_seed();
+
         Self {
+            preset: Preset::default(),
+            advanced: false,
             function: String::new(),
             validated: false,
+            validating: None,
+            validation: signal::task(),
+            preview: None,
             stop_time: 1.0f32,
+            samples: None,
+            samples_text: String::new(),
+            time_base,
+            seed,
+            seed_text: seed.to_string(),
+            normalization: None,
+            transmission: Transmission::default(),
+            view: View::TimeSeries,
+            outlets: Outlets::default(),
+            python,
+            python_changed: false,
             selected_port: None,
+            emulated_device: None,
+            networked: false,
+            network_address: network_address.unwrap_or_default(),
+            artifacts: Artifacts::NONE,
+            profiles,
+            selected_profile,
+            overrange,
             available_ports: Vec::new(),
+            recent_sessions,
+            recent_files,
+            function_history,
+            compared: Vec::new(),
+            self_test: None,
+            soak_hours: crate::DEFAULT_SOAK_HOURS,
+            settings: None,
         }
     }
 }
 
 impl Ports {
-    pub fn update(&mut self, message: Message) -> Option<(Filter, Command<super::Message>)> {
+    pub fn update(&mut self, message: Message) -> Transition {
         match message {
             Message::RefreshPorts => {
                 self.update_ports(serialport::available_ports().unwrap_or_default());
-                None
+                Transition::Stay(Command::none())
+            }
+
+            Message::PortSelected(port) => {
+                self.selected_port = Some(port);
+                self.emulated_device = None;
+                self.networked = false;
+                Transition::Stay(Command::none())
             }
 
-            Message::PortSelected(i) => {
-                self.selected_port = Some(i);
-                None
+            Message::ProfileSelected(name) => {
+                if let Some(i) = self
+                    .profiles
+                    .iter()
+                    .position(|profile| profile.name == name)
+                {
+                    self.selected_profile = i;
+                    self.preview = None;
+                    Config::update(|config| config.profile = Some(name));
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::ClampToggled(clamp) => {
+                self.overrange = if clamp {
+                    Overrange::Clamp
+                } else {
+                    Overrange::Warn
+                };
+
+                let overrange = self.overrange;
+                Config::update(|config| config.overrange = overrange);
+                Transition::Stay(Command::none())
+            }
+
+            Message::DeviceSelected(device) => {
+                self.emulated_device = Some(device);
+                self.selected_port = None;
+                self.networked = false;
+                Transition::Stay(Command::none())
+            }
+
+            Message::NetworkToggled(networked) => {
+                self.networked = networked;
+                if networked {
+                    self.selected_port = None;
+                    self.emulated_device = None;
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::NetworkAddressUpdated(address) => {
+                self.network_address = address;
+                Transition::Stay(Command::none())
+            }
+
+            Message::ResolutionUpdated(bits) => {
+                self.artifacts.resolution = (bits > 0).then_some(bits);
+                Transition::Stay(Command::none())
+            }
+
+            Message::OffsetUpdated(offset) => {
+                self.artifacts.offset = offset;
+                Transition::Stay(Command::none())
+            }
+
+            Message::NoiseUpdated(noise) => {
+                self.artifacts.noise = noise;
+                Transition::Stay(Command::none())
             }
 
             Message::StopTimeUpdated(t) => {
                 self.stop_time = t;
-                None
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::SampleCountToggled(enabled) => {
+                self.samples = enabled.then_some(crate::DEFAULT_SAMPLE_COUNT);
+                self.samples_text = crate::DEFAULT_SAMPLE_COUNT.to_string();
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::SampleCountUpdated(text) => {
+                if let Ok(samples) = text.parse() {
+                    if samples > 0 {
+                        self.samples = Some(samples);
+                    }
+                }
+
+                self.samples_text = text;
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::EndpointToggled(endpoint) => {
+                self.time_base.endpoint = endpoint;
+                self.preview = None;
+
+                let time_base = self.time_base;
+                Config::update(|config| config.time_base = time_base);
+                Transition::Stay(Command::none())
+            }
+
+            Message::CenteredToggled(centered) => {
+                self.time_base.centered = centered;
+                self.preview = None;
+
+                let time_base = self.time_base;
+                Config::update(|config| config.time_base = time_base);
+                Transition::Stay(Command::none())
+            }
+
+            Message::PreTriggerUpdated(milliseconds) => {
+                self.transmission.pre_trigger = Duration::from_millis(milliseconds.into());
+                Transition::Stay(Command::none())
+            }
+
+            Message::GapUpdated(microseconds) => {
+                self.transmission.gap = Duration::from_micros(microseconds.into());
+                Transition::Stay(Command::none())
+            }
+
+            Message::ChunkUpdated(chunk) => {
+                self.transmission.chunk = chunk;
+                Transition::Stay(Command::none())
+            }
+
+            Message::RepeatUpdated(repeat) => {
+                self.transmission.repeat = repeat;
+                Transition::Stay(Command::none())
+            }
+
+            Message::SyncToggled(sync) => {
+                self.transmission.sync = sync;
+                Transition::Stay(Command::none())
+            }
+
+            Message::RetriesUpdated(retries) => {
+                self.transmission.retries = retries;
+                Transition::Stay(Command::none())
+            }
+
+            Message::StallUpdated(milliseconds) => {
+                self.transmission.stall = Duration::from_millis(milliseconds.into());
+                Transition::Stay(Command::none())
+            }
+
+            Message::HandshakesUpdated(handshakes) => {
+                self.transmission.handshakes = handshakes;
+                Transition::Stay(Command::none())
+            }
+
+            Message::KeepOpenToggled(keep_open) => {
+                self.transmission.keep_open = keep_open;
+                Transition::Stay(Command::none())
+            }
+
+            Message::ProtocolSelected(protocol) => {
+                self.transmission.protocol = protocol;
+                Transition::Stay(Command::none())
+            }
+
+            Message::OscToggled(enabled) => {
+                self.outlets.osc = enabled.then(osc::Settings::default);
+                Transition::Stay(Command::none())
+            }
+
+            Message::OscTargetUpdated(target) => {
+                if let Some(osc) = &mut self.outlets.osc {
+                    osc.target = target;
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::OscDecimationUpdated(decimation) => {
+                if let Some(osc) = &mut self.outlets.osc {
+                    osc.decimation = decimation;
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::LslToggled(lsl) => {
+                self.outlets.lsl = lsl;
+                Transition::Stay(Command::none())
+            }
+
+            Message::ChooseInterpreter => Transition::Stay(
+                Command::perform(pick_interpreter(), Message::InterpreterPicked).map(App),
+            ),
+
+            Message::InterpreterPicked(Some(interpreter)) => {
+                self.python = Some(interpreter.clone());
+                self.python_changed = true;
+                Config::update(|config| config.python = Some(interpreter));
+                Transition::Stay(Command::none())
+            }
+
+            Message::InterpreterPicked(None) => Transition::Stay(Command::none()),
+
+            Message::EmbeddedInterpreter => {
+                self.python = None;
+                self.python_changed = true;
+                Config::update(|config| config.python = None);
+                Transition::Stay(Command::none())
+            }
+
+            Message::NormalizationToggled(enabled) => {
+                self.normalization = enabled.then_some(crate::DEFAULT_NORMALIZATION);
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::NormalizationUpdated(percent) => {
+                self.normalization = Some(percent);
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::AdvancedToggled(advanced) => {
+                self.advanced = advanced;
+                self.invalidate();
+                Transition::Stay(Command::none())
+            }
+
+            Message::WaveformSelected(waveform) => {
+                self.preset.waveform = waveform;
+                self.invalidate();
+                Transition::Stay(Command::none())
+            }
+
+            Message::AmplitudeUpdated(amplitude) => {
+                self.preset.amplitude = amplitude;
+                self.invalidate();
+                Transition::Stay(Command::none())
+            }
+
+            Message::FrequencyUpdated(frequency) => {
+                self.preset.frequency = frequency;
+                self.invalidate();
+                Transition::Stay(Command::none())
+            }
+
+            Message::EndFrequencyUpdated(frequency) => {
+                self.preset.end_frequency = frequency;
+                self.invalidate();
+                Transition::Stay(Command::none())
+            }
+
+            Message::DelayUpdated(delay) => {
+                self.preset.delay = delay;
+                self.invalidate();
+                Transition::Stay(Command::none())
             }
 
             Message::FunctionUpdated(f) => {
                 self.function = f;
-                self.validated = false;
-                None
+                self.invalidate();
+                Transition::Stay(Command::none())
             }
 
-            Message::EvaluateFunction => {
-                self.validate();
-                None
+            Message::SeedUpdated(text) => {
+                if let Ok(seed) = text.parse() {
+                    self.seed = seed;
+                }
+
+                self.seed_text = text;
+                self.preview = None;
+                Transition::Stay(Command::none())
+            }
+
+            Message::RandomizeSeed => {
+                self.seed = signal::random_seed();
+                self.seed_text = self.seed.to_string();
+                self.preview = None;
+                Transition::Stay(Command::none())
             }
 
+            Message::FunctionRecalled(function) => {
+                self.function = function;
+                Transition::Stay(self.validate())
+            }
+
+            Message::TemplateSelected(i) => {
+                let template = Template::ALL[i];
+
+                self.advanced = true;
+                self.function = template.function.to_owned();
+                self.stop_time = template.stop_time;
+                self.samples = None;
+                self.normalization = template.normalization;
+                self.view = template.view;
+
+                Transition::Stay(self.validate())
+            }
+
+            Message::RecallSession(i) => {
+                let Session {
+                    port,
+                    function,
+                    preset,
+                    stop_time,
+                    samples,
+                    seed,
+                    normalization,
+                    profile,
+                    overrange,
+                    time_base,
+                    transmission,
+                } = self.recent_sessions[i].clone();
+
+                match preset {
+                    Some(preset) => self.preset = preset,
+                    None => self.function = function,
+                }
+                self.advanced = preset.is_none();
+                self.stop_time = stop_time;
+                self.samples = samples;
+                self.samples_text = samples.map_or_else(String::new, |samples| samples.to_string());
+                self.seed = seed;
+                self.seed_text = seed.to_string();
+                self.normalization = normalization;
+                self.overrange = overrange;
+                self.time_base = time_base;
+                self.transmission = transmission;
+                self.view = View::TimeSeries;
+
+                // Profiles deleted since are left selected as they are
+                if let Some(i) = profile.and_then(|name| {
+                    self.profiles
+                        .iter()
+                        .position(|profile| profile.name == name)
+                }) {
+                    self.selected_profile = i;
+                }
+
+                let profile = self.profiles[self.selected_profile].name.clone();
+                Config::update(|config| {
+                    config.profile = Some(profile);
+                    config.overrange = overrange;
+                    config.time_base = time_base;
+                });
+                self.selected_port = self
+                    .available_ports
+                    .iter()
+                    .find(|available| available.port_name == port)
+                    .map(|available| identity(available).to_owned());
+                self.emulated_device = Device::ALL
+                    .into_iter()
+                    .find(|device| Target::Emulated(*device, self.artifacts).to_string() == port);
+                self.networked = false;
+                if let Some(address) = port.strip_prefix("tcp://") {
+                    self.networked = true;
+                    self.network_address = address.to_owned();
+                }
+
+                Transition::Stay(self.validate())
+            }
+
+            Message::CompareToggled(i) => {
+                if let Some(position) = self.compared.iter().position(|&compared| compared == i) {
+                    self.compared.remove(position);
+                } else {
+                    self.compared.push(i);
+                    if self.compared.len() > 2 {
+                        self.compared.remove(0);
+                    }
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::OpenFile => Transition::Stay(
+                Command::perform(import::pick_path(), Message::FilePicked).map(App),
+            ),
+
+            Message::FilePicked(Some(path)) => {
+                let (filter, command) =
+                    Filter::replay(path, self.profiles[self.selected_profile].clone());
+
+                Transition::Filter(filter, command)
+            }
+
+            Message::FilePicked(None) => Transition::Stay(Command::none()),
+
+            Message::EvaluateFunction => Transition::Stay(self.validate()),
+
+            Message::Validated(validation, result) => {
+                // Results of cancelled or superseded validations are dropped
+                if validation == self.validation {
+                    self.validating = None;
+
+                    match result {
+                        Ok(summary) => {
+                            tracing::info!("Evaluation successful: {summary}");
+                            self.validated = true;
+                            self.preview = Some(summary);
+
+                            if self.advanced {
+                                let function = self.function.clone();
+                                let history = &mut self.function_history;
+                                Config::update(|config| {
+                                    config.remember_function(function);
+                                    history.clone_from(&config.function_history);
+                                });
+                            }
+                        }
+                        Err(e) => tracing::error!("Evaluation failed: {e}"),
+                    }
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::CancelValidation => {
+                tracing::info!("Evaluation cancelled");
+                self.abandon();
+                Transition::Stay(Command::none())
+            }
+
+            Message::Spin => Transition::Stay(Command::none()),
+
             Message::Filter => {
-                use std::mem::take;
-                let i = self.selected_port.expect("selected port");
+                let target = self.target();
+                let parameters = self.parameters();
 
-                Some(Filter::new(
-                    take(&mut self.available_ports[i].port_name),
-                    take(&mut self.function),
-                    self.stop_time,
-                ))
+                let session = Session {
+                    port: target.to_string(),
+                    function: parameters.function.clone(),
+                    preset: parameters.preset,
+                    stop_time: self.stop_time,
+                    samples: self.samples,
+                    seed: self.seed,
+                    normalization: self.normalization,
+                    profile: Some(self.profiles[self.selected_profile].name.clone()),
+                    overrange: self.overrange,
+                    time_base: self.time_base,
+                    transmission: self.transmission,
+                };
+                Config::update(|config| {
+                    config.remember_session(session);
+                    if let Target::Tcp(address) = &target {
+                        config.network_address = Some(address.clone());
+                    }
+                });
+
+                let (filter, command) = Filter::new(
+                    target,
+                    parameters,
+                    self.transmission,
+                    self.profiles[self.selected_profile].clone(),
+                    self.view,
+                    self.outlets.clone(),
+                );
+
+                Transition::Filter(filter, command)
+            }
+
+            Message::SelfTest => {
+                let target = self.target();
+                let profile = self.profiles[self.selected_profile].clone();
+                let seed = self.seed;
+                let handshakes = self.transmission.handshakes;
+                self.self_test = Some(SelfTest::Running);
+
+                let future = async move {
+                    tokio::task::spawn_blocking(move || {
+                        selftest::run(target, &profile, seed, handshakes)
+                    })
+                    .await
+                    .expect("blocking task ran")
+                    .map_err(|e| e.to_string())
+                };
+
+                Transition::Stay(Command::perform(future, Message::SelfTestCompleted).map(App))
+            }
+
+            Message::SelfTestCompleted(result) => {
+                self.self_test = Some(match result {
+                    Ok(report) => SelfTest::Finished(report),
+                    Err(e) => {
+                        tracing::error!("Self-test failed: {e}");
+                        SelfTest::Failed(e)
+                    }
+                });
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::LoopbackTest => {
+                let port_name = self.port().expect("selected port").port_name.clone();
+                let seed = self.seed;
+                self.self_test = Some(SelfTest::Running);
+
+                let future = async move {
+                    tokio::task::spawn_blocking(move || selftest::loopback(&port_name, seed))
+                        .await
+                        .expect("blocking task ran")
+                        .map_err(|e| e.to_string())
+                };
+
+                Transition::Stay(Command::perform(future, Message::LoopbackCompleted).map(App))
+            }
+
+            Message::LoopbackCompleted(result) => {
+                self.self_test = Some(match result {
+                    Ok(loopback) => SelfTest::LoopedBack(loopback),
+                    Err(e) => {
+                        tracing::error!("Loopback test failed: {e}");
+                        SelfTest::Failed(e)
+                    }
+                });
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::SoakHoursUpdated(hours) => {
+                self.soak_hours = hours;
+                Transition::Stay(Command::none())
+            }
+
+            Message::SoakTest => {
+                let target = self.target();
+                let profile = self.profiles[self.selected_profile].clone();
+                let seed = self.seed;
+                let transmission = self.transmission;
+                let duration = Duration::from_secs(u64::from(self.soak_hours) * 3600);
+                let progress = Arc::new(soak::Progress::default());
+                self.self_test = Some(SelfTest::Soaking(Arc::clone(&progress), Instant::now()));
+
+                let future = async move {
+                    tokio::task::spawn_blocking(move || {
+                        soak::run(target, &profile, seed, transmission, duration, &progress)
+                    })
+                    .await
+                    .expect("blocking task ran")
+                    .map_err(|e| e.to_string())
+                };
+
+                Transition::Stay(Command::perform(future, Message::SoakCompleted).map(App))
+            }
+
+            Message::StopSoakTest => {
+                if let Some(SelfTest::Soaking(progress, _)) = &self.self_test {
+                    progress.stop();
+                }
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::SoakCompleted(result) => {
+                self.self_test = Some(match result {
+                    Ok(summary) => SelfTest::Soaked(summary),
+                    Err(e) => {
+                        tracing::error!("Soak test failed: {e}");
+                        SelfTest::Failed(e)
+                    }
+                });
+
+                Transition::Stay(Command::none())
+            }
+
+            Message::ToggleSettings => {
+                self.settings = match self.settings {
+                    Some(_) => None,
+                    None => Some(theme::Panel::new()),
+                };
+                Transition::Stay(Command::none())
+            }
+
+            Message::Theme(message) => {
+                if let Some(panel) = &mut self.settings {
+                    panel.update(message);
+                }
+
+                Transition::Stay(Command::none())
             }
         }
     }
 
     pub fn view(&self) -> Element<'_, super::Message> {
         let Self {
+            preset,
+            advanced,
             function,
             validated,
+            validating,
+            validation: _,
+            preview,
             stop_time,
+            samples,
+            samples_text,
+            time_base,
+            seed: _,
+            seed_text,
+            normalization,
+            transmission,
+            view: _,
+            outlets,
+            python,
+            python_changed,
             selected_port,
+            emulated_device,
+            networked,
+            network_address,
+            artifacts,
+            profiles,
+            selected_profile,
+            overrange,
             available_ports,
+            recent_sessions,
+            recent_files,
+            function_history,
+            compared,
+            self_test,
+            soak_hours,
+            settings,
         } = self;
 
-        let title = text("Online filtering")
+        let title = {
+            let title = text("Online filtering")
+                .width(Length::Fill)
+                .size(48)
+                .horizontal_alignment(Horizontal::Center);
+            let toggle = button(if settings.is_some() {
+                "Close settings"
+            } else {
+                "Settings"
+            })
+            .on_press(Message::ToggleSettings);
+
+            let mut title = column![row![title, toggle].align_items(Alignment::Center)];
+            if let Some(panel) = settings {
+                title = title.push(panel.view().map(Message::Theme));
+            }
+
+            title.spacing(15)
+        };
+
+        let templates = {
+            let buttons = Template::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, template)| {
+                    button(column![
+                        text(template.name),
+                        text(template.description).size(14)
+                    ])
+                    .width(Length::FillPortion(1))
+                    .on_press(Message::TemplateSelected(i))
+                    .into()
+                })
+                .collect();
+
+            column![text("Templates").size(24), row(buttons).spacing(10)].spacing(10)
+        };
+
+        let length_editor = {
+            let toggle = checkbox(
+                "Sample count",
+                samples.is_some(),
+                Message::SampleCountToggled,
+            );
+
+            match samples {
+                Some(samples) => {
+                    // The actual rate is only known once connected
+                    let rate = profiles[*selected_profile].max_sampling_rate;
+                    let duration = *samples as f32 / rate as f32;
+
+                    row![
+                        toggle,
+                        text_input("4096", samples_text).on_input(Message::SampleCountUpdated),
+                        text(format!("{duration:.2} s at {rate} Hz")),
+                    ]
+                }
+
+                None => row![
+                    toggle,
+                    slider(1.0f32..=30.0f32, *stop_time, Message::StopTimeUpdated).step(0.5f32),
+                ],
+            }
+            .spacing(10)
             .width(Length::Fill)
-            .size(48)
-            .horizontal_alignment(Horizontal::Center);
+        };
+
+        let time_base_editor = row![
+            checkbox(
+                "Include stop time",
+                time_base.endpoint,
+                Message::EndpointToggled
+            ),
+            checkbox(
+                "Center samples on their interval",
+                time_base.centered,
+                Message::CenteredToggled
+            ),
+        ]
+        .spacing(20);
+
+        let mode_selector = row![
+            radio("Preset", false, Some(*advanced), Message::AdvancedToggled),
+            radio(
+                "Function of t (advanced)",
+                true,
+                Some(*advanced),
+                Message::AdvancedToggled
+            ),
+        ]
+        .spacing(20);
+
+        let function_editor = {
+            let editor = if *advanced {
+                let editor = row![text_input("...", function)
+                    .on_input(Message::FunctionUpdated)
+                    .on_submit(Message::EvaluateFunction)]
+                .width(Length::Fill)
+                .spacing(10);
+
+                if function_history.is_empty() {
+                    editor
+                } else {
+                    editor.push(
+                        pick_list(&function_history[..], None, Message::FunctionRecalled)
+                            .placeholder("History"),
+                    )
+                }
+            } else {
+                row![
+                    pick_list(
+                        &Waveform::ALL[..],
+                        Some(preset.waveform),
+                        Message::WaveformSelected
+                    ),
+                    text(preset).width(Length::Fill),
+                ]
+                .width(Length::Fill)
+                .spacing(10)
+            };
+
+            match validating {
+                Some(started) => {
+                    const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+                    #[allow(clippy::cast_possible_truncation)]
+                    let frame = (started.elapsed().as_millis() / 100 % 4) as usize;
+
+                    editor
+                        .push(text(SPINNER[frame]))
+                        .push(button("Cancel").on_press(Message::CancelValidation))
+                }
+                None => editor.push(button("Accept").on_press(Message::EvaluateFunction)),
+            }
+            .align_items(Alignment::Center)
+        };
+
+        let preview = match (preview, *validated) {
+            (Some(preview), _) => {
+                let summary = text(format!("Preview: {preview}")).size(14);
+
+                if preview.aliased() {
+                    column![
+                        summary,
+                        text(format!(
+                            "Warning: the signal extends to {:.1} Hz, beyond the Nyquist \
+                             frequency of {:.1} Hz at {} Hz, and will be aliased",
+                            preview.bandwidth,
+                            preview.nyquist,
+                            profiles[*selected_profile].max_sampling_rate
+                        ))
+                        .size(14)
+                        .style(Color::from_rgb(1.0, 0.6, 0.0)),
+                    ]
+                } else {
+                    column![summary]
+                }
+            }
+            (None, true) => {
+                column![text("Settings changed, accept the input again to preview it").size(14)]
+            }
+            (None, false) => column![],
+        };
+
+        let preset_editor = {
+            let profile = &profiles[*selected_profile];
+            let nyquist = profile.max_sampling_rate as f32 / 2.0;
+            let duration = samples.map_or(*stop_time, |samples| {
+                samples as f32 / profile.max_sampling_rate as f32
+            });
+
+            let mut settings = vec![setting(
+                "Amplitude",
+                slider(
+                    0.0..=profile.full_scale,
+                    preset.amplitude,
+                    Message::AmplitudeUpdated,
+                )
+                .step(0.01f32),
+                format!("{:.2} {}", preset.amplitude, profile.unit),
+            )];
+
+            if preset.waveform.is_periodic() || preset.waveform == Waveform::Chirp {
+                settings.push(setting(
+                    if preset.waveform == Waveform::Chirp {
+                        "Start frequency"
+                    } else {
+                        "Frequency"
+                    },
+                    slider(0.5..=nyquist, preset.frequency, Message::FrequencyUpdated).step(0.5f32),
+                    format!("{:.1} Hz", preset.frequency),
+                ));
+            }
+
+            if preset.waveform == Waveform::Chirp {
+                settings.push(setting(
+                    "End frequency",
+                    slider(
+                        0.5..=nyquist,
+                        preset.end_frequency,
+                        Message::EndFrequencyUpdated,
+                    )
+                    .step(0.5f32),
+                    format!("{:.1} Hz", preset.end_frequency),
+                ));
+            }
+
+            if preset.waveform.is_delayed() {
+                settings.push(setting(
+                    "Delay",
+                    slider(0.0..=duration, preset.delay, Message::DelayUpdated).step(0.01f32),
+                    format!("{:.2} s", preset.delay),
+                ));
+            }
+
+            if *advanced {
+                column![]
+            } else {
+                column(settings).spacing(5)
+            }
+        };
+
+        let normalization_editor = {
+            let toggle = checkbox(
+                "Normalize peak to",
+                normalization.is_some(),
+                Message::NormalizationToggled,
+            );
+
+            let mut editor = row![toggle].spacing(10);
+
+            if let Some(percent) = normalization {
+                editor = editor
+                    .push(
+                        slider(1.0f32..=100.0f32, *percent, Message::NormalizationUpdated)
+                            .step(1.0f32),
+                    )
+                    .push(text(format!("{percent:.0} % of full scale")));
+            }
+
+            editor.width(Length::Fill)
+        };
+
+        let transmission_editor = {
+            let pre_trigger =
+                u16::try_from(transmission.pre_trigger.as_millis()).unwrap_or(u16::MAX);
+
+            let gap = u16::try_from(transmission.gap.as_micros()).unwrap_or(u16::MAX);
+            let chunk = transmission.chunk;
+            let repeat = transmission.repeat;
+            let retries = transmission.retries;
+            let stall = u16::try_from(transmission.stall.as_millis()).unwrap_or(u16::MAX);
+            let handshakes = transmission.handshakes;
+
+            column![
+                row![
+                    text(format!("Pre-trigger: {pre_trigger} ms")).width(Length::FillPortion(1)),
+                    slider(0..=2000, pre_trigger, Message::PreTriggerUpdated)
+                        .step(50)
+                        .width(Length::FillPortion(3)),
+                ],
+                row![
+                    text(if gap == 0 {
+                        "Gap: none".to_owned()
+                    } else {
+                        format!("Gap: {gap} µs")
+                    })
+                    .width(Length::FillPortion(1)),
+                    slider(0..=10_000, gap, Message::GapUpdated)
+                        .step(50)
+                        .width(Length::FillPortion(3)),
+                ],
+                row![
+                    text(format!("Gap every {chunk} samples")).width(Length::FillPortion(1)),
+                    slider(1..=256, chunk, Message::ChunkUpdated).width(Length::FillPortion(3)),
+                ],
+                row![
+                    text(if repeat == 1 {
+                        "Sent once".to_owned()
+                    } else {
+                        format!("Sent {repeat} times")
+                    })
+                    .width(Length::FillPortion(1)),
+                    slider(1..=100, repeat, Message::RepeatUpdated).width(Length::FillPortion(3)),
+                ],
+                checkbox(
+                    "Synchronize clocks (requires device support)",
+                    transmission.sync,
+                    Message::SyncToggled
+                ),
+                row![
+                    text(format!("Retry {retries} read timeouts")).width(Length::FillPortion(1)),
+                    slider(0..=1000, retries, Message::RetriesUpdated)
+                        .step(10)
+                        .width(Length::FillPortion(3)),
+                ],
+                row![
+                    text(format!(
+                        "Give up after {:.1} s of silence",
+                        f32::from(stall) / 1e3
+                    ))
+                    .width(Length::FillPortion(1)),
+                    slider(100..=30_000, stall, Message::StallUpdated)
+                        .step(100)
+                        .width(Length::FillPortion(3)),
+                ],
+                row![
+                    text(if handshakes == 1 {
+                        "Connect in a single attempt".to_owned()
+                    } else {
+                        format!("Connect in up to {handshakes} attempts")
+                    })
+                    .width(Length::FillPortion(1)),
+                    slider(1..=10, handshakes, Message::HandshakesUpdated)
+                        .width(Length::FillPortion(3)),
+                ],
+                row![
+                    text("Protocol").width(Length::FillPortion(1)),
+                    pick_list(
+                        &Protocol::ALL[..],
+                        Some(transmission.protocol),
+                        Message::ProtocolSelected
+                    )
+                    .width(Length::FillPortion(3)),
+                ],
+                checkbox(
+                    "Keep the connection open between runs",
+                    transmission.keep_open,
+                    Message::KeepOpenToggled
+                ),
+            ]
+            .spacing(5)
+            .width(Length::Fill)
+        };
+
+        let outlets_editor = {
+            let toggle = checkbox(
+                "Publish outputs over OSC",
+                outlets.osc.is_some(),
+                Message::OscToggled,
+            );
+
+            let editor = match &outlets.osc {
+                Some(osc) => column![
+                    row![
+                        toggle,
+                        text_input(crate::DEFAULT_OSC_TARGET, &osc.target)
+                            .on_input(Message::OscTargetUpdated),
+                    ]
+                    .spacing(10),
+                    row![
+                        text(format!("Every {} outputs", osc.decimation))
+                            .width(Length::FillPortion(1)),
+                        slider(1..=100, osc.decimation, Message::OscDecimationUpdated)
+                            .width(Length::FillPortion(3)),
+                    ],
+                ],
+                None => column![toggle],
+            };
 
-        let stop_time_slider =
-            slider(1.0f32..=30.0f32, *stop_time, Message::StopTimeUpdated).step(0.5f32);
+            if cfg!(feature = "lsl") {
+                editor.push(checkbox(
+                    "Stream outputs over LSL",
+                    outlets.lsl,
+                    Message::LslToggled,
+                ))
+            } else {
+                editor
+            }
+            .spacing(5)
+            .width(Length::Fill)
+        };
 
-        let function_editor = row![
-            text_input("...", function)
-                .on_input(Message::FunctionUpdated)
-                .on_submit(Message::EvaluateFunction),
-            button("Accept").on_press(Message::EvaluateFunction),
+        let python_editor = {
+            let mut embedded = button("Use embedded");
+            if python.is_some() {
+                embedded = embedded.on_press(Message::EmbeddedInterpreter);
+            }
+
+            let interpreter = match python {
+                Some(python) => python.display().to_string(),
+                None => "Embedded interpreter".to_owned(),
+            };
+
+            row![
+                text(if *python_changed {
+                    format!("{interpreter}, applied on restart")
+                } else {
+                    interpreter
+                })
+                .width(Length::Fill),
+                button("Choose...").on_press(Message::ChooseInterpreter),
+                embedded,
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        };
+
+        let seed_editor = row![
+            text_input("0", seed_text).on_input(Message::SeedUpdated),
+            button("Randomize").on_press(Message::RandomizeSeed),
         ]
         .width(Length::Fill)
         .spacing(10);
@@ -127,22 +1314,176 @@ impl Ports {
             let ports: Element<'_, _> = if available_ports.is_empty() {
                 text("No ports found").into()
             } else {
+                let selected = available_ports
+                    .iter()
+                    .position(|port| Some(identity(port)) == selected_port.as_deref());
+
                 let radios = available_ports
                     .iter()
                     .enumerate()
-                    .map(|(i, SerialPortInfo { port_name, .. })| {
-                        radio(port_name, i, *selected_port, Message::PortSelected)
-                            .width(Length::Fill)
-                            .into()
+                    .map(|(i, port)| {
+                        let id = identity(port).to_owned();
+                        radio(&port.port_name, i, selected, move |_| {
+                            Message::PortSelected(id)
+                        })
+                        .width(Length::Fill)
+                        .into()
                     })
                     .collect();
 
                 column(radios).width(Length::Fill).spacing(10).into()
             };
 
-            column![header, scrollable(ports)].spacing(5)
+            let profile = row![
+                text("Device profile"),
+                horizontal_space(Length::Fill),
+                pick_list(
+                    profiles
+                        .iter()
+                        .map(|profile| profile.name.clone())
+                        .collect::<Vec<_>>(),
+                    Some(profiles[*selected_profile].name.clone()),
+                    Message::ProfileSelected,
+                ),
+            ]
+            .width(Length::Fill);
+
+            let clamp = checkbox(
+                format!(
+                    "Clamp inputs to ±{} {}",
+                    profiles[*selected_profile].full_scale, profiles[*selected_profile].unit
+                ),
+                *overrange == Overrange::Clamp,
+                Message::ClampToggled,
+            );
+
+            let emulator = row![
+                text("Emulated device"),
+                horizontal_space(Length::Fill),
+                pick_list(&Device::ALL[..], *emulated_device, Message::DeviceSelected),
+            ]
+            .width(Length::Fill);
+
+            let network = row![
+                checkbox("Network device", *networked, Message::NetworkToggled),
+                horizontal_space(Length::Fill),
+                text_input("host:port", network_address)
+                    .on_input(Message::NetworkAddressUpdated)
+                    .width(Length::Fixed(200.0)),
+            ]
+            .width(Length::Fill)
+            .align_items(Alignment::Center);
+
+            if emulated_device.is_some() {
+                let resolution = artifacts.resolution.unwrap_or(0);
+                let artifacts = column![
+                    row![
+                        text(if resolution == 0 {
+                            "ADC: ideal".to_owned()
+                        } else {
+                            format!("ADC: {resolution} bits")
+                        })
+                        .width(Length::FillPortion(1)),
+                        slider(0..=24, resolution, Message::ResolutionUpdated)
+                            .width(Length::FillPortion(3)),
+                    ],
+                    row![
+                        text(format!("Offset: {:.2}", artifacts.offset))
+                            .width(Length::FillPortion(1)),
+                        slider(-1.0f32..=1.0f32, artifacts.offset, Message::OffsetUpdated)
+                            .step(0.01f32)
+                            .width(Length::FillPortion(3)),
+                    ],
+                    row![
+                        text(format!("Noise RMS: {:.3}", artifacts.noise))
+                            .width(Length::FillPortion(1)),
+                        slider(0.0f32..=0.5f32, artifacts.noise, Message::NoiseUpdated)
+                            .step(0.005f32)
+                            .width(Length::FillPortion(3)),
+                    ],
+                ]
+                .spacing(5);
+
+                column![
+                    header,
+                    scrollable(ports),
+                    profile,
+                    clamp,
+                    network,
+                    emulator,
+                    artifacts
+                ]
+                .spacing(5)
+            } else {
+                column![header, scrollable(ports), profile, clamp, network, emulator].spacing(5)
+            }
         };
 
+        let recent = {
+            let sessions: Element<'_, _> = if recent_sessions.is_empty() {
+                text("No previous runs").into()
+            } else {
+                let buttons = recent_sessions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, session)| {
+                        let length = match session.samples {
+                            Some(samples) => format!("{samples} samples"),
+                            None => format!("{:.2}s", session.stop_time),
+                        };
+
+                        row![
+                            button(text(format!(
+                                "{} on {} [{length}, seed {}]",
+                                session.function, session.port, session.seed
+                            )))
+                            .width(Length::Fill)
+                            .on_press(Message::RecallSession(i)),
+                            checkbox("Compare", compared.contains(&i), move |_| {
+                                Message::CompareToggled(i)
+                            }),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .into()
+                    })
+                    .collect();
+
+                column(buttons).width(Length::Fill).spacing(5).into()
+            };
+
+            let files = recent_files
+                .iter()
+                .map(|path| {
+                    button(text(path.display()).size(14))
+                        .width(Length::Fill)
+                        .on_press(Message::FilePicked(Some(path.clone())))
+                        .into()
+                })
+                .collect();
+
+            let recent = column![
+                row![
+                    text("Recent"),
+                    horizontal_space(Length::Fill),
+                    button("Open file...").on_press(Message::OpenFile),
+                ]
+                .width(Length::Fill),
+                scrollable(sessions),
+                column(files).spacing(2)
+            ]
+            .spacing(5);
+
+            match compared[..] {
+                [a, b] => recent.push(comparison(&recent_sessions[a], &recent_sessions[b])),
+                _ => recent,
+            }
+        };
+
+        let has_target = selected_port.is_some()
+            || emulated_device.is_some()
+            || (*networked && !network_address.trim().is_empty());
+
         let mut filter = button(
             text("Start filtering")
                 .width(Length::Fill)
@@ -150,24 +1491,110 @@ impl Ports {
         )
         .width(Length::Fill);
 
-        if selected_port.is_some() && *validated {
+        // Runs of a given length need a positive count of samples
+        let counted = samples.is_none()
+            || samples_text
+                .parse::<u32>()
+                .map_or(false, |samples| samples > 0);
+
+        // Soak tests end with the configuration screen
+        if has_target
+            && *validated
+            && seed_text.parse::<u32>().is_ok()
+            && counted
+            && !matches!(self_test, Some(SelfTest::Soaking(..)))
+        {
             filter = filter.on_press(Message::Filter);
         }
 
+        let self_test = {
+            let running = matches!(self_test, Some(SelfTest::Running | SelfTest::Soaking(..)));
+
+            let mut start = button("Self-test");
+            if has_target && !running {
+                start = start.on_press(Message::SelfTest);
+            }
+
+            let mut loopback = button("Loopback test");
+            if selected_port.is_some() && !running {
+                loopback = loopback.on_press(Message::LoopbackTest);
+            }
+
+            let soak = match self_test {
+                Some(SelfTest::Soaking(..)) => {
+                    button("Stop soak test").on_press(Message::StopSoakTest)
+                }
+                _ if has_target && !running => button("Soak test").on_press(Message::SoakTest),
+                _ => button("Soak test"),
+            };
+
+            let hours = row![
+                slider(1..=24, *soak_hours, Message::SoakHoursUpdated).width(Length::Fixed(100.0)),
+                text(format!("{soak_hours} h")),
+            ]
+            .spacing(5)
+            .align_items(Alignment::Center);
+
+            let status = match self_test {
+                None => "Echoes a known pattern through a device in pass-through mode, \
+                         or through a loopback plug, or verifies a device's outputs for hours"
+                    .to_owned(),
+                Some(SelfTest::Running) => "Test running...".to_owned(),
+                Some(SelfTest::Finished(report)) => format!("Self-test {report}"),
+                Some(SelfTest::LoopedBack(loopback)) => loopback.to_string(),
+                Some(SelfTest::Soaking(progress, started)) => {
+                    let (deviations, latest) = progress.deviations();
+                    let latest =
+                        latest.map_or_else(String::new, |latest| format!(", latest at {latest}"));
+
+                    format!(
+                        "Soak test at {}: {} periods verified, {deviations} deviations{latest}",
+                        soak::clock(started.elapsed()),
+                        progress.verified()
+                    )
+                }
+                Some(SelfTest::Soaked(summary)) => format!("Soak test {summary}"),
+                Some(SelfTest::Failed(e)) => format!("Test error: {e}"),
+            };
+
+            row![start, loopback, soak, hours, text(status)]
+                .spacing(10)
+                .align_items(Alignment::Center)
+        };
+
         let content: Element<'_, Message> = column![
             title,
+            templates,
             column![
-                column![text("f(t)").size(24), function_editor].spacing(10),
                 column![
-                    text(format!("Stop time [{stop_time:.2}]")).size(24),
-                    stop_time_slider,
+                    text("Input").size(24),
+                    mode_selector,
+                    function_editor,
+                    preset_editor,
+                    preview
+                ]
+                .spacing(10),
+                column![
+                    text(match samples {
+                        Some(samples) => format!("Length [{samples} samples]"),
+                        None => format!("Stop time [{stop_time:.2}]"),
+                    })
+                    .size(24),
+                    length_editor,
+                    time_base_editor,
                 ]
                 .spacing(10),
+                column![text("Seed").size(24), seed_editor].spacing(10),
+                column![text("Amplitude").size(24), normalization_editor].spacing(10),
+                column![text("Transmission").size(24), transmission_editor].spacing(10),
+                column![text("Outlets").size(24), outlets_editor].spacing(10),
+                column![text("Python").size(24), python_editor].spacing(10),
             ]
             .spacing(15),
             ports,
+            recent,
             vertical_space(Length::Fill),
-            filter
+            column![self_test, filter].spacing(10)
         ]
         .padding(15)
         .spacing(60)
@@ -176,61 +1603,261 @@ impl Ports {
         content.map(App)
     }
 
-    #[allow(clippy::unused_self)]
+    /// Refreshes ports as devices are plugged and unplugged, polling where unsupported, and
+    /// animates validations and soak tests in flight
     pub fn subscription(&self) -> Subscription<super::Message> {
-        use iced::time::{self, Duration};
+        use iced::{futures::StreamExt, subscription, time};
 
-        time::every(Duration::from_secs(3)).map(|_| App(Message::RefreshPorts))
+        let spinner = if self.validating.is_some() {
+            time::every(Duration::from_millis(100)).map(|_| App(Message::Spin))
+        } else if matches!(self.self_test, Some(SelfTest::Soaking(..))) {
+            time::every(Duration::from_secs(1)).map(|_| App(Message::Spin))
+        } else {
+            Subscription::none()
+        };
+
+        let hotplug = subscription::unfold("hotplug", Hotplug::Starting, |hotplug| async move {
+            let hotplug = match hotplug {
+                Hotplug::Starting => match nusb::watch_devices() {
+                    Ok(watch) => Hotplug::Watching(watch),
+                    Err(e) => {
+                        tracing::warn!("Hotplug events unavailable, polling ports: {e}");
+                        Hotplug::Polling
+                    }
+                },
+
+                Hotplug::Watching(mut watch) => {
+                    if watch.next().await.is_some() {
+                        // Serial ports are registered shortly after their device
+                        tokio::time::sleep(Duration::from_millis(crate::HOTPLUG_SETTLE)).await;
+                        Hotplug::Watching(watch)
+                    } else {
+                        tracing::warn!("Hotplug events ended, polling ports");
+                        Hotplug::Polling
+                    }
+                }
+
+                Hotplug::Polling => {
+                    tokio::time::sleep(Duration::from_secs(crate::PORT_POLLING_PERIOD)).await;
+                    Hotplug::Polling
+                }
+            };
+
+            (App(Message::RefreshPorts), hotplug)
+        });
+
+        Subscription::batch([hotplug, spinner])
     }
 
-    fn update_ports(&mut self, mut ports: Vec<SerialPortInfo>) {
-        if ports.is_empty() {
-            self.selected_port = None;
-            self.available_ports.clear();
-            return;
+    /// Selected port, networked or emulated device
+    fn target(&self) -> Target {
+        match self.emulated_device {
+            Some(device) => Target::Emulated(device, self.artifacts),
+            None if self.networked => Target::Tcp(self.network_address.trim().to_owned()),
+            None => Target::Serial(self.port().expect("selected port").port_name.clone()),
         }
+    }
 
-        let port_disconnected = self
-            .selected_port
-            .and_then(|i| self.available_ports.get(i))
-            .map_or(false, |port| !ports.contains(port));
+    /// Selected port, if still available
+    fn port(&self) -> Option<&SerialPortInfo> {
+        let selected = self.selected_port.as_deref()?;
+        self.available_ports
+            .iter()
+            .find(|port| identity(port) == selected)
+    }
+
+    /// Known ports keep their position, disconnected ones are removed and new ones appended,
+    /// with the selection following its device
+    fn update_ports(&mut self, ports: Vec<SerialPortInfo>) {
+        let position = |port: &SerialPortInfo| {
+            self.available_ports
+                .iter()
+                .position(|known| identity(known) == identity(port))
+        };
+
+        let (mut known, new): (Vec<_>, Vec<_>) =
+            ports.into_iter().partition(|port| position(port).is_some());
+        known.sort_by_key(position);
+        known.extend(new);
 
-        if port_disconnected {
+        self.available_ports = known;
+        if self.port().is_none() {
             self.selected_port = None;
         }
+    }
 
-        // Retain new ports only
-        ports.retain(|port| !self.available_ports.contains(port));
+    /// Parameters of the input, from [`Self::function`] or [`Self::preset`]
+    fn parameters(&self) -> Parameters {
+        Parameters {
+            function: if self.advanced {
+                self.function.clone()
+            } else {
+                self.preset.to_string()
+            },
+            preset: (!self.advanced).then_some(self.preset),
+            stop_time: self.stop_time,
+            samples: self.samples,
+            seed: self.seed,
+            overrange: self.overrange,
+            normalization: self.normalization,
+            time_base: self.time_base,
+        }
+    }
 
-        self.available_ports.extend(ports);
+    /// Discards the validation of the input, which changed, and any in flight
+    fn invalidate(&mut self) {
+        self.validated = false;
+        self.preview = None;
+        self.abandon();
     }
 
-    fn validate(&mut self) {
-        let Self {
-            function,
-            validated,
-            ..
-        } = self;
+    /// Supersedes the validation in flight, if any, interrupting its evaluation so that it
+    /// doesn't hold the GIL for those to come
+    fn abandon(&mut self) {
+        if self.validating.take().is_some() {
+            let validation = self.validation;
+            // Waits for the GIL, which the evaluation holds
+            thread::spawn(move || signal::interrupt(validation));
+        }
 
-        let result = Python::with_gil(|py| -> PyResult<_> {
-            let numpy = py.import("numpy")?;
-            let locals = crate::NUMPY_IMPORTS
-                .iter()
-                .map(|&member| (member, numpy.getattr(member).expect("valid member")))
-                .into_py_dict(py);
+        self.validation = signal::task();
+    }
 
-            locals.set_item("np", numpy)?;
-            locals.set_item("t", py.eval("np.array([0])", None, Some(locals))?)?;
+    /// Evaluates the input in the background, superseding any validation in flight
+    ///
+    /// Once valid, the whole input is generated at the profile's maximum sampling rate and
+    /// normalized as it would be for transmission, to preview its statistics. Its bandwidth is
+    /// estimated by oversampling, to warn about aliasing
+    fn validate(&mut self) -> Command<super::Message> {
+        self.validated = false;
+        self.abandon();
+        self.validating = Some(Instant::now());
+        self.preview = None;
 
-            py.eval(function, None, Some(locals)).map(|_| ())
-        });
+        let validation = self.validation;
+        let profile = &self.profiles[self.selected_profile];
+        let sampling_interval = (profile.max_sampling_rate as f32).recip();
+        let peak = self
+            .normalization
+            .map(|percent| percent / 100.0 * profile.full_scale);
+        let parameters = self.parameters();
 
-        if let Err(e) = result {
-            tracing::error!("Evaluation failed: {e}");
-            *validated = false;
-        } else {
-            tracing::info!("Evaluation successful");
-            *validated = true;
+        let future = async move {
+            tokio::task::spawn_blocking(move || {
+                signal::interruptible(validation, || -> Result<_, String> {
+                    signal::validate(&parameters)?;
+
+                    let (_, mut samples) = signal::evaluate(&parameters, sampling_interval)?;
+                    if let Some(peak) = peak {
+                        signal::normalize(&mut samples, peak);
+                    }
+
+                    let bandwidth = signal::estimate_bandwidth(&parameters, sampling_interval)?;
+                    Ok(Summary::new(&samples, sampling_interval, bandwidth))
+                })
+            })
+            .await
+            .expect("blocking task ran")
+        };
+
+        Command::perform(future, move |result| Message::Validated(validation, result)).map(App)
+    }
+}
+
+/// Soak tests hold the port for hours, so they end with the configuration screen, however it's
+/// left
+impl Drop for Ports {
+    fn drop(&mut self) {
+        if let Some(SelfTest::Soaking(progress, _)) = &self.self_test {
+            tracing::info!("Stopping the soak test");
+            progress.stop();
         }
+
+        self.abandon();
     }
 }
+
+/// Identity of a port's device: its USB serial number if any, surviving re-enumeration under
+/// another name, or else the port's name
+fn identity(port: &SerialPortInfo) -> &str {
+    match &port.port_type {
+        SerialPortType::UsbPort(UsbPortInfo {
+            serial_number: Some(serial_number),
+            ..
+        }) => serial_number,
+        _ => &port.port_name,
+    }
+}
+
+/// Preset setting adjusted with `editor`, labelled and showing its `value`
+fn setting<'a>(
+    label: &'a str,
+    editor: Slider<'a, f32, Message>,
+    value: String,
+) -> Element<'a, Message> {
+    row![
+        text(label).width(Length::FillPortion(1)),
+        editor.width(Length::FillPortion(4)),
+        text(value).width(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+/// Settings of two sessions side by side, highlighting those that differ
+fn comparison<'a>(a: &Session, b: &Session) -> Element<'a, Message> {
+    let highlight = Color::from_rgb(1.0, 0.6, 0.0);
+    let fields = fields(a).into_iter().zip(fields(b));
+    let differences = fields.clone().filter(|((_, a), (_, b))| a != b).count();
+
+    let rows = fields
+        .map(|((label, a), (_, b))| {
+            let cell = |value| {
+                let cell = text(value).size(14).width(Length::FillPortion(2));
+                if a == b {
+                    cell
+                } else {
+                    cell.style(highlight)
+                }
+            };
+
+            row![
+                text(label).size(14).width(Length::FillPortion(1)),
+                cell(&a),
+                cell(&b)
+            ]
+            .spacing(10)
+            .into()
+        })
+        .collect();
+
+    column![
+        text(match differences {
+            0 => "Compared sessions are identical".to_owned(),
+            1 => "1 setting differs".to_owned(),
+            n => format!("{n} settings differ"),
+        }),
+        column(rows).spacing(2),
+    ]
+    .spacing(5)
+    .into()
+}
+
+/// [`Session::fields`], with the coefficients of emulated devices
+fn fields(session: &Session) -> Vec<(&'static str, String)> {
+    let coefficients = Device::ALL
+        .into_iter()
+        .find(|device| Target::Emulated(*device, Artifacts::NONE).to_string() == session.port)
+        .map_or_else(
+            || "unknown".to_owned(),
+            |device| {
+                let coefficients = device.coefficients();
+                format!("b = {:.4?}, a = {:.4?}", coefficients.b, coefficients.a)
+            },
+        );
+
+    let mut fields = session.fields();
+    fields.insert(1, ("Coefficients", coefficients));
+    fields
+}
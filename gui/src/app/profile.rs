@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use std::{fs, io, ops::Range, path::PathBuf};
+
+/// Capabilities of a filtering device
+///
+/// Loaded from `*.toml` files in the profiles directory, e.g.
+///
+/// ```toml
+/// name = "Arduino Due"
+/// full_scale = 3.3
+/// unit = "V"
+/// sample_formats = ["f32"]
+/// max_sampling_rate = 1000
+/// channels = 1
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    /// Inputs are accepted within `-full_scale..=full_scale`
+    pub full_scale: f32,
+    /// Unit of sample values
+    pub unit: String,
+    pub sample_formats: Vec<SampleFormat>,
+    /// In Hz
+    pub max_sampling_rate: u32,
+    pub channels: u8,
+}
+
+/// Sample encoding on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleFormat {
+    F32,
+    I16,
+    I32,
+}
+
+impl Default for Profile {
+    /// Matches the reference firmware
+    fn default() -> Self {
+        Self {
+            name: "Default".to_owned(),
+            full_scale: 5.0,
+            unit: "V".to_owned(),
+            sample_formats: vec![SampleFormat::F32],
+            max_sampling_rate: 1000,
+            channels: 1,
+        }
+    }
+}
+
+impl Profile {
+    /// The default profile, followed by those in the profiles directory
+    pub fn load_all() -> Vec<Self> {
+        let mut profiles = vec![Self::default()];
+
+        let Some(directory) = directory() else {
+            return profiles;
+        };
+
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return profiles,
+            Err(e) => {
+                tracing::error!("Unable to read profiles in {}: {e}", directory.display());
+                return profiles;
+            }
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "toml")
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()))
+            {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => tracing::error!("Invalid profile {}: {e}", path.display()),
+            }
+        }
+
+        profiles
+    }
+
+    /// Range of values the device accepts
+    pub fn range(&self) -> Range<f32> {
+        -self.full_scale..self.full_scale
+    }
+
+    /// Checks the app can talk to a device reporting `sampling_frequency`
+    ///
+    /// # Errors
+    /// Fails if the device doesn't accept `f32` samples or samples faster than its profile allows
+    pub fn check(&self, sampling_frequency: u32) -> io::Result<()> {
+        if !self.sample_formats.contains(&SampleFormat::F32) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("profile `{}` doesn't support f32 samples", self.name),
+            ));
+        }
+
+        if sampling_frequency > self.max_sampling_rate {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "sampling frequency {sampling_frequency} Hz exceeds the {} Hz of profile `{}`",
+                    self.max_sampling_rate, self.name
+                ),
+            ));
+        }
+
+        if self.channels > 1 {
+            tracing::warn!("Only the first of {} channels is used", self.channels);
+        }
+
+        Ok(())
+    }
+}
+
+fn directory() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("online-filtering").join("profiles"))
+}
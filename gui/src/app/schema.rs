@@ -0,0 +1,52 @@
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
+
+/// Version of a file layout, independent of the application's
+///
+/// Minor versions only add to the layout, so readers ignoring what they don't know can read
+/// files of any minor version. Major versions change or remove parts of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Schema {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Schema {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Can files of this schema be read by a reader of `current`, possibly after migration?
+    pub const fn is_readable_by(self, current: Self) -> bool {
+        self.major <= current.major
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid schema {s:?}, expected MAJOR.MINOR");
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+
+        Ok(Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl TryFrom<String> for Schema {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
@@ -0,0 +1,393 @@
+use parking_lot::Mutex;
+use rustfft::{num_complex::Complex32, FftPlanner};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    mem,
+};
+
+pub mod native;
+mod preset;
+pub use preset::{Preset, Waveform};
+mod python;
+pub use python::{interrupt, interruptible, task, Environment};
+
+/// Parameters of the generated input signal
+#[derive(Debug, Clone)]
+pub struct Parameters {
+    /// Function to be evaluated
+    ///
+    /// Evaluated at uniform intervals between \[0, [`Self::stop_time`]\]
+    pub function: String,
+    /// Built-in signal generated instead of [`Self::function`], which then describes it
+    pub preset: Option<Preset>,
+    /// How long to simulate [`Self::function`] for
+    pub stop_time: f32,
+    /// Number of samples to generate, overriding [`Self::stop_time`]
+    pub samples: Option<u32>,
+    /// Seed of numpy's random number generators, recorded for reproducibility
+    pub seed: u32,
+    /// Handling of samples outside of the device's full scale
+    pub overrange: Overrange,
+    /// Percentage of the device's full scale to scale the input's peak to, if any
+    pub normalization: Option<f32>,
+    /// Convention of the time vector
+    pub time_base: TimeBase,
+}
+
+/// Where samples lie within the simulated time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeBase {
+    /// Span \[0, stop\] rather than \[0, stop), if the stop time falls on a sample
+    pub endpoint: bool,
+    /// Place samples at the center of their interval, `(i + 1/2) T`, rather than at its start
+    pub centered: bool,
+}
+
+/// Handling of generated samples outside of the device's full scale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Overrange {
+    /// Transmit them as is, warning about them
+    #[default]
+    Warn,
+    /// Clamp them to the full scale before transmission
+    Clamp,
+}
+
+/// Generated samples found outside of the device's full scale
+#[derive(Debug, Clone, Copy)]
+pub struct Excursion {
+    /// Number of samples outside of the full scale
+    pub count: usize,
+    /// Largest magnitude among them, before clamping
+    pub peak: f32,
+    /// Were they clamped?
+    pub clamped: bool,
+}
+
+/// Statistics of a generated input, previewed before connecting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// Number of samples
+    pub samples: usize,
+    /// Number of NaN or infinite samples, excluded from the other statistics
+    pub non_finite: usize,
+    pub min: f32,
+    pub max: f32,
+    pub rms: f32,
+    /// Frequency below which [`BANDWIDTH_POWER`] of the power lies, in Hz
+    pub bandwidth: f32,
+    /// Half the sampling frequency, in Hz
+    pub nyquist: f32,
+    /// Bytes taken by the time and input vectors
+    pub memory: usize,
+}
+
+/// Fraction of a signal's power within its estimated bandwidth
+const BANDWIDTH_POWER: f32 = 0.99;
+
+impl Summary {
+    /// Statistics of `samples`, with their `bandwidth` if estimated with
+    /// [`estimate_bandwidth`], or else as sampled
+    pub fn new(samples: &[f32], sampling_interval: f32, bandwidth: Option<f32>) -> Self {
+        let finite: Vec<_> = samples.iter().copied().filter(|x| x.is_finite()).collect();
+        let (min, max) = finite
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &x| {
+                (min.min(x), max.max(x))
+            });
+        let rms = (finite.iter().map(|x| x * x).sum::<f32>() / finite.len().max(1) as f32).sqrt();
+
+        Self {
+            samples: samples.len(),
+            non_finite: samples.len() - finite.len(),
+            min: if finite.is_empty() { 0.0 } else { min },
+            max: if finite.is_empty() { 0.0 } else { max },
+            rms,
+            bandwidth: bandwidth.unwrap_or_else(|| self::bandwidth(&finite, sampling_interval)),
+            nyquist: 0.5 / sampling_interval,
+            memory: 2 * samples.len() * mem::size_of::<f32>(),
+        }
+    }
+
+    /// Does the input have content beyond the Nyquist frequency, which will be aliased?
+    pub fn aliased(&self) -> bool {
+        self.bandwidth > self.nyquist
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let memory = self.memory as f32 / 1024.0;
+        let memory = if memory < 1024.0 {
+            format!("{memory:.1} KiB")
+        } else {
+            format!("{:.1} MiB", memory / 1024.0)
+        };
+
+        write!(
+            f,
+            "{} samples ({memory}), range [{:.4}, {:.4}], RMS {:.4}, {:.0}% of the power \
+             below {:.1} Hz",
+            self.samples,
+            self.min,
+            self.max,
+            self.rms,
+            BANDWIDTH_POWER * 100.0,
+            self.bandwidth
+        )?;
+
+        if self.non_finite > 0 {
+            write!(f, ", {} non-finite samples", self.non_finite)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Frequency below which [`BANDWIDTH_POWER`] of the power of `samples` lies, in Hz, ignoring
+/// their mean
+fn bandwidth(samples: &[f32], sampling_interval: f32) -> f32 {
+    let n = samples.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mean = samples.iter().sum::<f32>() / n as f32;
+    let mut spectrum: Vec<_> = samples
+        .iter()
+        .map(|&x| Complex32::new(x - mean, 0.0))
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+
+    // One-sided power, the mirrored negative frequencies having the same magnitude
+    let power: Vec<_> = spectrum[1..=n / 2]
+        .iter()
+        .map(|bin| bin.norm_sqr())
+        .collect();
+    let total: f32 = power.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    let mut cumulative = 0.0;
+    let bin = power
+        .iter()
+        .position(|p| {
+            cumulative += p;
+            cumulative >= BANDWIDTH_POWER * total
+        })
+        .unwrap_or(power.len() - 1);
+
+    (bin + 1) as f32 / (n as f32 * sampling_interval)
+}
+
+/// Scales `samples` so their peak magnitude is `peak`, returning the applied gain
+///
+/// Non-finite samples are ignored, and silent signals are left untouched
+pub fn normalize(samples: &mut [f32], peak: f32) -> f32 {
+    let current = samples
+        .iter()
+        .copied()
+        .filter(|sample| sample.is_finite())
+        .fold(0f32, |peak, sample| peak.max(sample.abs()));
+
+    if current == 0.0 {
+        return 1.0;
+    }
+
+    let gain = peak / current;
+    samples.iter_mut().for_each(|sample| *sample *= gain);
+
+    tracing::info!("Normalized input by a gain of {gain}");
+    gain
+}
+
+/// Checks `samples` against `-full_scale..=full_scale`, handling excursions per `overrange`
+pub fn limit(samples: &mut [f32], full_scale: f32, overrange: Overrange) -> Option<Excursion> {
+    let mut count = 0;
+    let mut peak = 0f32;
+
+    for sample in samples
+        .iter_mut()
+        .filter(|sample| sample.abs() > full_scale)
+    {
+        count += 1;
+        peak = peak.max(sample.abs());
+
+        if overrange == Overrange::Clamp {
+            *sample = sample.clamp(-full_scale, full_scale);
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    tracing::warn!("{count} samples exceed the full scale of {full_scale} (peak {peak})");
+
+    Some(Excursion {
+        count,
+        peak,
+        clamped: overrange == Overrange::Clamp,
+    })
+}
+
+/// Repeats `samples` back to back `count` times
+pub fn repeat(samples: &mut Vec<f32>, count: u16) {
+    let cycle = samples.len();
+    *samples = samples.repeat(count.max(1).into());
+
+    if count > 1 {
+        tracing::info!("Repeating {cycle} samples {count} times");
+    }
+}
+
+/// Pads `samples` with zeros up to a multiple of `block` samples
+pub fn pad(samples: &mut Vec<f32>, block: usize) {
+    let padding = (block - samples.len() % block) % block;
+    if padding == 0 {
+        return;
+    }
+
+    samples.resize(samples.len() + padding, 0.0);
+
+    tracing::info!("Padded input with {padding} zeros to a whole number of blocks");
+}
+
+/// Picks a fresh seed for [`Parameters::seed`]
+pub fn random_seed() -> u32 {
+    let hash = RandomState::new().build_hasher().finish();
+
+    #[allow(clippy::cast_possible_truncation)]
+    let seed = hash as u32;
+    seed
+}
+
+/// Checks that [`Parameters::function`] evaluates for a single sample
+///
+/// Functions are evaluated by numpy, or by the [`native`] evaluator where it's unavailable.
+/// Presets always are
+pub fn validate(parameters: &Parameters) -> Result<(), String> {
+    let Parameters { function, seed, .. } = parameters;
+
+    if parameters.preset.is_some() {
+        Ok(())
+    } else if python::available() {
+        python::validate(function, *seed).map_err(|e| e.to_string())
+    } else {
+        native::validate(function, *seed)
+    }
+}
+
+/// Parameters of generated vectors, with floats compared bitwise
+#[derive(Debug, PartialEq, Eq)]
+struct Key {
+    function: String,
+    /// Description of the preset, identifying it
+    preset: Option<String>,
+    stop_time: u32,
+    samples: Option<u32>,
+    seed: u32,
+    time_base: TimeBase,
+    sampling_interval: u32,
+}
+
+impl Key {
+    fn new(parameters: &Parameters, sampling_interval: f32) -> Self {
+        Self {
+            function: parameters.function.clone(),
+            preset: parameters.preset.as_ref().map(ToString::to_string),
+            stop_time: parameters.stop_time.to_bits(),
+            samples: parameters.samples,
+            seed: parameters.seed,
+            time_base: parameters.time_base,
+            sampling_interval: sampling_interval.to_bits(),
+        }
+    }
+}
+
+/// Time and input vectors of the last evaluation, reused by runs with identical parameters
+static LAST: Mutex<Option<(Key, (Vec<f32>, Vec<f32>))>> = parking_lot::const_mutex(None);
+
+/// Evaluates the time and input vectors for the given `sampling_interval`
+///
+/// Vectors are reused when the previous evaluation had the same parameters, sparing long
+/// signals from being regenerated between repeats of an experiment
+pub fn evaluate(
+    parameters: &Parameters,
+    sampling_interval: f32,
+) -> Result<(Vec<f32>, Vec<f32>), String> {
+    let key = Key::new(parameters, sampling_interval);
+    if let Some((last, tensors)) = &*crate::perf::lock(&LAST) {
+        if *last == key {
+            tracing::info!("Reusing the inputs of the previous run");
+            return Ok(tensors.clone());
+        }
+    }
+
+    if parameters.preset.is_none() && !python::available() {
+        tracing::info!("numpy unavailable, evaluating with built-in functions");
+    }
+    let tensors = generate(parameters, sampling_interval)?;
+
+    *crate::perf::lock(&LAST) = Some((key, tensors.clone()));
+    Ok(tensors)
+}
+
+/// Evaluates with numpy, or with the [`native`] evaluator where it's unavailable, generating
+/// presets natively
+fn generate(
+    parameters: &Parameters,
+    sampling_interval: f32,
+) -> Result<(Vec<f32>, Vec<f32>), String> {
+    if let Some(preset) = &parameters.preset {
+        Ok(preset::evaluate(preset, parameters, sampling_interval))
+    } else if python::available() {
+        python::evaluate(parameters, sampling_interval).map_err(|e| e.to_string())
+    } else {
+        native::evaluate(parameters, sampling_interval)
+    }
+}
+
+/// Bandwidth of the signal described by `parameters` in Hz, from a window evaluated at
+/// [`crate::BANDWIDTH_OVERSAMPLING`] times the rate of `sampling_interval`
+///
+/// Unlike that of the transmitted samples, it can exceed the Nyquist frequency, revealing
+/// aliasing. Random numbers are drawn per sample, and so are white at any rate: functions
+/// drawing them aren't estimated
+pub fn estimate_bandwidth(
+    parameters: &Parameters,
+    sampling_interval: f32,
+) -> Result<Option<f32>, String> {
+    let noise = match &parameters.preset {
+        Some(preset) => preset.waveform == Waveform::WhiteNoise,
+        None => ["random.", "rng."]
+            .iter()
+            .any(|module| parameters.function.contains(module)),
+    };
+
+    if noise {
+        return Ok(None);
+    }
+
+    let interval = sampling_interval / crate::BANDWIDTH_OVERSAMPLING as f32;
+    let duration = parameters.samples.map_or(parameters.stop_time, |samples| {
+        samples as f32 * sampling_interval
+    });
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let length = (duration / interval).round() as u32;
+    let window = Parameters {
+        samples: Some(length.clamp(2, crate::BANDWIDTH_WINDOW)),
+        ..parameters.clone()
+    };
+
+    let (_, samples) = generate(&window, interval)?;
+    let finite: Vec<_> = samples.into_iter().filter(|x| x.is_finite()).collect();
+
+    Ok(Some(bandwidth(&finite, interval)))
+}
@@ -0,0 +1,558 @@
+use std::{
+    f64::consts::{E, PI},
+    fmt,
+};
+
+use super::Parameters;
+
+/// Function of `t` compiled for elementwise evaluation, for environments without numpy
+///
+/// Supports the subset of numpy functions are usually written in: arithmetic, comparisons,
+/// elementwise functions, and the `normal` and `uniform` generators. Random samples are
+/// reproducible from the seed, but differ from numpy's
+#[derive(Debug)]
+struct Expression(Node);
+
+#[derive(Debug)]
+enum Node {
+    Constant(f64),
+    Time,
+    Unary(fn(f64) -> f64, Box<Node>),
+    Binary(fn(f64, f64) -> f64, Box<Node>, Box<Node>),
+    Ternary(fn(f64, f64, f64) -> f64, Box<[Node; 3]>),
+    /// Normal distribution of the given mean and standard deviation
+    Normal(Box<Node>, Box<Node>),
+    /// Uniform distribution over the given bounds
+    Uniform(Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    /// Dotted name, such as `np.sin`
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Name(name) => f.write_str(name),
+            Self::Symbol(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+/// Operators and punctuation, longest first so `**` isn't read as `*`
+const SYMBOLS: [&str; 15] = [
+    "**", "<=", ">=", "==", "!=", "+", "-", "*", "/", "%", "<", ">", "(", ")", ",",
+];
+
+impl Expression {
+    /// Parses `function`, resolving its names
+    fn parse(function: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(function)?,
+            position: 0,
+        };
+
+        let node = parser.comparison()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(Self(node)),
+            Some(token) => Err(format!("Unexpected `{token}`")),
+        }
+    }
+
+    /// Value at time `t`, drawing random samples from `rng`
+    fn value(&self, t: f64, rng: &mut Rng) -> f64 {
+        self.0.value(t, rng)
+    }
+}
+
+impl Node {
+    fn value(&self, t: f64, rng: &mut Rng) -> f64 {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Time => t,
+            Self::Unary(f, x) => f(x.value(t, rng)),
+            Self::Binary(f, a, b) => f(a.value(t, rng), b.value(t, rng)),
+            Self::Ternary(f, arguments) => f(
+                arguments[0].value(t, rng),
+                arguments[1].value(t, rng),
+                arguments[2].value(t, rng),
+            ),
+            Self::Normal(mean, deviation) => {
+                mean.value(t, rng) + deviation.value(t, rng) * rng.gaussian()
+            }
+            Self::Uniform(low, high) => {
+                let (low, high) = (low.value(t, rng), high.value(t, rng));
+                low + (high - low) * rng.uniform()
+            }
+        }
+    }
+}
+
+fn tokenize(function: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = function.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_digit()
+            || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let mut length = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+
+            // Exponent, as in `1e-3`
+            if rest[length..].starts_with(['e', 'E']) {
+                let exponent = &rest[length + 1..];
+                let sign = usize::from(exponent.starts_with(['+', '-']));
+                length += 1
+                    + sign
+                    + exponent[sign..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(exponent.len() - sign);
+            }
+
+            let number = &rest[..length];
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| format!("Invalid number {number}"))?,
+            ));
+            length
+        } else if c.is_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+
+            tokens.push(Token::Name(rest[..length].to_owned()));
+            length
+        } else if let Some(symbol) = SYMBOLS.iter().find(|&&symbol| rest.starts_with(symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else if c == '=' {
+            // Keyword arguments
+            tokens.push(Token::Symbol("="));
+            1
+        } else {
+            return Err(format!("Unexpected character {c:?}"));
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser, following Python's operator precedence
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consumes `symbol` if it's next
+    fn accept(&mut self, symbol: &str) -> bool {
+        let accepted = matches!(self.peek(), Some(Token::Symbol(next)) if *next == symbol);
+        if accepted {
+            self.position += 1;
+        }
+
+        accepted
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.accept(symbol) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(token) => Err(format!("Expected `{symbol}`, found `{token}`")),
+                None => Err(format!("Expected `{symbol}`")),
+            }
+        }
+    }
+
+    /// Comparisons evaluate to 1 or 0, as booleans do in arithmetic
+    #[allow(clippy::float_cmp)]
+    fn comparison(&mut self) -> Result<Node, String> {
+        let a = self.sum()?;
+
+        let f: fn(f64, f64) -> f64 = if self.accept("<") {
+            |a, b| f64::from(u8::from(a < b))
+        } else if self.accept("<=") {
+            |a, b| f64::from(u8::from(a <= b))
+        } else if self.accept(">") {
+            |a, b| f64::from(u8::from(a > b))
+        } else if self.accept(">=") {
+            |a, b| f64::from(u8::from(a >= b))
+        } else if self.accept("==") {
+            |a, b| f64::from(u8::from(a == b))
+        } else if self.accept("!=") {
+            |a, b| f64::from(u8::from(a != b))
+        } else {
+            return Ok(a);
+        };
+
+        Ok(Node::Binary(f, Box::new(a), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut node = self.product()?;
+
+        loop {
+            let f: fn(f64, f64) -> f64 = if self.accept("+") {
+                |a, b| a + b
+            } else if self.accept("-") {
+                |a, b| a - b
+            } else {
+                return Ok(node);
+            };
+
+            node = Node::Binary(f, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+
+        loop {
+            let f: fn(f64, f64) -> f64 = if self.accept("*") {
+                |a, b| a * b
+            } else if self.accept("/") {
+                |a, b| a / b
+            } else if self.accept("%") {
+                // Python's modulo takes the sign of the divisor
+                |a, b| a - b * (a / b).floor()
+            } else {
+                return Ok(node);
+            };
+
+            node = Node::Binary(f, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.accept("-") {
+            Ok(Node::Unary(|x| -x, Box::new(self.unary()?)))
+        } else if self.accept("+") {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    /// Right-associative, and binding tighter than a unary operator on its left
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.atom()?;
+
+        if self.accept("**") {
+            Ok(Node::Binary(
+                f64::powf,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let token = self.peek().cloned().ok_or("Unexpected end of function")?;
+        self.position += 1;
+
+        match token {
+            Token::Number(value) => Ok(Node::Constant(value)),
+            Token::Symbol("(") => {
+                let node = self.comparison()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Name(name) => {
+                let name = name
+                    .strip_prefix("np.")
+                    .or_else(|| name.strip_prefix("numpy."))
+                    .unwrap_or(&name);
+
+                if self.accept("(") {
+                    self.call(name)
+                } else {
+                    variable(name)
+                }
+            }
+            Token::Symbol(symbol) => Err(format!("Unexpected `{symbol}`")),
+        }
+    }
+
+    /// Arguments of a call to `name`, whose opening parenthesis was consumed
+    fn call(&mut self, name: &str) -> Result<Node, String> {
+        let mut positional = Vec::new();
+        let mut keywords = Vec::new();
+
+        while !self.accept(")") {
+            if !positional.is_empty() || !keywords.is_empty() {
+                self.expect(",")?;
+            }
+
+            let keyword = match self.tokens.get(self.position..self.position + 2) {
+                Some([Token::Name(keyword), Token::Symbol("=")]) => Some(keyword.clone()),
+                _ => None,
+            };
+
+            match keyword {
+                // Samples are drawn one by one, sized by construction
+                Some(keyword) if keyword == "size" => {
+                    self.position += 2;
+                    self.skip_argument();
+                }
+                Some(keyword) => {
+                    self.position += 2;
+                    keywords.push((keyword, self.comparison()?));
+                }
+                None => positional.push(self.comparison()?),
+            }
+        }
+
+        function(name, positional, keywords)
+    }
+
+    /// Skips an argument, up to the next comma or closing parenthesis of the call
+    fn skip_argument(&mut self) {
+        let mut depth = 0usize;
+
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Symbol("(") => depth += 1,
+                Token::Symbol(")" | ",") if depth == 0 => return,
+                Token::Symbol(")") => depth -= 1,
+                _ => {}
+            }
+
+            self.position += 1;
+        }
+    }
+}
+
+fn variable(name: &str) -> Result<Node, String> {
+    match name {
+        "t" => Ok(Node::Time),
+        "pi" => Ok(Node::Constant(PI)),
+        "e" => Ok(Node::Constant(E)),
+        "inf" => Ok(Node::Constant(f64::INFINITY)),
+        _ => Err(format!("Unknown name {name}")),
+    }
+}
+
+/// Resolves a call to `name`, with its positional and keyword arguments
+fn function(
+    name: &str,
+    positional: Vec<Node>,
+    keywords: Vec<(String, Node)>,
+) -> Result<Node, String> {
+    let generator = name
+        .strip_prefix("rng.")
+        .or_else(|| name.strip_prefix("random."));
+
+    if let Some(generator) = generator {
+        return match generator {
+            "normal" => parameters(name, positional, keywords, [("loc", 0.0), ("scale", 1.0)])
+                .map(|[mean, deviation]| Node::Normal(mean, deviation)),
+            "uniform" => parameters(name, positional, keywords, [("low", 0.0), ("high", 1.0)])
+                .map(|[low, high]| Node::Uniform(low, high)),
+            _ => Err(format!("Unsupported generator {generator}")),
+        };
+    }
+
+    if !keywords.is_empty() {
+        return Err(format!("{name} takes no keyword arguments"));
+    }
+
+    let unary: Option<fn(f64) -> f64> = match name {
+        "sin" => Some(f64::sin),
+        "cos" => Some(f64::cos),
+        "tan" => Some(f64::tan),
+        "arcsin" => Some(f64::asin),
+        "arccos" => Some(f64::acos),
+        "arctan" => Some(f64::atan),
+        "sinh" => Some(f64::sinh),
+        "cosh" => Some(f64::cosh),
+        "tanh" => Some(f64::tanh),
+        "exp" => Some(f64::exp),
+        "log" => Some(f64::ln),
+        "log2" => Some(f64::log2),
+        "log10" => Some(f64::log10),
+        "sqrt" => Some(f64::sqrt),
+        "abs" => Some(f64::abs),
+        "floor" => Some(f64::floor),
+        "ceil" => Some(f64::ceil),
+        "sign" => Some(|x| if x == 0.0 { 0.0 } else { x.signum() }),
+        "square" => Some(|x| x * x),
+        _ => None,
+    };
+
+    let binary: Option<fn(f64, f64) -> f64> = match name {
+        "maximum" => Some(f64::max),
+        "minimum" => Some(f64::min),
+        "arctan2" => Some(f64::atan2),
+        "power" => Some(f64::powf),
+        _ => None,
+    };
+
+    let ternary: Option<fn(f64, f64, f64) -> f64> = match name {
+        "where" => Some(|condition, a, b| if condition == 0.0 { b } else { a }),
+        "clip" => Some(|x, low, high| x.max(low).min(high)),
+        _ => None,
+    };
+
+    let count = positional.len();
+    let arity = |expected: usize| format!("{name} takes {expected} arguments, {count} given");
+
+    if let Some(f) = unary {
+        let [x] = <[Node; 1]>::try_from(positional).map_err(|_| arity(1))?;
+        Ok(Node::Unary(f, Box::new(x)))
+    } else if let Some(f) = binary {
+        let [a, b] = <[Node; 2]>::try_from(positional).map_err(|_| arity(2))?;
+        Ok(Node::Binary(f, Box::new(a), Box::new(b)))
+    } else if let Some(f) = ternary {
+        let arguments = <[Node; 3]>::try_from(positional).map_err(|_| arity(3))?;
+        Ok(Node::Ternary(f, Box::new(arguments)))
+    } else {
+        Err(format!("Unsupported function {name}"))
+    }
+}
+
+/// Two parameters of the generator `name`, given by position or keyword, with defaults
+fn parameters(
+    name: &str,
+    positional: Vec<Node>,
+    mut keywords: Vec<(String, Node)>,
+    defaults: [(&str, f64); 2],
+) -> Result<[Box<Node>; 2], String> {
+    if positional.len() > 2 {
+        return Err(format!("{name} takes at most 2 positional arguments"));
+    }
+
+    let mut positional = positional.into_iter();
+    let parameters = defaults.map(|(parameter, default)| {
+        let keyword = keywords
+            .iter()
+            .position(|(keyword, _)| keyword == parameter)
+            .map(|i| keywords.remove(i).1);
+
+        Box::new(
+            positional
+                .next()
+                .or(keyword)
+                .unwrap_or(Node::Constant(default)),
+        )
+    });
+
+    match keywords.first() {
+        None => Ok(parameters),
+        Some((keyword, _)) => Err(format!("Unexpected argument {keyword} of {name}")),
+    }
+}
+
+/// xorshift64 generator of the random samples
+pub(super) struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Self((u64::from(seed) << 32) | 0x9E37_79B9)
+    }
+
+    /// Uniform in `0.0..1.0`
+    pub(super) fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, through the Box-Muller transform
+    fn gaussian(&mut self) -> f64 {
+        let (u1, u2) = (1.0 - self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+/// Checks that `function` evaluates for a single sample
+pub fn validate(function: &str, seed: u32) -> Result<(), String> {
+    Expression::parse(function)?.value(0.0, &mut Rng::new(seed));
+    Ok(())
+}
+
+/// Evaluates the time and input vectors for the given `sampling_interval`, as numpy would
+pub fn evaluate(
+    parameters: &Parameters,
+    sampling_interval: f32,
+) -> Result<(Vec<f32>, Vec<f32>), String> {
+    let expression = Expression::parse(&parameters.function)?;
+
+    Ok(tabulate(parameters, sampling_interval, |t, rng| {
+        expression.value(t, rng)
+    }))
+}
+
+/// Time vector for the given `sampling_interval`, as numpy would build it, and `value` at each
+/// time, drawing random samples from a generator seeded by [`Parameters::seed`]
+pub(super) fn tabulate(
+    parameters: &Parameters,
+    sampling_interval: f32,
+    mut value: impl FnMut(f64, &mut Rng) -> f64,
+) -> (Vec<f32>, Vec<f32>) {
+    let Parameters {
+        stop_time,
+        samples,
+        seed,
+        time_base,
+        ..
+    } = parameters;
+
+    let mut rng = Rng::new(*seed);
+
+    // Parameters are handed to numpy as their shortest decimal representation
+    let decimal = |x: f32| {
+        x.to_string()
+            .parse::<f64>()
+            .expect("decimal representation")
+    };
+    let interval = decimal(sampling_interval);
+    let offset = if time_base.centered {
+        interval / 2.0
+    } else {
+        0.0
+    };
+
+    let count = if let Some(samples) = samples {
+        *samples as usize
+    } else {
+        // Half an interval past the stop time includes it without risking the next sample
+        let stop = if time_base.endpoint {
+            decimal(*stop_time) + interval / 2.0
+        } else {
+            decimal(*stop_time)
+        };
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = (stop / interval).ceil().max(0.0) as usize;
+        count
+    };
+
+    #[allow(clippy::cast_possible_truncation)]
+    let tensors = (0..count)
+        .map(|i| {
+            let t = i as f64 * interval + offset;
+            (t as f32, value(t, &mut rng) as f32)
+        })
+        .unzip();
+
+    tensors
+}
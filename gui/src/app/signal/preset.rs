@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, fmt};
+
+use super::{native, Parameters};
+
+/// Shape of a built-in input signal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Triangle,
+    /// Linear frequency sweep over the whole run
+    Chirp,
+    /// Uniformly distributed within the amplitude
+    WhiteNoise,
+    /// Starting at the sample nearest to the delay
+    Step,
+    /// Single sample, the one nearest to the delay
+    Impulse,
+}
+
+impl Waveform {
+    pub const ALL: [Self; 7] = [
+        Self::Sine,
+        Self::Square,
+        Self::Triangle,
+        Self::Chirp,
+        Self::WhiteNoise,
+        Self::Step,
+        Self::Impulse,
+    ];
+
+    /// Is it periodic, at [`Preset::frequency`]?
+    pub const fn is_periodic(self) -> bool {
+        matches!(self, Self::Sine | Self::Square | Self::Triangle)
+    }
+
+    /// Does it occur at [`Preset::delay`]?
+    pub const fn is_delayed(self) -> bool {
+        matches!(self, Self::Step | Self::Impulse)
+    }
+}
+
+impl fmt::Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sine => "Sine",
+            Self::Square => "Square",
+            Self::Triangle => "Triangle",
+            Self::Chirp => "Chirp",
+            Self::WhiteNoise => "White noise",
+            Self::Step => "Step",
+            Self::Impulse => "Impulse",
+        })
+    }
+}
+
+/// Built-in input signal, generated without Python
+///
+/// Settings a waveform doesn't use are kept, for when another one is picked
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub waveform: Waveform,
+    /// Peak value
+    pub amplitude: f32,
+    /// Of periodic waveforms, and at the start of chirps, in Hz
+    pub frequency: f32,
+    /// At the end of chirps, in Hz
+    pub end_frequency: f32,
+    /// Time of steps and impulses, in seconds
+    pub delay: f32,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::default(),
+            amplitude: 1.0,
+            frequency: 5.0,
+            end_frequency: 100.0,
+            delay: 0.1,
+        }
+    }
+}
+
+impl Preset {
+    /// Value at time `t` of a run lasting `duration`, sampled every `interval`
+    fn value(&self, t: f64, duration: f64, interval: f64, rng: &mut native::Rng) -> f64 {
+        let amplitude = f64::from(self.amplitude);
+        let frequency = f64::from(self.frequency);
+        let delay = f64::from(self.delay);
+        // Fraction of the current period elapsed
+        let phase = (t * frequency).rem_euclid(1.0);
+
+        amplitude
+            * match self.waveform {
+                Waveform::Sine => (2.0 * PI * phase).sin(),
+                Waveform::Square => {
+                    if phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                // Rising from 0, as sines do
+                Waveform::Triangle => 1.0 - 4.0 * ((phase + 0.25).rem_euclid(1.0) - 0.5).abs(),
+                Waveform::Chirp => {
+                    let rate = (f64::from(self.end_frequency) - frequency) / duration;
+                    (2.0 * PI * (frequency + rate / 2.0 * t) * t).sin()
+                }
+                Waveform::WhiteNoise => 2.0 * rng.uniform() - 1.0,
+                Waveform::Step => f64::from(u8::from(t - delay >= -interval / 2.0)),
+                Waveform::Impulse => f64::from(u8::from((t - delay).abs() < interval / 2.0)),
+            }
+    }
+}
+
+/// Settings in use, uniquely identifying the signal
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            waveform,
+            amplitude,
+            frequency,
+            end_frequency,
+            delay,
+        } = self;
+
+        write!(f, "{waveform} of amplitude {amplitude}")?;
+
+        match waveform {
+            Waveform::Sine | Waveform::Square | Waveform::Triangle => {
+                write!(f, " at {frequency} Hz")
+            }
+            Waveform::Chirp => write!(f, " from {frequency} Hz to {end_frequency} Hz"),
+            Waveform::WhiteNoise => Ok(()),
+            Waveform::Step | Waveform::Impulse => write!(f, " at {delay} s"),
+        }
+    }
+}
+
+/// Evaluates the time and input vectors of `preset` for the given `sampling_interval`
+pub fn evaluate(
+    preset: &Preset,
+    parameters: &Parameters,
+    sampling_interval: f32,
+) -> (Vec<f32>, Vec<f32>) {
+    let interval = f64::from(sampling_interval);
+    let duration = parameters
+        .samples
+        .map_or(f64::from(parameters.stop_time), |samples| {
+            f64::from(samples) * interval
+        });
+
+    native::tabulate(parameters, sampling_interval, |t, rng| {
+        preset.value(t, duration, interval, rng)
+    })
+}
@@ -0,0 +1,333 @@
+use pyo3::{
+    exceptions::{PyKeyboardInterrupt, PyRuntimeError},
+    ffi,
+    sync::{GILOnceCell, GILProtected},
+    types::{IntoPyDict, PyDict, PyList, PyModule},
+    Py, PyErr, PyResult, Python,
+};
+use std::{
+    cell::Cell,
+    os::raw::c_ulong,
+    path::{Path, PathBuf},
+    process::Command,
+    ptr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use super::Parameters;
+
+thread_local! {
+    /// Task the thread's evaluations are part of, if they can be interrupted
+    static TASK: Cell<Option<u64>> = Cell::new(None);
+}
+
+/// Number of the next task, increasing for the whole run
+static TASKS: AtomicU64 = AtomicU64::new(1);
+
+/// Tasks up to this one were abandoned
+static ABANDONED: AtomicU64 = AtomicU64::new(0);
+
+/// Thread running an interruptible evaluation, with its task
+static EVALUATING: GILProtected<Cell<Option<(c_ulong, u64)>>> = GILProtected::new(Cell::new(None));
+
+/// Numbers a new task, for [`interruptible`]
+pub fn task() -> u64 {
+    TASKS.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Runs `task`, whose evaluations are interrupted once [`interrupt`]ed under its number or a
+/// later one
+pub fn interruptible<T>(number: u64, task: impl FnOnce() -> T) -> T {
+    TASK.with(|current| current.set(Some(number)));
+    let result = task();
+    TASK.with(|current| current.set(None));
+
+    result
+}
+
+/// Abandons the tasks numbered up to `number`, raising `KeyboardInterrupt` in their evaluation
+/// in flight, if any, and failing those to come
+///
+/// Waits for the GIL, which evaluations hold between bytecodes
+pub fn interrupt(number: u64) {
+    ABANDONED.fetch_max(number, Ordering::AcqRel);
+
+    Python::with_gil(|py| {
+        if let Some((thread, task)) = EVALUATING.get(py).get() {
+            if task <= number {
+                // SAFETY: the GIL is held, and the exception is a valid type object
+                unsafe {
+                    ffi::PyThreadState_SetAsyncExc(thread as _, ffi::PyExc_KeyboardInterrupt)
+                };
+            }
+        }
+    });
+}
+
+/// Runs `evaluation` with the GIL held, interruptibly if the thread runs a task
+fn interruptibly<T>(py: Python<'_>, evaluation: impl FnOnce() -> PyResult<T>) -> PyResult<T> {
+    let Some(task) = TASK.with(Cell::get) else {
+        return evaluation();
+    };
+
+    // Runs Python code, which may release the GIL, so before the task is checked
+    let thread = py
+        .import("threading")?
+        .call_method0("get_ident")?
+        .extract()?;
+    if task <= ABANDONED.load(Ordering::Acquire) {
+        return Err(PyKeyboardInterrupt::new_err("evaluation abandoned"));
+    }
+
+    EVALUATING.get(py).set(Some((thread, task)));
+    let result = evaluation();
+    EVALUATING.get(py).set(None);
+
+    // Interrupts arriving too late aren't raised by the thread's next evaluation
+    // SAFETY: the GIL is held
+    unsafe { ffi::PyThreadState_SetAsyncExc(thread as _, ptr::null_mut()) };
+
+    result
+}
+
+/// Checks that `function` evaluates for a single sample
+pub fn validate(function: &str, seed: u32) -> PyResult<()> {
+    Python::with_gil(|py| {
+        interruptibly(py, || {
+            let locals = scope(py, seed)?;
+            locals.set_item("t", py.eval("np.array([0])", None, Some(locals))?)?;
+
+            py.eval(function, None, Some(locals)).map(|_| ())
+        })
+    })
+}
+
+/// Evaluates the time and input vectors for the given `sampling_interval`
+pub fn evaluate(parameters: &Parameters, sampling_interval: f32) -> PyResult<(Vec<f32>, Vec<f32>)> {
+    let Parameters {
+        function,
+        stop_time,
+        samples,
+        seed,
+        time_base,
+        ..
+    } = parameters;
+
+    Python::with_gil(|py| {
+        interruptibly(py, || {
+            let locals = scope(py, *seed)?;
+            let t = {
+                // Half an interval past the stop time includes it without risking the next sample
+                let stop = if time_base.endpoint {
+                    format!("{stop_time} + {sampling_interval} / 2")
+                } else {
+                    stop_time.to_string()
+                };
+
+                let offset = if time_base.centered {
+                    format!(" + {sampling_interval} / 2")
+                } else {
+                    String::new()
+                };
+
+                let code = match samples {
+                    Some(samples) => format!("np.arange({samples}) * {sampling_interval}{offset}"),
+                    None => format!("np.arange(0, {stop}, {sampling_interval}){offset}"),
+                };
+                py.eval(&code, None, Some(locals))?
+            };
+
+            locals.set_item("t", t)?;
+            let f = py.eval(function, None, Some(locals))?;
+
+            Ok((t.extract()?, f.extract()?))
+        })
+    })
+}
+
+/// numpy, imported once as it is slow to load
+static NUMPY: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+
+/// Is a supported numpy importable?
+pub fn available() -> bool {
+    Python::with_gil(|py| {
+        numpy(py)
+            .and_then(|numpy| numpy.getattr("__version__")?.extract::<String>())
+            .map_or(false, |version| supported(&version))
+    })
+}
+
+fn numpy(py: Python<'_>) -> PyResult<&PyModule> {
+    NUMPY
+        .get_or_try_init(py, || py.import("numpy").map(Into::into))
+        .map(|numpy| numpy.as_ref(py))
+}
+
+/// Python environment functions are evaluated in
+#[derive(Debug, Clone)]
+pub struct Environment {
+    /// Version of the embedded interpreter, as `major.minor.micro`
+    pub python: String,
+    /// Installation prefix, whose `site-packages` are searched
+    pub prefix: String,
+    /// Installed numpy version, or why it can't be imported
+    pub numpy: Result<String, String>,
+    /// Chosen interpreter whose packages are used, or why they can't be
+    pub interpreter: Option<Result<PathBuf, String>>,
+}
+
+impl Environment {
+    /// Inspects the embedded interpreter, importing numpy, after activating the environment of
+    /// `interpreter` if given
+    pub fn check(interpreter: Option<&Path>) -> PyResult<Self> {
+        let interpreter = interpreter.map(|interpreter| {
+            activate(interpreter)
+                .map(|()| interpreter.to_owned())
+                .map_err(|e| e.to_string())
+        });
+
+        Python::with_gil(|py| {
+            let sys = py.import("sys")?;
+            let version = sys.getattr("version_info")?;
+            let python = format!(
+                "{}.{}.{}",
+                version.getattr("major")?,
+                version.getattr("minor")?,
+                version.getattr("micro")?
+            );
+
+            Ok(Self {
+                python,
+                prefix: sys.getattr("prefix")?.extract()?,
+                numpy: numpy(py)
+                    .and_then(|numpy| numpy.getattr("__version__")?.extract())
+                    .map_err(|e| e.to_string()),
+                interpreter,
+            })
+        })
+    }
+
+    /// Can functions be evaluated?
+    pub fn ready(&self) -> bool {
+        matches!(&self.numpy, Ok(version) if supported(version))
+            && !matches!(self.interpreter, Some(Err(_)))
+    }
+
+    /// Steps to make functions evaluable, if they aren't
+    pub fn remediation(&self) -> Vec<String> {
+        let (major, minor) = crate::MIN_NUMPY;
+        let python = self
+            .python
+            .rsplit_once('.')
+            .map_or("3", |(version, _)| version);
+        let pip =
+            format!("python{python} -m pip install --user --upgrade \"numpy>={major}.{minor}\"");
+
+        if let Some(Err(_)) = &self.interpreter {
+            return vec![
+                format!("Choose an interpreter running Python {python}, as the embedded one does"),
+                "Or use the embedded interpreter's own packages".to_owned(),
+            ];
+        }
+
+        match &self.numpy {
+            Ok(version) if supported(version) => Vec::new(),
+            Ok(version) => vec![
+                format!(
+                    "numpy {version} predates {major}.{minor}, which seeded generators require"
+                ),
+                format!("Upgrade it for Python {python}: {pip}"),
+            ],
+            Err(_) => vec![
+                format!("numpy can't be imported by the embedded Python {python}"),
+                format!("Install it for Python {python}: {pip}"),
+                format!(
+                    "Or, with conda: conda install \"numpy>={major}.{minor}\" \"python={python}\""
+                ),
+                format!("Make sure it is installed under {}", self.prefix),
+                format!("Or choose an interpreter with numpy, running Python {python}"),
+                "Or continue with built-in signals and numpy equivalents".to_owned(),
+            ],
+        }
+    }
+}
+
+/// Makes the packages of the environment `interpreter` belongs to importable, ahead of others
+///
+/// Its Python version must match the embedded one, as compiled packages like numpy are built
+/// for a specific version
+fn activate(interpreter: &Path) -> PyResult<()> {
+    const PROBE: &str = "import site, sys
+print('%d.%d' % sys.version_info[:2])
+print('\\n'.join(site.getsitepackages()))";
+
+    let output = Command::new(interpreter).args(["-c", PROBE]).output()?;
+    if !output.status.success() {
+        return Err(PyErr::new::<PyRuntimeError, _>(format!(
+            "{} failed: {}",
+            interpreter.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let version = lines.next().unwrap_or_default();
+
+    Python::with_gil(|py| {
+        let sys = py.import("sys")?;
+        let info = sys.getattr("version_info")?;
+        let embedded = format!("{}.{}", info.getattr("major")?, info.getattr("minor")?);
+
+        if version != embedded {
+            return Err(PyErr::new::<PyRuntimeError, _>(format!(
+                "{} runs Python {version}, but Python {embedded} is embedded",
+                interpreter.display()
+            )));
+        }
+
+        let site = py.import("site")?;
+        let path: &PyList = sys.getattr("path")?.downcast()?;
+        for directory in lines.rev() {
+            if !path.contains(directory)? {
+                path.insert(0, directory)?;
+            }
+
+            // Processes `.pth` files, as for the interpreter's own site directories
+            site.call_method1("addsitedir", (directory,))?;
+        }
+
+        tracing::info!("Using the packages of {}", interpreter.display());
+        Ok(())
+    })
+}
+
+/// Is numpy `version` recent enough?
+fn supported(version: &str) -> bool {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let version = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+
+    version >= crate::MIN_NUMPY
+}
+
+/// Global scope functions are evaluated in
+///
+/// Both numpy's legacy global generator and a dedicated `rng` generator are seeded,
+/// so `random.normal(size=t.shape)` and `rng.normal(size=t.shape)` are reproducible
+fn scope(py: Python<'_>, seed: u32) -> PyResult<&PyDict> {
+    let numpy = numpy(py)?;
+    let locals = crate::NUMPY_IMPORTS
+        .iter()
+        .map(|&member| (member, numpy.getattr(member).expect("valid member")))
+        .into_py_dict(py);
+
+    locals.set_item("np", numpy)?;
+
+    let random = numpy.getattr("random")?;
+    random.call_method1("seed", (seed,))?;
+    locals.set_item("rng", random.call_method1("default_rng", (seed,))?)?;
+
+    Ok(locals)
+}
@@ -0,0 +1,53 @@
+use super::filter::View;
+
+/// Ready-made experiment, pre-filling the start screen
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+    pub name: &'static str,
+    /// What the experiment shows, for first-time users
+    pub description: &'static str,
+    /// Function of `t` generating the input
+    pub function: &'static str,
+    pub stop_time: f32,
+    /// Percentage of the full scale to normalize the input's peak to, if any
+    pub normalization: Option<f32>,
+    /// Plot shown once connected
+    pub view: View,
+}
+
+impl Template {
+    pub const ALL: [Self; 4] = [
+        Self {
+            name: "Low-pass characterization",
+            description: "Linear chirp sweeping up to 481 Hz, read off the frequency response",
+            function: "sin(2 * pi * (1 + 120 * t) * t)",
+            stop_time: 2.0,
+            normalization: Some(crate::DEFAULT_NORMALIZATION),
+            view: View::Response,
+        },
+        Self {
+            name: "Notch rejection demo",
+            description: "5 Hz tone riding on 50 Hz mains hum, which a notch should remove",
+            function: "sin(2 * pi * 5 * t) + sin(2 * pi * 50 * t)",
+            stop_time: 1.0,
+            normalization: None,
+            view: View::TimeSeries,
+        },
+        Self {
+            name: "Step response",
+            description: "Unit step at 100 ms, showing rise time, overshoot and settling",
+            function: "1.0 * (t >= 0.1)",
+            stop_time: 0.5,
+            normalization: None,
+            view: View::TimeSeries,
+        },
+        Self {
+            name: "Noise suppression",
+            description: "5 Hz tone buried in white noise, to see how much of it is filtered out",
+            function: "sin(2 * pi * 5 * t) + 0.5 * rng.normal(size=t.shape)",
+            stop_time: 2.0,
+            normalization: None,
+            view: View::TimeSeries,
+        },
+    ];
+}
@@ -0,0 +1,322 @@
+use iced::{
+    widget::{column, radio, row, text, text_input},
+    Alignment, Color, Element, Length, Theme,
+};
+use parking_lot::Mutex;
+use plotters::style::{RGBColor, BLACK, CYAN, WHITE, YELLOW};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::config::Config;
+
+/// Appearance in use, as loaded at startup or last chosen
+static CURRENT: Mutex<Settings> = parking_lot::const_mutex(Settings::DEFAULT);
+
+/// Color as red, green and blue components
+pub type Rgb = [u8; 3];
+
+/// Color scheme of the application and its charts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Appearance {
+    /// Cyan and yellow charts on black
+    #[default]
+    Dark,
+    Light,
+    /// Colors of [`Settings::custom`]
+    Custom,
+}
+
+impl Appearance {
+    pub const ALL: [Self; 3] = [Self::Dark, Self::Light, Self::Custom];
+}
+
+impl fmt::Display for Appearance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+            Self::Custom => "Custom",
+        })
+    }
+}
+
+/// User-defined colors of [`Appearance::Custom`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Custom {
+    pub background: Rgb,
+    pub text: Rgb,
+    /// Buttons, sliders and other controls
+    pub accent: Rgb,
+    /// Input traces of charts, and their single traces
+    pub input: Rgb,
+    /// Output traces of charts
+    pub output: Rgb,
+}
+
+impl Custom {
+    const DEFAULT: Self = Self {
+        background: [24, 26, 33],
+        text: [230, 230, 230],
+        accent: [94, 124, 226],
+        input: [0, 255, 255],
+        output: [255, 255, 0],
+    };
+
+    fn get(&self, role: Role) -> Rgb {
+        match role {
+            Role::Background => self.background,
+            Role::Text => self.text,
+            Role::Accent => self.accent,
+            Role::Input => self.input,
+            Role::Output => self.output,
+        }
+    }
+
+    fn set(&mut self, role: Role, color: Rgb) {
+        *match role {
+            Role::Background => &mut self.background,
+            Role::Text => &mut self.text,
+            Role::Accent => &mut self.accent,
+            Role::Input => &mut self.input,
+            Role::Output => &mut self.output,
+        } = color;
+    }
+}
+
+impl Default for Custom {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Persisted appearance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub appearance: Appearance,
+    /// Kept while other appearances are chosen
+    pub custom: Custom,
+}
+
+impl Settings {
+    const DEFAULT: Self = Self {
+        appearance: Appearance::Dark,
+        custom: Custom::DEFAULT,
+    };
+}
+
+/// Colors charts are drawn with
+#[derive(Debug, Clone, Copy)]
+pub struct Colors {
+    pub background: RGBColor,
+    /// Axes, labels and grid lines
+    pub foreground: RGBColor,
+    /// Input traces, and the traces of single-series charts
+    pub input: RGBColor,
+    pub output: RGBColor,
+}
+
+impl Colors {
+    const DARK: Self = Self {
+        background: BLACK,
+        foreground: WHITE,
+        input: CYAN,
+        output: YELLOW,
+    };
+
+    /// Blue and vermillion, as yellow is unreadable on white
+    const LIGHT: Self = Self {
+        background: WHITE,
+        foreground: BLACK,
+        input: RGBColor(0, 114, 178),
+        output: RGBColor(213, 94, 0),
+    };
+}
+
+/// Restores the appearance saved in the settings file
+pub fn load() {
+    *crate::perf::lock(&CURRENT) = Config::load().theme;
+}
+
+pub fn current() -> Settings {
+    *crate::perf::lock(&CURRENT)
+}
+
+/// Switches to `settings`, saving them
+pub fn apply(settings: Settings) {
+    *crate::perf::lock(&CURRENT) = settings;
+    Config::update(|config| config.theme = settings);
+}
+
+/// Theme of the application's widgets
+pub fn theme() -> Theme {
+    let Settings { appearance, custom } = current();
+
+    match appearance {
+        Appearance::Dark => Theme::Dark,
+        Appearance::Light => Theme::Light,
+        Appearance::Custom => {
+            let color = |[r, g, b]: Rgb| Color::from_rgb8(r, g, b);
+
+            Theme::custom(iced::theme::Palette {
+                background: color(custom.background),
+                text: color(custom.text),
+                primary: color(custom.accent),
+                ..iced::theme::Palette::DARK
+            })
+        }
+    }
+}
+
+/// Colors of charts in the current appearance
+pub fn colors() -> Colors {
+    let Settings { appearance, custom } = current();
+
+    match appearance {
+        Appearance::Dark => Colors::DARK,
+        Appearance::Light => Colors::LIGHT,
+        Appearance::Custom => {
+            let color = |[r, g, b]: Rgb| RGBColor(r, g, b);
+
+            Colors {
+                background: color(custom.background),
+                foreground: color(custom.text),
+                input: color(custom.input),
+                output: color(custom.output),
+            }
+        }
+    }
+}
+
+/// Color of a [`Custom`] appearance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Background,
+    Text,
+    Accent,
+    Input,
+    Output,
+}
+
+impl Role {
+    const ALL: [Self; 5] = [
+        Self::Background,
+        Self::Text,
+        Self::Accent,
+        Self::Input,
+        Self::Output,
+    ];
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Background => "Background",
+            Self::Text => "Text",
+            Self::Accent => "Accent",
+            Self::Input => "Input trace",
+            Self::Output => "Output trace",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    AppearanceSelected(Appearance),
+    ColorEdited(Role, String),
+}
+
+/// Editor of the appearance, applying changes as they're made
+pub struct Panel {
+    settings: Settings,
+    /// Custom colors as typed, by [`Role`], applied once valid
+    drafts: [String; Role::ALL.len()],
+}
+
+impl Panel {
+    pub fn new() -> Self {
+        let settings = current();
+
+        Self {
+            settings,
+            drafts: Role::ALL.map(|role| hex(settings.custom.get(role))),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::AppearanceSelected(appearance) => self.settings.appearance = appearance,
+
+            Message::ColorEdited(role, draft) => {
+                let parsed = parse(&draft);
+                self.drafts[role as usize] = draft;
+
+                let Some(color) = parsed else {
+                    return;
+                };
+                self.settings.custom.set(role, color);
+            }
+        }
+
+        apply(self.settings);
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let appearances = row(Appearance::ALL
+            .into_iter()
+            .map(|appearance| {
+                radio(
+                    appearance.to_string(),
+                    appearance,
+                    Some(self.settings.appearance),
+                    Message::AppearanceSelected,
+                )
+                .into()
+            })
+            .collect())
+        .spacing(20);
+
+        let mut content = column![text("Appearance").size(24), appearances].spacing(10);
+
+        if self.settings.appearance == Appearance::Custom {
+            for role in Role::ALL {
+                let draft = &self.drafts[role as usize];
+
+                let mut editor = row![
+                    text(role.to_string()).width(Length::Fixed(150.0)),
+                    text_input("#RRGGBB", draft)
+                        .on_input(move |draft| Message::ColorEdited(role, draft))
+                        .width(Length::Fixed(120.0)),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center);
+
+                if parse(draft).is_none() {
+                    editor =
+                        editor.push(text("Expected #RRGGBB").style(Color::from_rgb(1.0, 0.6, 0.0)));
+                }
+
+                content = content.push(editor);
+            }
+        }
+
+        content.into()
+    }
+}
+
+/// `#RRGGBB` code of `color`
+fn hex([r, g, b]: Rgb) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
+/// Color of a `#RRGGBB` code, the `#` being optional
+fn parse(code: &str) -> Option<Rgb> {
+    let digits = code.trim().trim_start_matches('#');
+    if digits.len() != 6 || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let component = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some([component(0)?, component(2)?, component(4)?])
+}
@@ -2,9 +2,14 @@ use iced::{
     window::{self, PlatformSpecific},
     Application, Result, Settings,
 };
+use tracing_subscriber::{filter::LevelFilter, prelude::*};
 
 mod app;
 use app::OnlineFiltering;
+mod perf;
+
+#[global_allocator]
+static ALLOCATOR: perf::CountingAllocator = perf::CountingAllocator;
 
 /// Graph refresh rate when streaming
 pub const FPS: u64 = 60;
@@ -12,25 +17,155 @@ pub const FPS: u64 = 60;
 pub const BAUD_RATE: u32 = 115_200;
 /// Minimum number of points to visualize on graph
 pub const MIN_WINDOW_SIZE: usize = 32;
+/// Factor the static window is shrunk or grown by per line scrolled on the graph
+pub const ZOOM_FACTOR: f32 = 1.25;
 /// Number of points to look-back when displaying streaming data
 pub const STREAMING_WINDOW_SIZE: usize = 384;
+/// Number of latest samples transformed by the spectrum view while streaming
+pub const SPECTRUM_WINDOW_SIZE: usize = 2048;
+/// Period of metric recomputation while streaming, in milliseconds
+pub const ANALYSIS_PERIOD: u64 = 1000;
+/// Per-sample markers and index tooltips are shown below this many visible points
+pub const MARKER_THRESHOLD: usize = 200;
+/// Oldest numpy version supported, as `(major, minor)`, providing seeded generators
+pub const MIN_NUMPY: (u32, u32) = (1, 17);
 /// Useful numpy functions to bring to the global scope
-pub const NUMPY_IMPORTS: &[&str] = &["abs", "sin", "cos", "pi"];
-/// End of transmission marker (Equal to [`f32::NaN`])
-pub const EOT: &[u8] = &(0x7F_C0_00_00u32.to_le_bytes());
+pub const NUMPY_IMPORTS: &[&str] = &["abs", "sin", "cos", "pi", "random"];
+/// Start of the frames both ends exchange once the handshake is done, each followed by the
+/// payload's length and the frame's sequence number as `u16`s, the payload, and the CRC-16/
+/// CCITT-FALSE of all but the start. Payloads hold whole words, samples or sideband reports,
+/// which are never split across frames, and an empty payload, the EOT, ends the transmission
+pub const FRAME_START: &[u8] = &[0xA5, 0x5A];
+/// Longest accepted frame payload, in bytes. Longer ones are assumed to be corrupted
+pub const MAX_FRAME_PAYLOAD: usize = 2048;
+/// Most samples sent per frame, when not sent in chunks or blocks
+pub const FRAME_SAMPLES: usize = 64;
+/// Captures larger than this many bytes are stored in a memory-mapped file
+pub const MMAP_THRESHOLD: usize = 256 * 1024 * 1024;
+/// Size of the receiver's read buffer, in bytes
+pub const RX_BUFFER_SIZE: usize = 4096;
+/// Number of latest frames kept for the protocol inspector
+pub const INSPECTED_FRAMES: usize = 100;
+/// Reads of up to [`RX_BUFFER_SIZE`] bytes queued between the receiver's reader and its
+/// decoder, letting the device be read while the decoder waits on locks
+pub const RX_QUEUE_LENGTH: usize = 256;
+/// Telemetry report marker, followed by a channel and a value (A [`f32::NaN`] with payload 1)
+pub const TELEMETRY: &[u8] = &(0x7F_C0_00_01u32.to_le_bytes());
+/// Device log message marker, followed by a severity, a length and the message (A
+/// [`f32::NaN`] with payload 2)
+pub const LOG: &[u8] = &(0x7F_C0_00_02u32.to_le_bytes());
+/// Time synchronization marker, followed by a timestamp, and by a sample index in replies (A
+/// [`f32::NaN`] with payload 3)
+pub const TIME_SYNC: &[u8] = &(0x7F_C0_00_03u32.to_le_bytes());
+/// Period of time synchronization exchanges, when enabled, in milliseconds
+pub const TIME_SYNC_PERIOD: u64 = 500;
+/// Flag of the sampling frequency sent in the handshake, announcing that the device's output
+/// rate follows as another `u32`
+pub const MULTI_RATE: u32 = 1 << 31;
+/// Flag of the sampling frequency sent in the handshake, announcing that the device processes
+/// blocks, whose size in inputs follows as another `u32` (After the output rate, if any)
+pub const BLOCK_MODE: u32 = 1 << 30;
+/// Flag of the sampling frequency sent in the handshake, announcing that each output is a frame
+/// of several interleaved channels, whose count follows as another `u32` (After the block size,
+/// if any). The first channel is the filtered output
+pub const MULTI_CHANNEL: u32 = 1 << 29;
+/// How long to wait for a processed block before giving up, in milliseconds
+pub const BLOCK_TIMEOUT: u64 = 5000;
+/// How long a run being shut down keeps receiving outputs in flight until the device's EOT,
+/// before the link is closed regardless, in milliseconds
+pub const EOT_DRAIN_TIMEOUT: u64 = 500;
+/// Number of pattern words sent by the round-trip self-test
+pub const SELF_TEST_LENGTH: usize = 4096;
+/// Number of single words timed by the loopback test to measure latency
+pub const LOOPBACK_PINGS: usize = 32;
+/// How long the self-test waits for echoed words before giving up, in milliseconds
+pub const SELF_TEST_TIMEOUT: u64 = 1000;
+/// Number of samples of the pattern repeated by soak tests
+pub const SOAK_PERIOD: usize = 1024;
+/// Largest difference of a soak test's period of outputs from the reference, relative to the
+/// full scale
+pub const SOAK_TOLERANCE: f32 = 1e-4;
+/// Period of soak test status logs, in seconds
+pub const SOAK_STATUS_PERIOD: u64 = 600;
+/// Duration of soak tests, in hours, unless changed
+pub const DEFAULT_SOAK_HOURS: u8 = 8;
 /// Serial synchronization marker
 pub const SYN: &[u8] = b"SYN\x00";
-/// Name of the file to export filtered data to
-pub const FILENAME: &str = "filtered.json";
+/// Marker framing the device's handshake response, preceding its announced sampling frequency
+pub const ACK: &[u8] = b"ACK\x00";
+/// How long the first handshake attempt scans for [`ACK`] past banners and bootloader output,
+/// in milliseconds, doubling with each attempt
+pub const HANDSHAKE_TIMEOUT: u64 = 500;
+/// Most bytes discarded before [`ACK`] until the handshake gives up
+pub const HANDSHAKE_SCAN_LIMIT: usize = 4096;
+/// Time allowed to connect to a networked device, in milliseconds
+pub const TCP_CONNECT_TIMEOUT: u64 = 2000;
+/// Application version, recorded in exported and settings files
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Name of the file to export filtered data to, without extension
+pub const FILENAME: &str = "filtered";
+/// Name of the file to export chart images to
+pub const IMAGE_FILENAME: &str = "filtered.png";
+/// Name of the file to export animations of a run to
+pub const ANIMATION_FILENAME: &str = "filtered.gif";
+/// Frame rate of exported animations
+pub const ANIMATION_FPS: u32 = 20;
+/// Exported animations are sped up past this many frames
+pub const ANIMATION_MAX_FRAMES: usize = 300;
+/// Name of the file to export impulse response coefficients to
+pub const COEFFICIENTS_FILENAME: &str = "coefficients.txt";
+/// Percentage of the full scale inputs are normalized to, when enabled
+pub const DEFAULT_NORMALIZATION: f32 = 80.0;
+/// Run length in samples, when entered as a sample count
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4096;
+/// Window size of the always-on-top mini monitor
+pub const MINI_MONITOR_SIZE: (u32, u32) = (420, 260);
+/// Where outputs are published over OSC, when enabled
+pub const DEFAULT_OSC_TARGET: &str = "127.0.0.1:9000";
+/// How long a hotplugged device is given to register its serial port, in milliseconds
+pub const HOTPLUG_SETTLE: u64 = 250;
+/// Period of port enumeration where hotplug events are unavailable, in seconds
+pub const PORT_POLLING_PERIOD: u64 = 3;
+/// Number of recent sessions and files to remember
+pub const RECENT_LIMIT: usize = 8;
+/// Number of validated functions to remember
+pub const FUNCTION_HISTORY_LIMIT: usize = 32;
+/// Rate at which functions are evaluated to estimate their bandwidth, relative to the device's
+pub const BANDWIDTH_OVERSAMPLING: u32 = 4;
+/// Most samples evaluated to estimate a function's bandwidth
+pub const BANDWIDTH_WINDOW: u32 = 65_536;
+/// Number of recent log events included in crash reports
+pub const CRASH_LOG_LINES: usize = 500;
+/// Name of the file to save crash diagnostics to
+pub const CRASH_FILENAME: &str = "crash.zip";
 
 pub fn main() -> Result {
-    tracing_subscriber::fmt::init();
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer())
+        .with(app::crash::Recorder)
+        .init();
     pyo3::prepare_freethreaded_python();
 
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if_eq("--headless").is_some() {
+        if let Err(e) = app::headless::run(args) {
+            tracing::error!("Headless run failed: {e}");
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    app::crash::install();
+    let geometry = app::Geometry::load();
     OnlineFiltering::run(Settings {
         antialiasing: true,
         window: window::Settings {
-            min_size: Some((400, 600)),
+            size: geometry.size,
+            position: geometry.position(),
+            // Down to the mini monitor's height
+            min_size: Some((400, MINI_MONITOR_SIZE.1)),
             platform_specific: PlatformSpecific {
                 title_hidden: true,
                 titlebar_transparent: true,
@@ -38,6 +173,8 @@ pub fn main() -> Result {
             },
             ..Default::default()
         },
+        // Closing is handled once the run's workers are torn down
+        exit_on_close_request: false,
         ..Default::default()
     })
 }
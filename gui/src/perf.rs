@@ -0,0 +1,164 @@
+use iced::{
+    widget::{row, text},
+    Element,
+};
+use parking_lot::{Mutex, MutexGuard};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Number of allocations since startup
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of allocated bytes since startup
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Total time spent waiting for instrumented locks
+static LOCK_WAIT_NANOS: AtomicU64 = AtomicU64::new(0);
+/// Bytes waiting in the serial input buffer, as of the last check
+static RX_BACKLOG: AtomicUsize = AtomicUsize::new(0);
+/// Time taken to draw the last chart frame
+static FRAME_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator which counts allocations
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+fn count(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+}
+
+/// Locks `mutex`, accounting for the time spent waiting on it
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    let start = Instant::now();
+    let guard = mutex.lock();
+    LOCK_WAIT_NANOS.fetch_add(nanos(start.elapsed()), Ordering::Relaxed);
+
+    guard
+}
+
+pub fn record_backlog(bytes: usize) {
+    RX_BACKLOG.store(bytes, Ordering::Relaxed);
+}
+
+pub fn record_frame(duration: Duration) {
+    FRAME_NANOS.store(nanos(duration), Ordering::Relaxed);
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Cumulative counters at a point in time
+#[derive(Clone, Copy)]
+struct Sample {
+    at: Instant,
+    allocations: usize,
+    allocated_bytes: usize,
+    lock_wait: u64,
+}
+
+impl Sample {
+    fn now() -> Self {
+        Self {
+            at: Instant::now(),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            lock_wait: LOCK_WAIT_NANOS.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Performance heads-up display
+pub struct Hud {
+    previous: Sample,
+    /// Last frame draw time
+    frame_time: Duration,
+    /// Bytes waiting to be read by the receiver
+    backlog: usize,
+    /// Lock wait time per second
+    lock_wait: Duration,
+    /// Allocations per second
+    allocations: f64,
+    /// Allocated bytes per second
+    allocated_bytes: f64,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self {
+            previous: Sample::now(),
+            frame_time: Duration::ZERO,
+            backlog: 0,
+            lock_wait: Duration::ZERO,
+            allocations: 0.0,
+            allocated_bytes: 0.0,
+        }
+    }
+}
+
+impl Hud {
+    /// Refreshes the displayed rates
+    pub fn tick(&mut self) {
+        let current = Sample::now();
+        let elapsed = (current.at - self.previous.at).as_secs_f64();
+
+        if elapsed > 0.0 {
+            let lock_wait = (current.lock_wait - self.previous.lock_wait) as f64 / elapsed;
+
+            self.allocations = (current.allocations - self.previous.allocations) as f64 / elapsed;
+            self.allocated_bytes =
+                (current.allocated_bytes - self.previous.allocated_bytes) as f64 / elapsed;
+            self.lock_wait = Duration::from_secs_f64(lock_wait / 1e9);
+        }
+
+        self.frame_time = Duration::from_nanos(FRAME_NANOS.load(Ordering::Relaxed));
+        self.backlog = RX_BACKLOG.load(Ordering::Relaxed);
+        self.previous = current;
+    }
+
+    pub fn view<'a, Message: 'a>(&self) -> Element<'a, Message> {
+        let Self {
+            frame_time,
+            backlog,
+            lock_wait,
+            allocations,
+            allocated_bytes,
+            ..
+        } = self;
+
+        row![
+            text(format!("Frame: {frame_time:.2?}")),
+            text(format!("RX backlog: {backlog} B")),
+            text(format!("Lock wait: {lock_wait:.2?}/s")),
+            text(format!(
+                "Allocations: {allocations:.0}/s ({:.1} KiB/s)",
+                allocated_bytes / 1024.0
+            )),
+        ]
+        .spacing(20)
+        .padding([5, 15])
+        .into()
+    }
+}